@@ -0,0 +1,297 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    cache::Route,
+    diagnostics,
+    error::KiwiToolError,
+    input::{self, ToolInput},
+    store::SearchStore,
+};
+
+#[derive(Deserialize, JsonSchema)]
+struct RotateApiKeyArgs {
+    /// The new Tequila API key
+    api_key: String,
+}
+
+impl ToolInput for RotateApiKeyArgs {
+    fn validate(&self) -> Result<()> {
+        if self.api_key.is_empty() {
+            return Err(KiwiToolError::validation("api_key must not be empty").into());
+        }
+        Ok(())
+    }
+}
+
+/// Swaps the Tequila API key at runtime after checking the new one works.
+pub struct RotateApiKeyTool {
+    client: Arc<KiwiClient>,
+}
+
+impl RotateApiKeyTool {
+    pub fn new(client: Arc<KiwiClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for RotateApiKeyTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing RotateApiKeyTool");
+        let args: RotateApiKeyArgs = input::parse(arguments)?;
+
+        let api_key = args.api_key.as_str();
+
+        self.client.verify_api_key(api_key).await.map_err(|err| {
+            KiwiToolError::validation(format!("The new API key was rejected: {}", err))
+        })?;
+
+        self.client.set_api_key(api_key);
+        log::info!("Kiwi API key rotated");
+
+        Ok(vec![ToolContent::Text {
+            text: "API key rotated. Update the configuration too, or the old key returns on the next reload."
+                .into(),
+        }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "rotate_api_key".into(),
+            description: Some(
+                "Admin: replace the Kiwi API key used by the server without restarting it".into(),
+            ),
+            input_schema: input::schema::<RotateApiKeyArgs>(),
+        }
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct CacheStatsArgs {}
+
+/// Reports what the response cache holds and how well it is doing.
+pub struct CacheStatsTool {
+    client: Arc<KiwiClient>,
+}
+
+impl ToolInput for CacheStatsArgs {}
+
+impl CacheStatsTool {
+    pub fn new(client: Arc<KiwiClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for CacheStatsTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing CacheStatsTool");
+        let _: CacheStatsArgs = input::parse(arguments)?;
+
+        let cache = self.client.cache();
+        if !cache.is_enabled() {
+            return Ok(vec![ToolContent::Text {
+                text: "The response cache is disabled.".into(),
+            }]);
+        }
+
+        let stats = cache.stats();
+
+        let mut result = format!("Cache entries: {}\n", stats.entries);
+        result.push_str(&format!(
+            "Lookups: {} (hits: {}, misses: {}, hit ratio: {})\n",
+            stats.hits + stats.misses,
+            stats.hits,
+            stats.misses,
+            stats
+                .hit_ratio()
+                .map(|ratio| format!("{:.1}%", ratio * 100.0))
+                .unwrap_or_else(|| "n/a".to_string())
+        ));
+        result.push_str(&format!(
+            "Approximate memory usage: {}\n",
+            format_bytes(stats.size)
+        ));
+
+        if let Some(disk) = &stats.disk {
+            result.push_str(&format!(
+                "On disk: {} entries, {} in {}\n",
+                disk.entries,
+                format_bytes(disk.size as usize),
+                disk.dir.display()
+            ));
+        }
+
+        if !stats.routes.is_empty() {
+            result.push_str("Cached routes:\n");
+            for route in &stats.routes {
+                result.push_str(&format!(
+                    "  {} → {}: {} entries, newest {}s old\n",
+                    route.route.fly_from,
+                    route.route.fly_to,
+                    route.entries,
+                    route.newest.as_secs()
+                ));
+            }
+        }
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "cache_stats".into(),
+            description: Some(
+                "Admin: show response cache entry counts, hit ratio, memory usage and cached routes"
+                    .into(),
+            ),
+            input_schema: input::schema::<CacheStatsArgs>(),
+        }
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct CacheClearArgs {
+    /// Only clear searches from this location, exactly as it was searched (requires fly_to)
+    fly_from: Option<String>,
+    /// Only clear searches to this location, exactly as it was searched (requires fly_from)
+    fly_to: Option<String>,
+}
+
+impl ToolInput for CacheClearArgs {
+    fn validate(&self) -> Result<()> {
+        if self.fly_from.is_some() != self.fly_to.is_some() {
+            return Err(
+                KiwiToolError::validation("Provide both fly_from and fly_to, or neither").into(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Drops cached responses, either all of them or those for a single route.
+pub struct CacheClearTool {
+    client: Arc<KiwiClient>,
+}
+
+impl CacheClearTool {
+    pub fn new(client: Arc<KiwiClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for CacheClearTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing CacheClearTool");
+        let args: CacheClearArgs = input::parse(arguments)?;
+
+        let route = match (args.fly_from, args.fly_to) {
+            (Some(fly_from), Some(fly_to)) => Some(Route { fly_from, fly_to }),
+            _ => None,
+        };
+
+        let removed = self.client.cache().clear(route.as_ref());
+
+        let text = match route {
+            Some(route) => format!(
+                "Removed {} cached entries for {} → {}.",
+                removed, route.fly_from, route.fly_to
+            ),
+            None => format!("Removed all {} cached entries.", removed),
+        };
+
+        Ok(vec![ToolContent::Text { text }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "cache_clear".into(),
+            description: Some(
+                "Admin: clear the response cache, optionally only for one route".into(),
+            ),
+            input_schema: input::schema::<CacheClearArgs>(),
+        }
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct PurgeUserDataArgs {
+    /// Must be true to confirm the deletion
+    confirm: bool,
+}
+
+impl ToolInput for PurgeUserDataArgs {
+    fn validate(&self) -> Result<()> {
+        if !self.confirm {
+            return Err(KiwiToolError::validation("This permanently deletes the search history, saved searches, stored results, cached responses, request counts and diagnostic reports; call again with confirm: true").into());
+        }
+        Ok(())
+    }
+}
+
+/// Deletes everything the server has stored about past usage: the search
+/// history, saved searches, stored results, cached responses, request
+/// counts, usage statistics and diagnostic reports.
+pub struct PurgeUserDataTool {
+    client: Arc<KiwiClient>,
+    store: Arc<SearchStore>,
+}
+
+impl PurgeUserDataTool {
+    pub fn new(client: Arc<KiwiClient>, store: Arc<SearchStore>) -> Self {
+        Self { client, store }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for PurgeUserDataTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing PurgeUserDataTool");
+        let _: PurgeUserDataArgs = input::parse(arguments)?;
+
+        let searches = self.client.history().purge()?;
+        let bookmarks = self.client.bookmarks().purge()?;
+        let results = self.store.clear();
+        let cached = self.client.cache().clear(None);
+        self.client.budget().purge()?;
+        self.client.usage().reset();
+        let reports = diagnostics::purge()?;
+
+        log::info!("Purged user data");
+
+        Ok(vec![ToolContent::Text {
+            text: format!(
+                "Deleted {} searches from the history, {} saved searches, {} stored results, {} cached responses and {} diagnostic reports, and reset the request counts and usage statistics.",
+                searches, bookmarks, results, cached, reports
+            ),
+        }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "purge_user_data".into(),
+            description: Some(
+                "Admin: permanently delete all stored user data (search history, saved searches, stored results, cached responses, request counts, usage statistics and diagnostic reports)"
+                    .into(),
+            ),
+            input_schema: input::schema::<PurgeUserDataArgs>(),
+        }
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    match bytes {
+        0..1024 => format!("{} B", bytes),
+        1024..1_048_576 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
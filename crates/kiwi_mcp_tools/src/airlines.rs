@@ -0,0 +1,125 @@
+//! A directory of common carriers by IATA code, with whether each is a
+//! low-cost carrier. Alliance membership comes from [`alliances`].
+//!
+//! The list is maintained by hand and covers major airlines only; it is
+//! not the Kiwi carriers directory, so codes missing from it are shown as
+//! they are.
+
+use crate::alliances;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Airline {
+    pub code: &'static str,
+    pub name: &'static str,
+    /// Low-cost carrier, as opposed to full service.
+    pub low_cost: bool,
+}
+
+impl Airline {
+    pub fn alliance(&self) -> Option<&'static str> {
+        alliances::of(self.code)
+    }
+}
+
+const fn airline(code: &'static str, name: &'static str, low_cost: bool) -> Airline {
+    Airline {
+        code,
+        name,
+        low_cost,
+    }
+}
+
+const AIRLINES: &[Airline] = &[
+    airline("A3", "Aegean Airlines", false),
+    airline("AA", "American Airlines", false),
+    airline("AC", "Air Canada", false),
+    airline("AF", "Air France", false),
+    airline("AI", "Air India", false),
+    airline("AK", "AirAsia", true),
+    airline("AM", "Aeroméxico", false),
+    airline("AR", "Aerolíneas Argentinas", false),
+    airline("AS", "Alaska Airlines", false),
+    airline("AT", "Royal Air Maroc", false),
+    airline("AV", "Avianca", false),
+    airline("AY", "Finnair", false),
+    airline("AZ", "ITA Airways", false),
+    airline("B6", "JetBlue", true),
+    airline("BA", "British Airways", false),
+    airline("BR", "EVA Air", false),
+    airline("BT", "airBaltic", false),
+    airline("CA", "Air China", false),
+    airline("CI", "China Airlines", false),
+    airline("CM", "Copa Airlines", false),
+    airline("CX", "Cathay Pacific", false),
+    airline("D8", "Norwegian Air Sweden", true),
+    airline("DL", "Delta Air Lines", false),
+    airline("DY", "Norwegian", true),
+    airline("EI", "Aer Lingus", false),
+    airline("EK", "Emirates", false),
+    airline("ET", "Ethiopian Airlines", false),
+    airline("EW", "Eurowings", true),
+    airline("EY", "Etihad Airways", false),
+    airline("F9", "Frontier Airlines", true),
+    airline("FI", "Icelandair", false),
+    airline("FR", "Ryanair", true),
+    airline("FZ", "flydubai", true),
+    airline("G3", "GOL", true),
+    airline("GA", "Garuda Indonesia", false),
+    airline("HV", "Transavia", true),
+    airline("IB", "Iberia", false),
+    airline("JL", "Japan Airlines", false),
+    airline("JQ", "Jetstar", true),
+    airline("JU", "Air Serbia", false),
+    airline("KE", "Korean Air", false),
+    airline("KL", "KLM", false),
+    airline("KQ", "Kenya Airways", false),
+    airline("LH", "Lufthansa", false),
+    airline("LO", "LOT Polish Airlines", false),
+    airline("LS", "Jet2", true),
+    airline("LX", "Swiss", false),
+    airline("ME", "Middle East Airlines", false),
+    airline("MH", "Malaysia Airlines", false),
+    airline("MS", "EgyptAir", false),
+    airline("MU", "China Eastern", false),
+    airline("NH", "ANA", false),
+    airline("NK", "Spirit Airlines", true),
+    airline("NZ", "Air New Zealand", false),
+    airline("OK", "Czech Airlines", false),
+    airline("OS", "Austrian Airlines", false),
+    airline("OU", "Croatia Airlines", false),
+    airline("OZ", "Asiana Airlines", false),
+    airline("PC", "Pegasus Airlines", true),
+    airline("QF", "Qantas", false),
+    airline("QR", "Qatar Airways", false),
+    airline("RJ", "Royal Jordanian", false),
+    airline("RO", "TAROM", false),
+    airline("SA", "South African Airways", false),
+    airline("SK", "SAS", false),
+    airline("SN", "Brussels Airlines", false),
+    airline("SQ", "Singapore Airlines", false),
+    airline("SV", "Saudia", false),
+    airline("TG", "Thai Airways", false),
+    airline("TK", "Turkish Airlines", false),
+    airline("TO", "Transavia France", true),
+    airline("TP", "TAP Air Portugal", false),
+    airline("TR", "Scoot", true),
+    airline("U2", "easyJet", true),
+    airline("UA", "United Airlines", false),
+    airline("UL", "SriLankan Airlines", false),
+    airline("UX", "Air Europa", false),
+    airline("V7", "Volotea", true),
+    airline("VN", "Vietnam Airlines", false),
+    airline("VS", "Virgin Atlantic", false),
+    airline("VY", "Vueling", true),
+    airline("W6", "Wizz Air", true),
+    airline("WN", "Southwest Airlines", true),
+    airline("WS", "WestJet", false),
+    airline("WY", "Oman Air", false),
+    airline("XQ", "SunExpress", true),
+    airline("ZH", "Shenzhen Airlines", false),
+];
+
+/// Every airline in the directory, by code.
+pub fn all() -> &'static [Airline] {
+    AIRLINES
+}
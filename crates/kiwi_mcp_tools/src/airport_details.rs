@@ -0,0 +1,143 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    error::KiwiToolError,
+    input::{self, ToolInput},
+    locations::{self, Airport},
+};
+
+#[derive(Deserialize, JsonSchema)]
+struct AirportDetailsArgs {
+    /// IATA airport code (e.g., 'LHR')
+    code: String,
+}
+
+impl ToolInput for AirportDetailsArgs {
+    fn validate(&self) -> Result<()> {
+        let code = self.code.trim();
+        if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(KiwiToolError::validation(format!(
+                "{:?} is not an IATA airport code; use three letters, e.g. LHR",
+                self.code
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Describes an airport from the locations API: its name, city, country,
+/// timezone and coordinates, and the other airports serving its city.
+pub struct AirportDetailsTool {
+    client: Arc<KiwiClient>,
+}
+
+impl AirportDetailsTool {
+    pub fn new(client: Arc<KiwiClient>) -> Self {
+        Self { client }
+    }
+
+    /// The other airports of `airport`'s city, nearest first.
+    async fn alternatives(&self, airport: &Airport) -> Result<Vec<Airport>> {
+        if airport.city.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut alternatives: Vec<Airport> = self
+            .client
+            .city_airports(&airport.city, &airport.city_code)
+            .await?
+            .into_iter()
+            .filter(|other| other.code != airport.code)
+            .collect();
+
+        let distance = |other: &Airport| match (airport.position, other.position) {
+            (Some(from), Some(to)) => locations::distance_km(from, to),
+            _ => f64::INFINITY,
+        };
+        alternatives.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+        Ok(alternatives)
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for AirportDetailsTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing AirportDetailsTool");
+        let args: AirportDetailsArgs = input::parse(arguments)?;
+        let code = args.code.trim().to_ascii_uppercase();
+
+        let response = self.client.locations_of_type(&code, "airport", 5).await?;
+        let Some(airport) = locations::parse_airports(&response)
+            .into_iter()
+            .find(|airport| airport.code == code)
+        else {
+            let candidates = locations::parse(&response);
+            let suggestions: Vec<String> = locations::closest(&code, &candidates, 3)
+                .into_iter()
+                .map(|location| format!("{} ({})", location.code, location.name))
+                .collect();
+            let mut message = format!("Unknown airport code '{}'", code);
+            if !suggestions.is_empty() {
+                message.push_str(&format!("; did you mean {}?", suggestions.join(" or ")));
+            }
+            return Err(KiwiToolError::validation(message).into());
+        };
+
+        let mut result = format!("{} ({})\n", airport.name, airport.code);
+        result.push_str(&format!(
+            "City: {} ({}), {} ({})\n",
+            airport.city, airport.city_code, airport.country, airport.country_code
+        ));
+        if let Some(timezone) = &airport.timezone {
+            result.push_str(&format!("Timezone: {}\n", timezone));
+        }
+        if let Some((lon, lat)) = airport.position {
+            result.push_str(&format!("Coordinates: {:.4}, {:.4}\n", lat, lon));
+        }
+
+        let alternatives = self.alternatives(&airport).await?;
+        if alternatives.is_empty() {
+            result.push_str(&format!("No other airports serve {}.\n", airport.city));
+        } else {
+            result.push_str(&format!(
+                "Other airports serving {} (search them all with {}):\n",
+                airport.city, airport.city_code
+            ));
+            for alternative in alternatives {
+                let distance = match (airport.position, alternative.position) {
+                    (Some(from), Some(to)) => {
+                        format!(", {:.0} km away", locations::distance_km(from, to))
+                    }
+                    _ => String::new(),
+                };
+                result.push_str(&format!(
+                    "  {} {}{}\n",
+                    alternative.code, alternative.name, distance
+                ));
+            }
+        }
+
+        self.client.push_quota_warning(&mut result);
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "airport_details".into(),
+            description: Some(
+                "Details of an airport by IATA code: full name, city, country, timezone, coordinates and the other airports serving the same city"
+                    .into(),
+            ),
+            input_schema: input::schema::<AirportDetailsArgs>(),
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Member airlines of the three global alliances, by IATA code.
+
+const STAR_ALLIANCE: &[&str] = &[
+    "A3", "AC", "AI", "AV", "BR", "CA", "CM", "ET", "LH", "LO", "LX", "MS", "NH", "NZ", "OS", "OU",
+    "OZ", "SA", "SN", "SQ", "TG", "TP", "TK", "UA", "ZH",
+];
+
+const ONEWORLD: &[&str] = &[
+    "AA", "AS", "AY", "BA", "CX", "FJ", "IB", "JL", "MH", "QF", "QR", "RJ", "UL", "WY", "AT",
+];
+
+const SKYTEAM: &[&str] = &[
+    "AF", "AM", "AR", "DL", "GA", "KE", "KL", "KQ", "ME", "MU", "RO", "SV", "UX", "VN", "VS", "CI",
+    "MF", "SK",
+];
+
+/// Airlines belonging to `alliance` (`star_alliance`, `oneworld` or `skyteam`).
+pub fn members(alliance: &str) -> Option<&'static [&'static str]> {
+    match alliance.to_lowercase().replace([' ', '-'], "_").as_str() {
+        "star_alliance" | "star" => Some(STAR_ALLIANCE),
+        "oneworld" => Some(ONEWORLD),
+        "skyteam" => Some(SKYTEAM),
+        _ => None,
+    }
+}
+
+/// Name of the alliance `airline` belongs to, if any.
+pub fn of(airline: &str) -> Option<&'static str> {
+    [
+        ("Star Alliance", STAR_ALLIANCE),
+        ("oneworld", ONEWORLD),
+        ("SkyTeam", SKYTEAM),
+    ]
+    .into_iter()
+    .find(|(_, members)| members.contains(&airline))
+    .map(|(name, _)| name)
+}
@@ -0,0 +1,58 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{KiwiClient, bookmarks::SavedSearch, error::KiwiToolError, history::HistoryEntry};
+
+/// Bumped when the archive format changes incompatibly.
+const ARCHIVE_VERSION: u32 = 1;
+
+/// Everything the server keeps about its users (saved searches and the
+/// search history) as one JSON document, for moving to another machine.
+#[derive(Serialize, Deserialize)]
+pub struct StateArchive {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    bookmarks: Vec<SavedSearch>,
+    history: Vec<HistoryEntry>,
+}
+
+/// What an import added.
+pub struct ImportSummary {
+    pub bookmarks: usize,
+    pub history: usize,
+}
+
+impl StateArchive {
+    pub fn export(client: &KiwiClient) -> Self {
+        Self {
+            version: ARCHIVE_VERSION,
+            exported_at: Utc::now(),
+            bookmarks: client.bookmarks().list_all(),
+            history: client.history().entries(),
+        }
+    }
+
+    /// Adds the archive's contents to `client`'s stores. Saved searches
+    /// replace any with the same name; history entries already present are
+    /// skipped, so importing twice is harmless.
+    pub fn import(self, client: &KiwiClient) -> Result<ImportSummary> {
+        if self.version > ARCHIVE_VERSION {
+            return Err(KiwiToolError::validation(format!(
+                "The archive is version {}, but this server reads up to version {}; upgrade it first",
+                self.version, ARCHIVE_VERSION
+            ))
+            .into());
+        }
+
+        let bookmarks = self.bookmarks.len();
+        for search in self.bookmarks {
+            client.bookmarks().save(search)?;
+        }
+
+        Ok(ImportSummary {
+            bookmarks,
+            history: client.history().import(self.history)?,
+        })
+    }
+}
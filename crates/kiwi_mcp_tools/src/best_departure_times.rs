@@ -0,0 +1,166 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    config::SearchDefaults,
+    input::{self, ToolInput},
+    search::{SearchArgs, SearchOptions},
+};
+
+/// How many results are sampled for the histogram.
+const SAMPLE_SIZE: u64 = 200;
+
+/// Departure hour bands, as `[start, end)` local hours.
+const BANDS: [(u32, u32); 6] = [(0, 6), (6, 9), (9, 12), (12, 15), (15, 18), (18, 24)];
+
+/// Width of the longest histogram bar.
+const BAR_WIDTH: usize = 20;
+
+#[derive(Deserialize, JsonSchema)]
+struct BestDepartureTimesArgs {
+    /// IATA code of departure location (e.g., 'LHR', 'NYC', 'UK')
+    fly_from: String,
+    /// IATA code of arrival location
+    fly_to: String,
+    /// Departure date in format dd/mm/yyyy
+    date_from: String,
+    /// Latest departure date in format dd/mm/yyyy
+    date_to: String,
+    #[serde(flatten)]
+    search: SearchArgs,
+}
+
+impl ToolInput for BestDepartureTimesArgs {}
+
+/// Groups a large sample of results by local departure hour, to show which
+/// times of day are cheapest on a route.
+pub struct BestDepartureTimesTool {
+    client: Arc<KiwiClient>,
+    defaults: SearchDefaults,
+}
+
+impl BestDepartureTimesTool {
+    pub fn new(client: Arc<KiwiClient>, defaults: SearchDefaults) -> Self {
+        Self { client, defaults }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for BestDepartureTimesTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing BestDepartureTimesTool");
+        let args: BestDepartureTimesArgs = input::parse(arguments)?;
+
+        let fly_from = args.fly_from.as_str();
+        let fly_to = args.fly_to.as_str();
+        let date_from = args.date_from.as_str();
+        let date_to = args.date_to.as_str();
+        let options = SearchOptions {
+            limit: SAMPLE_SIZE,
+            sort: "price".into(),
+            ..SearchOptions::from_args(&args.search, &self.defaults)?
+        };
+
+        let mut params = vec![
+            ("fly_from", fly_from.to_string()),
+            ("fly_to", fly_to.to_string()),
+            ("date_from", date_from.to_string()),
+            ("date_to", date_to.to_string()),
+        ];
+        params.extend(options.params());
+
+        log::info!("Sampling departure times from {} to {}", fly_from, fly_to);
+
+        let mut response = self.client.search_itineraries(&params).await?;
+        options.apply_filters(&mut response.data);
+
+        let mut bands: Vec<Vec<f64>> = vec![Vec::new(); BANDS.len()];
+        for itinerary in &response.data {
+            let Some(hour) = itinerary
+                .journey_starts()
+                .next()
+                .and_then(|leg| leg.local_hours().0)
+            else {
+                continue;
+            };
+            if let Some(band) = BANDS
+                .iter()
+                .position(|(start, end)| (*start..*end).contains(&hour))
+            {
+                bands[band].push(itinerary.price);
+            }
+        }
+
+        let sampled: usize = bands.iter().map(Vec::len).sum();
+        if sampled == 0 {
+            return Ok(vec![ToolContent::Text {
+                text: String::from("No flights found matching your criteria."),
+            }]);
+        }
+
+        let busiest = bands.iter().map(Vec::len).max().unwrap_or(1);
+        let mut result = format!(
+            "Prices by departure time for {} → {}, from {} flights (prices in {}):\n\n",
+            fly_from, fly_to, sampled, options.curr
+        );
+
+        let mut cheapest_band: Option<(usize, f64)> = None;
+        for (i, ((start, end), prices)) in BANDS.iter().zip(bands.iter_mut()).enumerate() {
+            let label = format!("{:02}:00-{:02}:00", start, end);
+            if prices.is_empty() {
+                result.push_str(&format!("{} | no flights\n", label));
+                continue;
+            }
+
+            prices.sort_by(f64::total_cmp);
+            let median = prices[prices.len() / 2];
+            if cheapest_band.is_none_or(|(_, best)| median < best) {
+                cheapest_band = Some((i, median));
+            }
+
+            let bar = "#".repeat((prices.len() * BAR_WIDTH).div_ceil(busiest));
+            result.push_str(&format!(
+                "{} | {:<width$} {} flights, from {}, median {}\n",
+                label,
+                bar,
+                prices.len(),
+                options.price_format.amount(prices[0]),
+                options.price_format.amount(median),
+                width = BAR_WIDTH
+            ));
+        }
+
+        if let Some((i, median)) = cheapest_band {
+            let (start, end) = BANDS[i];
+            result.push_str(&format!(
+                "\nCheapest time to leave: {:02}:00-{:02}:00 (median {}).\n",
+                start,
+                end,
+                options.price(median)
+            ));
+        }
+        result.push_str("\nHours are local to the departure airport. The histogram covers the cheapest flights found, so it is a sample rather than every flight on the route.\n");
+        options.push_notes(&mut result);
+        self.client.push_quota_warning(&mut result);
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "best_departure_times".into(),
+            description: Some(
+                "Show how prices on a route vary with the time of day of departure, e.g. whether early-morning flights are cheaper"
+                    .into(),
+            ),
+            input_schema: input::schema::<BestDepartureTimesArgs>(),
+        }
+    }
+}
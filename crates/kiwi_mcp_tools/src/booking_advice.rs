@@ -0,0 +1,293 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Datelike, Utc};
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    config::SearchDefaults,
+    error::KiwiToolError,
+    fare_history::{FareStats, MIN_SAMPLES, RouteQuery, Standing, Trend, trend},
+    holidays,
+    input::{self, ToolInput},
+    search::{SearchArgs, SearchOptions, format_date, parse_date},
+};
+
+/// Results asked for on the departure date, so filters still leave a fare.
+const DAY_LIMIT: u64 = 5;
+
+/// Score from which the advice is to book now.
+const BOOK_SCORE: i32 = 2;
+
+/// Score at or below which the advice is to wait.
+const WAIT_SCORE: i32 = -1;
+
+#[derive(Deserialize, JsonSchema)]
+struct BookingAdviceArgs {
+    /// IATA code of departure location (e.g., 'LHR', 'NYC')
+    fly_from: String,
+    /// IATA code of arrival location
+    fly_to: String,
+    /// Departure date in dd/mm/yyyy format
+    date: String,
+    /// Nights at the destination, for a round trip (one-way when omitted)
+    nights: Option<u64>,
+    /// Price on offer for the whole party; when omitted the date is searched for the cheapest fare
+    current_price: Option<f64>,
+    #[serde(flatten)]
+    search: SearchArgs,
+}
+
+impl ToolInput for BookingAdviceArgs {
+    fn validate(&self) -> Result<()> {
+        let date = parse_date(&self.date)?;
+        if date <= Utc::now().date_naive() {
+            return Err(KiwiToolError::validation("date must be in the future").into());
+        }
+        if self.current_price.is_some_and(|price| price <= 0.0) {
+            return Err(KiwiToolError::validation("current_price must be positive").into());
+        }
+        Ok(())
+    }
+}
+
+/// One consideration behind the advice. Positive scores favour booking
+/// now, negative ones waiting.
+struct Factor {
+    name: &'static str,
+    score: i32,
+    reason: String,
+}
+
+/// Weighs the price against past searches, the time left before departure
+/// and the season into a recommendation to book now or wait, listing each
+/// factor so the reasoning can be checked.
+pub struct BookingAdviceTool {
+    client: Arc<KiwiClient>,
+    defaults: SearchDefaults,
+}
+
+impl BookingAdviceTool {
+    pub fn new(client: Arc<KiwiClient>, defaults: SearchDefaults) -> Self {
+        Self { client, defaults }
+    }
+
+    async fn current_price(
+        &self,
+        args: &BookingAdviceArgs,
+        options: &SearchOptions,
+    ) -> Result<Option<f64>> {
+        let mut params = vec![
+            ("fly_from", args.fly_from.clone()),
+            ("fly_to", args.fly_to.clone()),
+            ("date_from", args.date.clone()),
+            ("date_to", args.date.clone()),
+        ];
+        if let Some(nights) = args.nights {
+            params.push(("flight_type", "round".to_string()));
+            params.push(("nights_in_dst_from", nights.to_string()));
+            params.push(("nights_in_dst_to", nights.to_string()));
+        }
+        params.extend(options.params());
+
+        let mut response = self.client.search_itineraries(&params).await?;
+        options.apply_filters(&mut response.data);
+        Ok(response
+            .data
+            .iter()
+            .map(|itinerary| itinerary.price)
+            .min_by(f64::total_cmp))
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for BookingAdviceTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing BookingAdviceTool");
+        let args: BookingAdviceArgs = input::parse(arguments)?;
+
+        let date = parse_date(&args.date)?;
+        let today = Utc::now().date_naive();
+        let options = SearchOptions {
+            limit: DAY_LIMIT,
+            sort: "price".into(),
+            ..SearchOptions::from_args(&args.search, &self.defaults)?
+        };
+
+        // Read before searching, so the search doesn't judge itself
+        let fares = RouteQuery {
+            fly_from: &args.fly_from,
+            fly_to: &args.fly_to,
+            nights: args.nights,
+            options: &options,
+            month: date.with_day(1),
+        }
+        .fares(&self.client.history().entries());
+        let stats = FareStats::new(&fares);
+
+        let price = match args.current_price {
+            Some(price) => Some(price),
+            None => self.current_price(&args, &options).await?,
+        };
+        let Some(price) = price else {
+            return Ok(vec![ToolContent::Text {
+                text: format!(
+                    "No fares found from {} to {} on {}, so there is nothing to book yet. Try nearby dates or save a search to watch the route.",
+                    args.fly_from,
+                    args.fly_to,
+                    date.format("%d %b %Y")
+                ),
+            }]);
+        };
+
+        let mut factors = Vec::new();
+
+        factors.push(match &stats {
+            Some(stats) => Factor {
+                name: "Price history",
+                score: match stats.standing(price) {
+                    Standing::Lowest => 2,
+                    Standing::Low => 1,
+                    Standing::Typical => 0,
+                    Standing::High => -1,
+                },
+                reason: format!(
+                    "{} is {} (typical {}, low {}, from {} days of searches with the same cabin, party and stay).",
+                    options.price(price),
+                    stats.standing(price).describe(),
+                    options.price(stats.median),
+                    options.price(stats.low),
+                    stats.samples
+                ),
+            },
+            None => Factor {
+                name: "Price history",
+                score: 0,
+                reason: format!(
+                    "Too few past searches of single dates this month with the same cabin, party and stay to judge the price ({} days, {} needed).",
+                    fares.len(),
+                    MIN_SAMPLES
+                ),
+            },
+        });
+
+        if let Some(trend) = trend(&fares) {
+            factors.push(Factor {
+                name: "Recent trend",
+                score: match trend {
+                    Trend::Rising => 1,
+                    Trend::Steady => 0,
+                    Trend::Falling => -1,
+                },
+                reason: match trend {
+                    Trend::Rising => "Fares in recent searches are higher than in earlier ones.",
+                    Trend::Steady => "Fares in recent searches are about the same as earlier.",
+                    Trend::Falling => "Fares in recent searches are lower than in earlier ones.",
+                }
+                .to_string(),
+            });
+        }
+
+        let days_left = (date - today).num_days();
+        factors.push(Factor {
+            name: "Time to departure",
+            score: match days_left {
+                ..=14 => 2,
+                15..=42 => 1,
+                43..=120 => 0,
+                _ => -1,
+            },
+            reason: format!(
+                "{} days to go. {}",
+                days_left,
+                match days_left {
+                    ..=14 => "In the last two weeks fares rarely fall and often rise sharply.",
+                    15..=42 => "Within six weeks fares usually start to climb.",
+                    43..=120 => "Six weeks to four months out is usually the cheapest time to book.",
+                    _ => "This far out fares often come down nearer the date, so waiting costs little.",
+                }
+            ),
+        });
+
+        factors.push(match holidays::peak_period(date) {
+            Some(period) => Factor {
+                name: "Season",
+                score: 1,
+                reason: format!(
+                    "Departs during {}, when seats fill early and fares tend to climb.",
+                    period
+                ),
+            },
+            None => Factor {
+                name: "Season",
+                score: 0,
+                reason: "Departs outside the Christmas, Easter and summer peaks.".to_string(),
+            },
+        });
+
+        let score: i32 = factors.iter().map(|factor| factor.score).sum();
+        let advice = if score >= BOOK_SCORE {
+            "Book now: waiting is more likely to cost than to save.".to_string()
+        } else if score <= WAIT_SCORE {
+            format!(
+                "Wait: the price is more likely to fall than rise. Save the search with a target price{} to be told when it drops.",
+                stats
+                    .map(|stats| format!(" such as {}", options.price(stats.median.min(price))))
+                    .unwrap_or_default()
+            )
+        } else {
+            "No strong signal either way: book if the price suits you, or save the search with a target price to watch it.".to_string()
+        };
+
+        let mut result = format!(
+            "{} → {} on {}{}, at {}:\n\n{}\n\nFactors (+ favours booking now, − waiting):\n",
+            args.fly_from,
+            args.fly_to,
+            date.format("%d %b %Y"),
+            args.nights
+                .map(|nights| format!(", {} nights", nights))
+                .unwrap_or_default(),
+            options.price(price),
+            advice
+        );
+        for factor in &factors {
+            result.push_str(&format!(
+                "- {} ({:+}): {}\n",
+                factor.name, factor.score, factor.reason
+            ));
+        }
+        result.push_str(&format!(
+            "Total {:+}: {:+} or more advises booking, {:+} or less waiting.\n",
+            score, BOOK_SCORE, WAIT_SCORE
+        ));
+        result.push_str(
+            "\nThis is a rule of thumb from past searches through this server and common fare patterns, not a forecast.\n",
+        );
+        if args.current_price.is_none() {
+            result.push_str(&format!(
+                "The price is the cheapest found on {}.\n",
+                format_date(date)
+            ));
+        }
+        options.push_notes(&mut result);
+        self.client.push_quota_warning(&mut result);
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "booking_advice".into(),
+            description: Some(
+                "Advise whether to book a flight now or wait, from the price compared with past searches, their trend, the days left before departure and the season, listing each factor behind the advice"
+                    .into(),
+            ),
+            input_schema: input::schema::<BookingAdviceArgs>(),
+        }
+    }
+}
@@ -0,0 +1,408 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    encryption::{self, Cipher},
+    error::KiwiToolError,
+    model::Itinerary,
+    search::parse_date,
+    session, storage,
+};
+
+/// A search saved under a name, with the results it had when last run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    pub saved_at: DateTime<Utc>,
+    pub checked_at: DateTime<Utc>,
+    /// Query parameters sent to `/v2/search`.
+    pub params: Vec<(String, String)>,
+    pub currency: String,
+    pub itineraries: Vec<Itinerary>,
+    /// The cheapest price found on each day the search was run, oldest
+    /// first.
+    #[serde(default)]
+    pub prices: Vec<PricePoint>,
+    /// Price at or below which the search counts as triggered, with
+    /// [`Trigger::Below`].
+    #[serde(default)]
+    pub target_price: Option<f64>,
+    #[serde(default)]
+    pub trigger: Trigger,
+    #[serde(default)]
+    pub status: SearchStatus,
+    /// Checks in a row that found the price on target.
+    #[serde(default)]
+    pub checks_on_target: u32,
+    /// Session the search was saved in, when the transport has sessions;
+    /// only that session sees it, and it is dropped when the session ends.
+    #[serde(default)]
+    pub session: Option<String>,
+}
+
+/// What makes a saved search's price count as on target.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Trigger {
+    /// At or below the target price.
+    #[default]
+    Below,
+    /// Down by at least `percent` on the first price recorded.
+    Drop { percent: f64 },
+    /// At or below the lowest price recorded on earlier days.
+    AllTimeLow,
+}
+
+/// Where a saved search is in its life.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchStatus {
+    /// Checked for price changes.
+    #[default]
+    Active,
+    /// The last check found the price at or below the target.
+    Triggered,
+    /// No longer checked: the departure dates have passed, or the price
+    /// stayed on target for long enough.
+    Expired,
+}
+
+impl SearchStatus {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Triggered => "triggered",
+            Self::Expired => "expired",
+        }
+    }
+}
+
+/// The cheapest price a saved search found on one day.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PricePoint {
+    pub date: NaiveDate,
+    pub price: f64,
+}
+
+/// Days of prices kept per saved search.
+const MAX_PRICE_POINTS: usize = 366;
+
+impl SavedSearch {
+    /// The cheapest of the saved results.
+    pub fn cheapest(&self) -> Option<f64> {
+        self.itineraries
+            .iter()
+            .map(|itinerary| itinerary.price)
+            .min_by(f64::total_cmp)
+    }
+
+    /// Whether the search covers several destinations, as in
+    /// `fly_to=BCN,LIS,OPO`.
+    pub fn has_several_destinations(&self) -> bool {
+        self.param("fly_to")
+            .is_some_and(|fly_to| fly_to.split(',').filter(|code| !code.is_empty()).count() > 1)
+    }
+
+    /// The cheapest saved result to each destination city, cheapest first,
+    /// labelled like `Lisbon (LIS)`.
+    pub fn cheapest_by_destination(&self) -> Vec<(String, f64)> {
+        let mut cheapest: BTreeMap<&str, &Itinerary> = BTreeMap::new();
+        for itinerary in &self.itineraries {
+            cheapest
+                .entry(itinerary.city_to.as_str())
+                .and_modify(|best| {
+                    if itinerary.price < best.price {
+                        *best = itinerary;
+                    }
+                })
+                .or_insert(itinerary);
+        }
+
+        let mut destinations: Vec<(String, f64)> = cheapest
+            .into_iter()
+            .map(|(city, itinerary)| (format!("{} ({})", city, itinerary.fly_to), itinerary.price))
+            .collect();
+        destinations.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+        destinations
+    }
+
+    /// The destinations whose cheapest saved result is on target on `date`.
+    pub fn destinations_on_target(&self, date: NaiveDate) -> Vec<(String, f64)> {
+        let Some(target) = self.threshold(date) else {
+            return Vec::new();
+        };
+        self.cheapest_by_destination()
+            .into_iter()
+            .filter(|(_, price)| *price <= target)
+            .collect()
+    }
+
+    /// Whether the search has a trigger that can fire.
+    pub fn has_trigger(&self) -> bool {
+        self.trigger != Trigger::Below || self.target_price.is_some()
+    }
+
+    /// The price at or below which a check on `date` is on target, when
+    /// there is one yet: the target price, the first price less the drop,
+    /// or the lowest price recorded before `date`.
+    pub fn threshold(&self, date: NaiveDate) -> Option<f64> {
+        match self.trigger {
+            Trigger::Below => self.target_price,
+            Trigger::Drop { percent } => self
+                .prices
+                .first()
+                .map(|first| first.price * (1.0 - percent / 100.0)),
+            Trigger::AllTimeLow => self
+                .prices
+                .iter()
+                .filter(|point| point.date < date)
+                .map(|point| point.price)
+                .reduce(f64::min),
+        }
+    }
+
+    /// Records `price` as the cheapest found on `date`, in place of an
+    /// earlier run the same day.
+    pub fn record_price(&mut self, date: NaiveDate, price: f64) {
+        if let Some(last) = self.prices.last_mut()
+            && last.date == date
+        {
+            last.price = price;
+            return;
+        }
+        self.prices.push(PricePoint { date, price });
+        if self.prices.len() > MAX_PRICE_POINTS {
+            self.prices.remove(0);
+        }
+    }
+
+    /// Records the outcome of a check on `date` that found `cheapest`, and
+    /// moves the search along its life: triggered while the price is on
+    /// target by its [`Trigger`], and expired after `expire_after` such checks in a row (never
+    /// when 0). Expired searches stay expired.
+    pub fn observe(&mut self, date: NaiveDate, cheapest: Option<f64>, expire_after: u32) {
+        let threshold = self.threshold(date);
+        if let Some(cheapest) = cheapest {
+            self.record_price(date, cheapest);
+        }
+        if self.status == SearchStatus::Expired {
+            return;
+        }
+
+        let on_target = threshold
+            .zip(cheapest)
+            .is_some_and(|(target, cheapest)| cheapest <= target);
+        if on_target {
+            self.checks_on_target += 1;
+            self.status = if expire_after > 0 && self.checks_on_target >= expire_after {
+                SearchStatus::Expired
+            } else {
+                SearchStatus::Triggered
+            };
+        } else {
+            self.checks_on_target = 0;
+            self.status = SearchStatus::Active;
+        }
+    }
+
+    /// The status on `today`: expired as well once the last departure date
+    /// has passed.
+    pub fn status_on(&self, today: NaiveDate) -> SearchStatus {
+        if self.last_departure().is_some_and(|date| date < today) {
+            SearchStatus::Expired
+        } else {
+            self.status
+        }
+    }
+
+    /// The end of the departure window.
+    pub fn last_departure(&self) -> Option<NaiveDate> {
+        self.param("date_to")
+            .or_else(|| self.param("date_from"))
+            .and_then(|date| parse_date(date).ok())
+    }
+
+    /// The latest price recorded before `date`.
+    pub fn price_before(&self, date: NaiveDate) -> Option<PricePoint> {
+        self.prices
+            .iter()
+            .rev()
+            .find(|point| point.date < date)
+            .copied()
+    }
+
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Saved searches, kept in memory and written to a JSON file when a path is
+/// configured. Names are per session.
+#[derive(Default)]
+pub struct Bookmarks {
+    path: RwLock<Option<PathBuf>>,
+    /// Searches by [`key`].
+    searches: RwLock<BTreeMap<String, SavedSearch>>,
+    /// Checks on target after which a search expires; never when 0.
+    expire_after: RwLock<u32>,
+}
+
+impl Bookmarks {
+    pub fn set_expire_after(&self, checks_on_target: u32) {
+        *self.expire_after.write() = checks_on_target;
+    }
+
+    /// Records a check of the saved search `name` on `date` that found
+    /// `cheapest` (see [`SavedSearch::observe`]) and returns the search as
+    /// updated. Searches deleted meanwhile are left alone.
+    pub fn observe(
+        &self,
+        name: &str,
+        date: NaiveDate,
+        cheapest: Option<f64>,
+    ) -> Result<Option<SavedSearch>> {
+        let mut searches = self.searches.write();
+        let Some(saved) = searches.get_mut(&key(session::current().as_deref(), name)) else {
+            return Ok(None);
+        };
+        saved.observe(date, cheapest, *self.expire_after.read());
+        let saved = saved.clone();
+        self.write(&searches)?;
+        Ok(Some(saved))
+    }
+
+    pub fn expire_after(&self) -> u32 {
+        *self.expire_after.read()
+    }
+
+    /// Switches to the bookmarks file at `path`, loading the searches it
+    /// already holds. `None` keeps bookmarks in memory only. Fails, rather
+    /// than risk overwriting the file, when it can't be read, decrypted or
+    /// parsed.
+    pub fn set_path(&self, path: Option<PathBuf>) -> Result<()> {
+        if *self.path.read() == path {
+            return Ok(());
+        }
+
+        let (mut searches, unsealed) = match path.as_deref() {
+            Some(path) => load(path, encryption::cipher().as_deref())?,
+            None => (BTreeMap::new(), false),
+        };
+        searches.retain(|_, saved| session::is_kept(saved.session.as_deref()));
+
+        *self.searches.write() = searches;
+        *self.path.write() = path;
+
+        // Encrypt a file written before encryption was turned on
+        if unsealed && encryption::is_enabled() {
+            self.write(&self.searches.read())?;
+        }
+
+        Ok(())
+    }
+
+    /// Fails if the bookmarks file at `path` couldn't be loaded with
+    /// `cipher`, as [`set_path`](Self::set_path) would.
+    pub fn check(path: &Path, cipher: Option<&Cipher>) -> Result<()> {
+        load(path, cipher).map(|_| ())
+    }
+
+    /// Saves `search`, replacing any search with the same name in its
+    /// session. Searches of sessions that ended are dropped.
+    pub fn save(&self, search: SavedSearch) -> Result<()> {
+        let mut searches = self.searches.write();
+        searches.retain(|_, saved| session::is_kept(saved.session.as_deref()));
+        searches.insert(key(search.session.as_deref(), &search.name), search);
+        self.write(&searches)
+    }
+
+    /// The search saved as `name` in the current session.
+    pub fn get(&self, name: &str) -> Result<SavedSearch> {
+        self.searches
+            .read()
+            .get(&key(session::current().as_deref(), name))
+            .cloned()
+            .ok_or_else(|| {
+                KiwiToolError::validation(format!("No saved search named {:?}", name)).into()
+            })
+    }
+
+    /// The searches saved in the current session.
+    pub fn list(&self) -> Vec<SavedSearch> {
+        let session = session::current();
+        self.searches
+            .read()
+            .values()
+            .filter(|saved| saved.session == session)
+            .cloned()
+            .collect()
+    }
+
+    /// The searches saved in every live session, for the background
+    /// refresh and exports.
+    pub fn list_all(&self) -> Vec<SavedSearch> {
+        self.searches
+            .read()
+            .values()
+            .filter(|saved| session::is_kept(saved.session.as_deref()))
+            .cloned()
+            .collect()
+    }
+
+    /// Deletes every saved search, in memory and on disk. Returns how many
+    /// there were.
+    pub fn purge(&self) -> Result<usize> {
+        if let Some(path) = self.path.read().as_ref() {
+            storage::current().remove(path)?;
+        }
+
+        Ok(std::mem::take(&mut *self.searches.write()).len())
+    }
+
+    fn write(&self, searches: &BTreeMap<String, SavedSearch>) -> Result<()> {
+        let Some(path) = self.path.read().clone() else {
+            return Ok(());
+        };
+
+        let contents = encryption::seal(&serde_json::to_string_pretty(searches)?)?;
+        storage::current().write(&path, contents.as_bytes())
+    }
+}
+
+/// Where the search saved as `name` in `session` is kept: unscoped searches
+/// under their name, as before sessions, and others under the session too.
+fn key(session: Option<&str>, name: &str) -> String {
+    match session {
+        Some(session) => format!("{}:{}", session, name),
+        None => name.to_string(),
+    }
+}
+
+/// The searches in the bookmarks file at `path`, opened with `cipher`, and
+/// whether the file was written unencrypted.
+fn load(path: &Path, cipher: Option<&Cipher>) -> Result<(BTreeMap<String, SavedSearch>, bool)> {
+    let Some(contents) = storage::read_to_string(path)
+        .with_context(|| format!("Failed to read the saved searches {}", path.display()))?
+    else {
+        return Ok((BTreeMap::new(), false));
+    };
+
+    let searches =
+        serde_json::from_str(&encryption::open_with(cipher, &contents)?).with_context(|| {
+            format!(
+                "The saved searches {} are corrupt; fix or remove the file",
+                path.display()
+            )
+        })?;
+    Ok((searches, !encryption::is_sealed(&contents)))
+}
@@ -0,0 +1,185 @@
+//! Daily and monthly caps on Kiwi API requests, for plans with a limited
+//! allowance. Requests are counted per UTC day and calendar month, in a
+//! file so the counts survive restarts.
+//!
+//! Past a share of a budget the background refresh stops and fan-out tools
+//! search fewer dates, hubs and cabins; once it is used up searches either carry on with a warning
+//! or are refused, as configured. Cached results cost nothing and are
+//! always served.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{BudgetAction, BudgetConfig},
+    error::KiwiToolError,
+    rate_limit::Priority,
+    storage,
+};
+
+/// Share of a budget after which it counts as tight.
+const TIGHT_SHARE: f64 = 0.8;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+struct Counts {
+    day: Option<NaiveDate>,
+    daily: u64,
+    /// The first day of the month counted.
+    month: Option<NaiveDate>,
+    monthly: u64,
+}
+
+impl Counts {
+    /// The counts for `today`, starting again when the day or month has
+    /// changed.
+    fn on(self, today: NaiveDate) -> Self {
+        let month = today.with_day(1);
+        Self {
+            day: Some(today),
+            daily: if self.day == Some(today) {
+                self.daily
+            } else {
+                0
+            },
+            month,
+            monthly: if self.month == month { self.monthly } else { 0 },
+        }
+    }
+}
+
+/// How much of one budget has been used.
+#[derive(Debug, Clone, Copy)]
+pub struct BudgetUse {
+    /// `daily` or `monthly`.
+    pub period: &'static str,
+    pub used: u64,
+    pub limit: u64,
+}
+
+impl BudgetUse {
+    pub fn is_exceeded(&self) -> bool {
+        self.used >= self.limit
+    }
+
+    pub fn is_tight(&self) -> bool {
+        self.used as f64 >= self.limit as f64 * TIGHT_SHARE
+    }
+}
+
+#[derive(Default)]
+pub struct Budget {
+    config: RwLock<BudgetConfig>,
+    path: RwLock<Option<PathBuf>>,
+    counts: Mutex<Counts>,
+}
+
+impl Budget {
+    pub fn configure(&self, config: BudgetConfig) {
+        *self.config.write() = config;
+    }
+
+    /// Keeps the counts in the JSON file at `path`, continuing from the
+    /// counts it holds. `None` keeps them in memory only.
+    pub fn set_path(&self, path: Option<PathBuf>) -> Result<()> {
+        if *self.path.read() == path {
+            return Ok(());
+        }
+
+        let counts = match path.as_deref() {
+            Some(path) => storage::read_to_string(path)?
+                .and_then(|contents| serde_json::from_str(&contents).ok())
+                .unwrap_or_default(),
+            None => *self.counts.lock(),
+        };
+        *self.counts.lock() = counts;
+        *self.path.write() = path;
+
+        Ok(())
+    }
+
+    /// Deletes the counts, in memory and on disk, so the budgets start
+    /// again from zero.
+    pub fn purge(&self) -> Result<()> {
+        if let Some(path) = self.path.read().as_ref() {
+            storage::current().remove(path)?;
+        }
+
+        *self.counts.lock() = Counts::default();
+        Ok(())
+    }
+
+    pub fn action(&self) -> BudgetAction {
+        self.config.read().on_exceeded
+    }
+
+    /// Refuses a request the budget doesn't allow: any request once a budget
+    /// is used up and set to refuse, and background requests once one is
+    /// tight.
+    pub fn check(&self, priority: Priority) -> Result<()> {
+        let refuse = self.action() == BudgetAction::Refuse;
+        for budget in self.uses() {
+            let blocked = match priority {
+                Priority::Background => budget.is_tight(),
+                Priority::Interactive => refuse && budget.is_exceeded(),
+            };
+            if blocked {
+                return Err(KiwiToolError::Budget {
+                    period: budget.period,
+                    limit: budget.limit,
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Counts a request sent to the API.
+    pub fn record(&self) {
+        let counts = {
+            let mut counts = self.counts.lock();
+            *counts = counts.on(Utc::now().date_naive());
+            counts.daily += 1;
+            counts.monthly += 1;
+            *counts
+        };
+
+        let Some(path) = self.path.read().clone() else {
+            return;
+        };
+        let written = serde_json::to_vec(&counts)
+            .map_err(anyhow::Error::from)
+            .and_then(|contents| storage::current().write(&path, &contents));
+        if let Err(err) = written {
+            log::warn!("Failed to save the API request counts: {:#}", err);
+        }
+    }
+
+    /// The configured budgets and how much of each has been used.
+    pub fn uses(&self) -> Vec<BudgetUse> {
+        let config = self.config.read();
+        let counts = self.counts.lock().on(Utc::now().date_naive());
+        [
+            ("daily", config.daily_requests, counts.daily),
+            ("monthly", config.monthly_requests, counts.monthly),
+        ]
+        .into_iter()
+        .filter_map(|(period, limit, used)| {
+            Some(BudgetUse {
+                period,
+                used,
+                limit: limit?,
+            })
+        })
+        .collect()
+    }
+
+    /// Whether a budget is nearly or completely used up, so tools should
+    /// make fewer requests.
+    pub fn is_tight(&self) -> bool {
+        self.uses().iter().any(BudgetUse::is_tight)
+    }
+}
@@ -0,0 +1,326 @@
+mod disk;
+
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use chrono::{NaiveDate, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use disk::DiskCache;
+
+/// Size the on-disk cache is pruned to unless configured otherwise.
+const DEFAULT_MAX_DISK_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Disk writes between prunes of expired and excess entries.
+const PRUNE_EVERY: u64 = 100;
+
+/// Cache of API responses keyed by request URL. Entries live in memory and,
+/// when a directory is configured, on disk as well.
+pub struct ResponseCache {
+    entries: RwLock<HashMap<String, CacheEntry>>,
+    disk: RwLock<Option<DiskCache>>,
+    ttl: RwLock<Duration>,
+    max_ttl: RwLock<Duration>,
+    max_disk_bytes: AtomicU64,
+    /// Entries written to disk since it was last pruned.
+    disk_writes: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+struct CacheEntry {
+    value: Value,
+    route: Option<Route>,
+    /// Earliest departure date searched, which sets how long the entry
+    /// stays fresh.
+    departs: Option<NaiveDate>,
+    size: usize,
+    inserted_at: Instant,
+}
+
+/// The origin/destination pair a cached search was made for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Route {
+    pub fly_from: String,
+    pub fly_to: String,
+}
+
+pub struct CacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    /// Approximate size of the cached JSON payloads, in bytes.
+    pub size: usize,
+    pub routes: Vec<RouteStats>,
+    pub disk: Option<DiskStats>,
+}
+
+pub struct DiskStats {
+    pub dir: PathBuf,
+    pub entries: usize,
+    pub size: u64,
+}
+
+pub struct RouteStats {
+    pub route: Route,
+    pub entries: usize,
+    pub newest: Duration,
+}
+
+impl CacheStats {
+    pub fn hit_ratio(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f64 / lookups as f64)
+    }
+}
+
+impl ResponseCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: RwLock::default(),
+            disk: RwLock::default(),
+            ttl: RwLock::new(ttl),
+            max_ttl: RwLock::new(ttl),
+            max_disk_bytes: AtomicU64::new(DEFAULT_MAX_DISK_BYTES),
+            disk_writes: AtomicU64::default(),
+            hits: AtomicU64::default(),
+            misses: AtomicU64::default(),
+        }
+    }
+
+    /// A zero TTL disables caching.
+    pub fn set_ttl(&self, ttl: Duration) {
+        *self.ttl.write() = ttl;
+        if ttl.is_zero() {
+            self.entries.write().clear();
+        }
+    }
+
+    /// Longest a response for a far-off departure is kept. Shorter than the
+    /// base TTL means every entry uses the base TTL.
+    pub fn set_max_ttl(&self, max_ttl: Duration) {
+        *self.max_ttl.write() = max_ttl;
+    }
+
+    /// How long an entry for departures from `departs` stays fresh: the
+    /// base TTL for the next fortnight, doubling for every further two
+    /// weeks ahead up to the maximum, as fares far out change slowly.
+    fn ttl_for(&self, departs: Option<NaiveDate>) -> Duration {
+        const DOUBLING_DAYS: i64 = 14;
+
+        let ttl = *self.ttl.read();
+        let max_ttl = *self.max_ttl.read();
+        let Some(departs) = departs else {
+            return ttl;
+        };
+        if ttl.is_zero() || max_ttl <= ttl {
+            return ttl;
+        }
+
+        let days_ahead = (departs - Utc::now().date_naive()).num_days();
+        let doublings = (days_ahead / DOUBLING_DAYS).clamp(0, 16) as u32;
+        ttl.saturating_mul(1 << doublings).min(max_ttl)
+    }
+
+    /// Persists entries under `dir` in addition to memory; `None` keeps the
+    /// cache in memory only.
+    pub fn set_disk_dir(&self, dir: Option<PathBuf>) {
+        *self.disk.write() = dir.map(DiskCache::new);
+        self.prune_disk();
+    }
+
+    /// Largest the on-disk cache may grow before its oldest entries are
+    /// removed.
+    pub fn set_max_disk_size(&self, bytes: u64) {
+        self.max_disk_bytes.store(bytes, Ordering::Relaxed);
+    }
+
+    /// Removes expired files from disk, and the oldest ones beyond the size
+    /// cap, since entries that are never read again would otherwise stay.
+    fn prune_disk(&self) {
+        self.disk_writes.store(0, Ordering::Relaxed);
+        if let Some(disk) = self.disk.read().as_ref() {
+            let removed = disk.prune(
+                |departs| self.ttl_for(departs),
+                self.max_disk_bytes.load(Ordering::Relaxed),
+            );
+            if removed > 0 {
+                log::debug!(
+                    "Pruned {} cache entries from {}",
+                    removed,
+                    disk.dir().display()
+                );
+            }
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.ttl.read().is_zero()
+    }
+
+    /// Whether a fresh entry for `key` is held in memory, without counting
+    /// a lookup.
+    pub fn contains(&self, key: &str) -> bool {
+        self.is_enabled()
+            && self
+                .entries
+                .read()
+                .get(key)
+                .is_some_and(|entry| entry.inserted_at.elapsed() < self.ttl_for(entry.departs))
+    }
+
+    pub fn get(&self, key: &str) -> Option<Value> {
+        if !self.is_enabled() {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+
+        // Bound on its own so the read guard drops before the disk lookup
+        // takes the write lock
+        let in_memory = self
+            .entries
+            .read()
+            .get(key)
+            .filter(|entry| entry.inserted_at.elapsed() < self.ttl_for(entry.departs))
+            .map(|entry| entry.value.clone());
+        let value = in_memory.or_else(|| self.get_from_disk(key));
+
+        match value {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+
+        value
+    }
+
+    /// Looks `key` up on disk and promotes a hit back into memory, keeping
+    /// its original age so it still expires on time.
+    fn get_from_disk(&self, key: &str) -> Option<Value> {
+        let disk = self.disk.read();
+        let (value, route, departs, age) =
+            disk.as_ref()?.get(key, |departs| self.ttl_for(departs))?;
+
+        let inserted_at = Instant::now().checked_sub(age).unwrap_or_else(Instant::now);
+        self.entries.write().insert(
+            key.to_string(),
+            CacheEntry {
+                value: value.clone(),
+                route,
+                departs,
+                size: key.len() + value.to_string().len(),
+                inserted_at,
+            },
+        );
+
+        Some(value)
+    }
+
+    pub fn insert(
+        &self,
+        key: String,
+        route: Option<Route>,
+        departs: Option<NaiveDate>,
+        value: Value,
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        if let Some(disk) = self.disk.read().as_ref()
+            && let Err(err) = disk.insert(&key, route.clone(), departs, value.clone())
+        {
+            log::warn!(
+                "Failed to write cache entry to {}: {}",
+                disk.dir().display(),
+                err
+            );
+        }
+        if self.disk_writes.fetch_add(1, Ordering::Relaxed) + 1 >= PRUNE_EVERY {
+            self.prune_disk();
+        }
+
+        let size = key.len() + value.to_string().len();
+
+        let mut entries = self.entries.write();
+        entries.retain(|_, entry| entry.inserted_at.elapsed() < self.ttl_for(entry.departs));
+        entries.insert(
+            key,
+            CacheEntry {
+                value,
+                route,
+                departs,
+                size,
+                inserted_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Removes the entries for `route`, or everything when `None`, from
+    /// memory and disk. Returns the number of distinct entries removed.
+    pub fn clear(&self, route: Option<&Route>) -> usize {
+        let mut removed: HashSet<String> = self
+            .disk
+            .read()
+            .as_ref()
+            .map(|disk| disk.clear(route).into_iter().collect())
+            .unwrap_or_default();
+
+        self.entries.write().retain(|key, entry| {
+            let matches = route.is_none_or(|route| entry.route.as_ref() == Some(route));
+            if matches {
+                removed.insert(key.clone());
+            }
+            !matches
+        });
+
+        removed.len()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let entries = self.entries.read();
+
+        let mut routes: HashMap<&Route, RouteStats> = HashMap::new();
+        for entry in entries.values() {
+            let Some(route) = &entry.route else {
+                continue;
+            };
+            let age = entry.inserted_at.elapsed();
+            let stats = routes.entry(route).or_insert_with(|| RouteStats {
+                route: route.clone(),
+                entries: 0,
+                newest: age,
+            });
+            stats.entries += 1;
+            stats.newest = stats.newest.min(age);
+        }
+
+        let mut routes: Vec<RouteStats> = routes.into_values().collect();
+        routes.sort_by_key(|stats| Reverse(stats.entries));
+
+        CacheStats {
+            entries: entries
+                .values()
+                .filter(|entry| entry.inserted_at.elapsed() < self.ttl_for(entry.departs))
+                .count(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            size: entries.values().map(|entry| entry.size).sum(),
+            routes,
+            disk: self.disk.read().as_ref().map(|disk| {
+                let (entries, size) = disk.usage(|departs| self.ttl_for(departs));
+                DiskStats {
+                    dir: disk.dir().to_path_buf(),
+                    entries,
+                    size,
+                }
+            }),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use super::Route;
+use crate::storage;
+
+/// Cached responses persisted as one JSON file per entry, so they survive
+/// server restarts. Files go through the installed [`storage`].
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredEntry {
+    key: String,
+    route: Option<Route>,
+    #[serde(default)]
+    departs: Option<NaiveDate>,
+    /// Seconds since the Unix epoch.
+    stored_at: u64,
+    value: Value,
+}
+
+impl StoredEntry {
+    fn age(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.stored_at))
+    }
+}
+
+impl DiskCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the cached value, its route, departure date and age, removing
+    /// the file if it has expired. `ttl` gives the lifetime for a departure
+    /// date.
+    pub fn get(
+        &self,
+        key: &str,
+        ttl: impl Fn(Option<NaiveDate>) -> Duration,
+    ) -> Option<(Value, Option<Route>, Option<NaiveDate>, Duration)> {
+        let path = self.path(key);
+        let contents = storage::current().read(&path).ok()??;
+        let entry: StoredEntry = serde_json::from_slice(&contents).ok()?;
+
+        if entry.key != key {
+            return None;
+        }
+
+        let age = entry.age();
+        if age >= ttl(entry.departs) {
+            let _ = storage::current().remove(&path);
+            return None;
+        }
+
+        Some((entry.value, entry.route, entry.departs, age))
+    }
+
+    pub fn insert(
+        &self,
+        key: &str,
+        route: Option<Route>,
+        departs: Option<NaiveDate>,
+        value: Value,
+    ) -> Result<()> {
+        let entry = StoredEntry {
+            key: key.to_string(),
+            route,
+            departs,
+            stored_at: now(),
+            value,
+        };
+
+        storage::current().write(&self.path(key), &serde_json::to_vec(&entry)?)
+    }
+
+    /// Removes the entries for `route`, or everything when `None`. Returns
+    /// the keys removed.
+    pub fn clear(&self, route: Option<&Route>) -> Vec<String> {
+        self.entries()
+            .filter(|(_, entry, _)| route.is_none_or(|route| entry.route.as_ref() == Some(route)))
+            .filter(|(path, _, _)| storage::current().remove(path).is_ok())
+            .map(|(_, entry, _)| entry.key)
+            .collect()
+    }
+
+    /// Removes expired entries, then the oldest ones until the rest take at
+    /// most `max_bytes`. Returns the number of files removed.
+    pub fn prune(&self, ttl: impl Fn(Option<NaiveDate>) -> Duration, max_bytes: u64) -> usize {
+        let (expired, mut fresh): (Vec<_>, Vec<_>) = self
+            .entries()
+            .partition(|(_, entry, _)| entry.age() >= ttl(entry.departs));
+
+        let mut removed = expired
+            .iter()
+            .filter(|(path, _, _)| storage::current().remove(path).is_ok())
+            .count();
+
+        let mut size: u64 = fresh.iter().map(|(_, _, file_size)| file_size).sum();
+        fresh.sort_by_key(|(_, entry, _)| entry.stored_at);
+        for (path, _, file_size) in fresh {
+            if size <= max_bytes {
+                break;
+            }
+            if storage::current().remove(&path).is_ok() {
+                size -= file_size;
+                removed += 1;
+            }
+        }
+        removed
+    }
+
+    /// Number and total size in bytes of the unexpired entries.
+    pub fn usage(&self, ttl: impl Fn(Option<NaiveDate>) -> Duration) -> (usize, u64) {
+        self.entries()
+            .filter(|(_, entry, _)| entry.age() < ttl(entry.departs))
+            .fold((0, 0), |(count, size), (_, _, file_size)| {
+                (count + 1, size + file_size)
+            })
+    }
+
+    /// Each entry with its path and size in bytes.
+    fn entries(&self) -> impl Iterator<Item = (PathBuf, StoredEntry, u64)> {
+        storage::current()
+            .list(&self.dir)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| {
+                let contents = storage::current().read(&path).ok()??;
+                let entry = serde_json::from_slice(&contents).ok()?;
+                Some((path, entry, contents.len() as u64))
+            })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:x}.json", Sha256::digest(key.as_bytes())))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,349 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Datelike, Days, Months, NaiveDate, Utc};
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    config::SearchDefaults,
+    error::KiwiToolError,
+    input::{self, ToolInput},
+    model::Itinerary,
+    progress::join_with_progress,
+    search::{SearchArgs, SearchOptions, format_date},
+};
+
+const DEFAULT_MONTHS: u64 = 6;
+const MAX_MONTHS: u64 = 12;
+
+/// Each month is sampled in windows starting on these days.
+const WINDOW_STARTS: [u32; 3] = [1, 11, 21];
+
+/// The same while a request budget is tight: one window for the month.
+const SPARING_WINDOW_STARTS: [u32; 1] = [1];
+
+/// Results asked for per window, so filters still leave a fare.
+const WINDOW_LIMIT: u64 = 5;
+
+/// A sampled departure window and the cheapest fare found in it.
+struct Window {
+    from: NaiveDate,
+    to: NaiveDate,
+    cheapest: Result<Option<Itinerary>>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct CheapestMonthArgs {
+    /// IATA code of departure location (e.g., 'LHR', 'NYC', 'UK')
+    fly_from: String,
+    /// IATA code of arrival location
+    fly_to: String,
+    /// How many months to sample, starting with the current one (default 6, at most 12)
+    #[serde(default = "default_months")]
+    months: u64,
+    /// Nights at the destination, to compare round trips (one-way when omitted)
+    nights: Option<u64>,
+    #[serde(flatten)]
+    search: SearchArgs,
+}
+
+fn default_months() -> u64 {
+    DEFAULT_MONTHS
+}
+
+impl ToolInput for CheapestMonthArgs {
+    fn validate(&self) -> Result<()> {
+        if !(1..=MAX_MONTHS).contains(&self.months) {
+            return Err(KiwiToolError::validation(format!(
+                "months must be between 1 and {}",
+                MAX_MONTHS
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Samples fares across the coming months to find the cheapest one to
+/// travel in.
+pub struct CheapestMonthTool {
+    client: Arc<KiwiClient>,
+    defaults: SearchDefaults,
+}
+
+impl CheapestMonthTool {
+    pub fn new(client: Arc<KiwiClient>, defaults: SearchDefaults) -> Self {
+        Self { client, defaults }
+    }
+
+    async fn sample(
+        &self,
+        route: &[(&'static str, String)],
+        from: NaiveDate,
+        to: NaiveDate,
+        options: &SearchOptions,
+    ) -> Result<Option<Itinerary>> {
+        let mut params = route.to_vec();
+        params.push(("date_from", format_date(from)));
+        params.push(("date_to", format_date(to)));
+        params.extend(options.params());
+
+        let mut response = self.client.search_itineraries(&params).await?;
+        options.apply_filters(&mut response.data);
+        Ok(response
+            .data
+            .into_iter()
+            .min_by(|a, b| a.price.total_cmp(&b.price)))
+    }
+}
+
+/// Departure windows starting on `starts` for each of the `months` months
+/// starting with the current one, skipping days already past.
+fn month_windows(
+    today: NaiveDate,
+    months: u64,
+    starts: &[u32],
+) -> Vec<(NaiveDate, Vec<(NaiveDate, NaiveDate)>)> {
+    let first_day = today.with_day(1).expect("every month has a first day");
+    let earliest = today + Days::new(1);
+
+    (0..months as u32)
+        .filter_map(|i| {
+            let month = first_day.checked_add_months(Months::new(i))?;
+            let last = month.checked_add_months(Months::new(1))? - Days::new(1);
+
+            let windows = starts
+                .iter()
+                .enumerate()
+                .filter_map(|(w, start)| {
+                    let from = month.with_day(*start)?.max(earliest);
+                    let to = match starts.get(w + 1) {
+                        Some(next) => month.with_day(next - 1)?,
+                        None => last,
+                    };
+                    (from <= to).then_some((from, to))
+                })
+                .collect();
+            Some((month, windows))
+        })
+        .collect()
+}
+
+/// Runs of consecutive months in which every window was searched and none
+/// had flights, when other months did. These suggest a seasonal route
+/// rather than fares that are merely expensive.
+fn seasonal_gaps(sampled: &[(NaiveDate, Vec<Window>)]) -> Vec<(NaiveDate, NaiveDate)> {
+    let empty: Vec<bool> = sampled
+        .iter()
+        .map(|(_, windows)| {
+            !windows.is_empty()
+                && windows
+                    .iter()
+                    .all(|window| matches!(window.cheapest, Ok(None)))
+        })
+        .collect();
+    let served = sampled.iter().any(|(_, windows)| {
+        windows
+            .iter()
+            .any(|window| matches!(window.cheapest, Ok(Some(_))))
+    });
+    if !served {
+        return Vec::new();
+    }
+
+    let mut gaps: Vec<(NaiveDate, NaiveDate)> = Vec::new();
+    for ((month, _), empty) in sampled.iter().zip(empty) {
+        if !empty {
+            continue;
+        }
+        match gaps.last_mut() {
+            Some((_, end)) if end.checked_add_months(Months::new(1)) == Some(*month) => {
+                *end = *month
+            }
+            _ => gaps.push((*month, *month)),
+        }
+    }
+    gaps
+}
+
+#[async_trait]
+impl ToolExecutor for CheapestMonthTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing CheapestMonthTool");
+        let args: CheapestMonthArgs = input::parse(arguments)?;
+
+        let fly_from = args.fly_from.as_str();
+        let fly_to = args.fly_to.as_str();
+        let months = args.months;
+        let nights = args.nights;
+        let options = SearchOptions {
+            limit: WINDOW_LIMIT,
+            sort: "price".into(),
+            ..SearchOptions::from_args(&args.search, &self.defaults)?
+        };
+
+        let mut route = vec![
+            ("fly_from", fly_from.to_string()),
+            ("fly_to", fly_to.to_string()),
+        ];
+        if let Some(nights) = nights {
+            route.push(("flight_type", "round".to_string()));
+            route.push(("nights_in_dst_from", nights.to_string()));
+            route.push(("nights_in_dst_to", nights.to_string()));
+        }
+
+        log::info!(
+            "Sampling {} months of fares from {} to {}",
+            months,
+            fly_from,
+            fly_to
+        );
+
+        let sparing = self.client.budget().is_tight();
+        let starts: &[u32] = if sparing {
+            &SPARING_WINDOW_STARTS
+        } else {
+            &WINDOW_STARTS
+        };
+        let calendar = month_windows(Utc::now().date_naive(), months, starts);
+        let mut best: Option<f64> = None;
+        let searches: Vec<_> = calendar
+            .iter()
+            .flat_map(|(_, windows)| windows.iter().copied())
+            .map(|(from, to)| self.sample(&route, from, to, &options))
+            .collect();
+        let samples = join_with_progress(self.client.progress(), searches, |sample| {
+            if let Ok(Some(itinerary)) = sample
+                && best.is_none_or(|best| itinerary.price < best)
+            {
+                best = Some(itinerary.price);
+            }
+            best.map(|best| format!("cheapest so far {}", options.price(best)))
+        })
+        .await;
+
+        let mut samples = samples.into_iter();
+        let sampled: Vec<(NaiveDate, Vec<Window>)> = calendar
+            .into_iter()
+            .map(|(month, windows)| {
+                let windows = windows
+                    .into_iter()
+                    .zip(samples.by_ref())
+                    .map(|((from, to), cheapest)| Window { from, to, cheapest })
+                    .collect();
+                (month, windows)
+            })
+            .collect();
+
+        let mut result = format!(
+            "Fares by month for {} → {}{} (prices in {}):\n\n",
+            fly_from,
+            fly_to,
+            nights
+                .map(|nights| format!(", {} nights", nights))
+                .unwrap_or_default(),
+            options.curr
+        );
+
+        let mut cheapest_month: Option<(NaiveDate, &Itinerary)> = None;
+        for (month, windows) in &sampled {
+            let fares: Vec<String> = windows
+                .iter()
+                .map(|window| {
+                    let dates =
+                        format!("{}-{}", window.from.format("%d"), window.to.format("%d %b"));
+                    match &window.cheapest {
+                        Ok(Some(flight)) => {
+                            format!("{}: {}", dates, options.price_format.amount(flight.price))
+                        }
+                        Ok(None) => format!("{}: no flights", dates),
+                        Err(err) => {
+                            log::warn!("Search for {} failed: {}", dates, err);
+                            format!("{}: search failed", dates)
+                        }
+                    }
+                })
+                .collect();
+
+            let cheapest = windows
+                .iter()
+                .filter_map(|window| window.cheapest.as_ref().ok()?.as_ref())
+                .min_by(|a, b| a.price.total_cmp(&b.price));
+            match cheapest {
+                Some(flight) => {
+                    result.push_str(&format!(
+                        "{}: from {} ({})\n",
+                        month.format("%B %Y"),
+                        options.price_format.amount(flight.price),
+                        fares.join("; ")
+                    ));
+                    if cheapest_month.is_none_or(|(_, best)| flight.price < best.price) {
+                        cheapest_month = Some((*month, flight));
+                    }
+                }
+                None => result.push_str(&format!(
+                    "{}: no fares found ({})\n",
+                    month.format("%B %Y"),
+                    fares.join("; ")
+                )),
+            }
+        }
+
+        match cheapest_month {
+            Some((month, flight)) => result.push_str(&format!(
+                "\nCheapest month: {}, from {} with {}{}.\n",
+                month.format("%B %Y"),
+                options.price(flight.price),
+                flight.airlines.join(", "),
+                flight
+                    .local_departure_date()
+                    .map(|date| format!(" departing {}", date.format("%d %b")))
+                    .unwrap_or_default()
+            )),
+            None => result.push_str("\nNo fares found in any month.\n"),
+        }
+
+        let gaps = seasonal_gaps(&sampled);
+        if !gaps.is_empty() {
+            let ranges: Vec<String> = gaps
+                .iter()
+                .map(|(start, end)| {
+                    if start == end {
+                        start.format("%b %Y").to_string()
+                    } else {
+                        format!("{}–{}", start.format("%b"), end.format("%b %Y"))
+                    }
+                })
+                .collect();
+            result.push_str(&format!(
+                "\nThis route appears seasonal: no flights found in {}.\n",
+                ranges.join(", ")
+            ));
+        }
+
+        result.push_str("\nFares are the cheapest found in each window; use plan_trip on the chosen dates for the full list.\n");
+        if sparing {
+            result.push_str("Each month was searched as a single window to save API requests, as the request budget is nearly used up.\n");
+        }
+        options.push_notes(&mut result);
+        self.client.push_quota_warning(&mut result);
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "cheapest_month".into(),
+            description: Some(
+                "Find the cheapest month to fly a route by sampling fares across the coming months"
+                    .into(),
+            ),
+            input_schema: input::schema::<CheapestMonthArgs>(),
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    error::KiwiToolError,
+    input::{self, ToolInput},
+    locations,
+};
+
+#[derive(Deserialize, JsonSchema)]
+struct CityAirportsArgs {
+    /// IATA city or metropolitan area code (e.g., 'NYC', 'LON', 'TYO')
+    code: String,
+}
+
+impl ToolInput for CityAirportsArgs {
+    fn validate(&self) -> Result<()> {
+        let code = self.code.trim();
+        if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(KiwiToolError::validation(format!(
+                "{:?} is not an IATA city code; use three letters, e.g. LON",
+                self.code
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Expands a city or metropolitan area code into the airports serving it,
+/// with each one's distance from the city centre.
+pub struct CityAirportsTool {
+    client: Arc<KiwiClient>,
+}
+
+impl CityAirportsTool {
+    pub fn new(client: Arc<KiwiClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for CityAirportsTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing CityAirportsTool");
+        let args: CityAirportsArgs = input::parse(arguments)?;
+        let code = args.code.trim().to_ascii_uppercase();
+
+        let response = self.client.locations_of_type(&code, "city", 5).await?;
+        let candidates = locations::parse(&response);
+        let Some(city) = candidates.iter().find(|city| city.code == code) else {
+            let suggestions: Vec<String> = locations::closest(&code, &candidates, 3)
+                .into_iter()
+                .map(|city| format!("{} ({})", city.code, city.name))
+                .collect();
+            let mut message = format!("Unknown city code '{}'", code);
+            if !suggestions.is_empty() {
+                message.push_str(&format!("; did you mean {}?", suggestions.join(" or ")));
+            }
+            return Err(KiwiToolError::validation(message).into());
+        };
+
+        let mut airports: Vec<(Option<f64>, locations::Airport)> = self
+            .client
+            .city_airports(&city.name, &city.code)
+            .await?
+            .into_iter()
+            .map(|airport| {
+                let distance = city
+                    .position
+                    .zip(airport.position)
+                    .map(|(centre, airport)| locations::distance_km(centre, airport));
+                (distance, airport)
+            })
+            .collect();
+        airports.sort_by(|(a, _), (b, _)| {
+            a.unwrap_or(f64::INFINITY)
+                .total_cmp(&b.unwrap_or(f64::INFINITY))
+        });
+
+        let mut result = if airports.is_empty() {
+            format!("No airports found for {} ({}).\n", city.name, city.code)
+        } else {
+            format!(
+                "{} ({}) is served by {} airports, nearest the centre first:\n\n",
+                city.name,
+                city.code,
+                airports.len()
+            )
+        };
+        for (distance, airport) in &airports {
+            let distance = distance
+                .map(|km| format!(", {:.0} km from the centre", km))
+                .unwrap_or_default();
+            result.push_str(&format!("{} {}{}\n", airport.code, airport.name, distance));
+        }
+        if airports.len() > 1 {
+            result.push_str(&format!(
+                "\nSearch from or to {} to cover them all, or give airport codes separated by commas (e.g. {}) to pick some.\n",
+                city.code,
+                airports
+                    .iter()
+                    .take(2)
+                    .map(|(_, airport)| airport.code.as_str())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ));
+        }
+
+        self.client.push_quota_warning(&mut result);
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "city_airports".into(),
+            description: Some(
+                "List the airports of a city or metropolitan area code (e.g. NYC, LON, TYO) with their distance from the city centre, to choose airports and build precise searches"
+                    .into(),
+            ),
+            input_schema: input::schema::<CityAirportsArgs>(),
+        }
+    }
+}
@@ -0,0 +1,665 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use http_client::{HttpClient, Request, RequestBuilderExt, ResponseAsyncBodyExt};
+use parking_lot::{Mutex, RwLock};
+use serde_json::Value;
+use url::Url;
+
+use crate::{
+    bookmarks::Bookmarks,
+    budget::{Budget, BudgetUse},
+    cache::{ResponseCache, Route},
+    config::{BudgetAction, BudgetConfig},
+    diagnostics, dry_run,
+    encryption::Cipher,
+    error::KiwiToolError,
+    history::{HistoryEntry, SearchHistory},
+    locations::{self, Airport},
+    model::SearchResponse,
+    progress::ProgressHub,
+    rate_limit::{Priority, Quota, RateLimiter},
+    redact,
+    search::parse_date,
+    usage::Usage,
+};
+
+const API_BASE_URL: &str = "https://api.tequila.kiwi.com";
+
+/// Airports looked up for a city; even the largest have fewer.
+const CITY_AIRPORTS_LIMIT: u64 = 20;
+
+/// Longest a single API request may take.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Thin wrapper around the Tequila REST API shared by every tool.
+pub struct KiwiClient {
+    http_client: Arc<dyn HttpClient>,
+    base_url: RwLock<String>,
+    api_key: RwLock<String>,
+    /// Static headers added to every request.
+    headers: RwLock<Vec<(String, String)>>,
+    rate_limiter: RateLimiter,
+    cache: ResponseCache,
+    history: SearchHistory,
+    bookmarks: Bookmarks,
+    /// When the last interactive search started.
+    last_search: Mutex<Instant>,
+    progress: ProgressHub,
+    usage: Usage,
+    budget: Budget,
+}
+
+impl KiwiClient {
+    pub fn new(http_client: Arc<dyn HttpClient>, api_key: impl Into<String>) -> Self {
+        let api_key = api_key.into();
+        redact::register_secret(&api_key);
+
+        Self {
+            http_client,
+            base_url: RwLock::new(API_BASE_URL.to_string()),
+            api_key: RwLock::new(api_key),
+            headers: RwLock::new(Vec::new()),
+            rate_limiter: RateLimiter::default(),
+            cache: ResponseCache::new(Duration::ZERO),
+            history: SearchHistory::default(),
+            bookmarks: Bookmarks::default(),
+            last_search: Mutex::new(Instant::now()),
+            progress: ProgressHub::default(),
+            usage: Usage::default(),
+            budget: Budget::default(),
+        }
+    }
+
+    /// Replaces the API key used for subsequent requests.
+    pub fn set_api_key(&self, api_key: impl Into<String>) {
+        let api_key = api_key.into();
+        redact::register_secret(&api_key);
+        *self.api_key.write() = api_key;
+    }
+
+    /// Sends requests to `base_url` instead of the Tequila API; `None`
+    /// restores the default.
+    pub fn set_base_url(&self, base_url: Option<String>) {
+        *self.base_url.write() = base_url
+            .map(|url| url.trim_end_matches('/').to_string())
+            .unwrap_or_else(|| API_BASE_URL.to_string());
+    }
+
+    /// Replaces the static headers, such as `User-Agent`, sent with every
+    /// request.
+    pub fn set_request_headers(&self, headers: Vec<(String, String)>) {
+        *self.headers.write() = headers;
+    }
+
+    /// Adds a note to tool output when the API quota, or a configured
+    /// request budget, is nearly used up.
+    pub fn push_quota_warning(&self, result: &mut String) {
+        self.push_budget_warning(result);
+
+        let Some(quota) = self.rate_limiter.quota().filter(|quota| quota.is_low()) else {
+            return;
+        };
+        let resets = quota
+            .resets_at
+            .map(|resets_at| {
+                let seconds = resets_at
+                    .saturating_duration_since(tokio::time::Instant::now())
+                    .as_secs();
+                format!(" for the next {}m {}s", seconds / 60, seconds % 60)
+            })
+            .unwrap_or_default();
+
+        result.push_str(&format!(
+            "\nNote: only {}{} Kiwi API requests are left{}; further searches will be slowed down to stay within the quota.\n",
+            quota.remaining,
+            quota
+                .limit
+                .map(|limit| format!(" of {}", limit))
+                .unwrap_or_default(),
+            resets
+        ));
+    }
+
+    fn push_budget_warning(&self, result: &mut String) {
+        let Some(budget) = self.budget.uses().into_iter().find(BudgetUse::is_tight) else {
+            return;
+        };
+
+        if budget.is_exceeded() {
+            let consequence = match self.budget.action() {
+                BudgetAction::Warn => "searches carry on, over the budget",
+                BudgetAction::Refuse => {
+                    "searches the cache can't answer are refused until it resets"
+                }
+            };
+            result.push_str(&format!(
+                "\nNote: the {} budget of {} Kiwi API requests is used up ({} made); {}.\n",
+                budget.period, budget.limit, budget.used, consequence
+            ));
+        } else {
+            result.push_str(&format!(
+                "\nNote: {} of the {} budget of {} Kiwi API requests are used; tools search fewer dates, hubs and cabins and the background refresh is paused to save the rest.\n",
+                budget.used, budget.period, budget.limit
+            ));
+        }
+    }
+
+    /// Caps outbound requests per minute; `None` removes the limit.
+    pub fn set_rate_limit(&self, requests_per_minute: Option<u32>) {
+        self.rate_limiter
+            .set_requests_per_minute(requests_per_minute);
+    }
+
+    /// Sets how long responses are cached; zero disables the cache.
+    pub fn set_cache_ttl(&self, ttl: Duration) {
+        self.cache.set_ttl(ttl);
+    }
+
+    /// Lets responses for departures further ahead be cached for up to
+    /// `max_ttl`.
+    pub fn set_cache_max_ttl(&self, max_ttl: Duration) {
+        self.cache.set_max_ttl(max_ttl);
+    }
+
+    /// Caps the on-disk cache at `bytes`, removing the oldest entries beyond.
+    pub fn set_cache_max_disk_size(&self, bytes: u64) {
+        self.cache.set_max_disk_size(bytes);
+    }
+
+    /// Persists cached responses under `dir` as well; `None` keeps them in
+    /// memory only.
+    pub fn set_cache_dir(&self, dir: Option<PathBuf>) {
+        self.cache.set_disk_dir(dir);
+    }
+
+    /// Caps requests per day and month as `config` says.
+    pub fn set_budget(&self, config: BudgetConfig) {
+        self.budget.configure(config);
+    }
+
+    /// Keeps the request counts the budget is checked against in the JSON
+    /// file at `path`; `None` keeps them in memory only.
+    pub fn set_request_counts_path(&self, path: Option<PathBuf>) -> Result<()> {
+        self.budget.set_path(path)
+    }
+
+    /// Appends the search history to the JSON Lines file at `path`; `None`
+    /// keeps it in memory only.
+    pub fn set_history_path(&self, path: Option<PathBuf>) -> Result<()> {
+        self.history.set_path(path)
+    }
+
+    /// Writes saved searches to the JSON file at `path`; `None` keeps them
+    /// in memory only.
+    pub fn set_bookmarks_path(&self, path: Option<PathBuf>) -> Result<()> {
+        self.bookmarks.set_path(path)
+    }
+
+    /// Fails if the history or bookmarks file at these paths couldn't be
+    /// loaded with `cipher`, so a configuration that would fail to switch
+    /// to them is refused before any of it is applied.
+    pub fn check_data_files(
+        history_path: Option<&Path>,
+        bookmarks_path: Option<&Path>,
+        cipher: Option<&Cipher>,
+    ) -> Result<()> {
+        if let Some(path) = history_path {
+            SearchHistory::check(path, cipher)?;
+        }
+        if let Some(path) = bookmarks_path {
+            Bookmarks::check(path, cipher)?;
+        }
+        Ok(())
+    }
+
+    /// Calls `/v2/search` with the given query parameters and records the
+    /// search in the history.
+    pub async fn search(&self, params: &[(&str, String)]) -> Result<Value> {
+        *self.last_search.lock() = Instant::now();
+        let response = self.get("/v2/search", params).await?;
+        self.history
+            .record(HistoryEntry::from_search(params, &response));
+        Ok(response)
+    }
+
+    /// Fetches a search into the cache unless it is already there, without
+    /// recording it in the history. Interactive requests go first. Returns
+    /// whether a request was made.
+    pub async fn prefetch(&self, params: &[(&str, String)]) -> Result<bool> {
+        let url = self.url("/v2/search", params)?;
+        if self.cache.contains(url.as_str()) {
+            return Ok(false);
+        }
+
+        self.fetch("/v2/search", params, Priority::Background)
+            .await?;
+        Ok(true)
+    }
+
+    /// The cached results of a search, without making a request or
+    /// recording it anywhere. `None` when the cache doesn't hold them.
+    pub fn cached_search(&self, params: &[(&str, String)]) -> Option<SearchResponse> {
+        let url = self.url("/v2/search", params).ok()?;
+        serde_json::from_value(self.cache.get(url.as_str())?).ok()
+    }
+
+    /// How long since the last interactive search started.
+    pub fn idle_for(&self) -> Duration {
+        self.last_search.lock().elapsed()
+    }
+
+    /// Like [`KiwiClient::search_checked`], but parses the response into
+    /// itineraries.
+    pub async fn search_itineraries(&self, params: &[(&str, String)]) -> Result<SearchResponse> {
+        Ok(serde_json::from_value(self.search_checked(params).await?)?)
+    }
+
+    /// Like [`KiwiClient::search`], but fails on a response without
+    /// itineraries. The API answers a search between unknown locations with
+    /// no results or a terse error, so those are checked for codes it
+    /// doesn't know.
+    pub async fn search_checked(&self, params: &[(&str, String)]) -> Result<Value> {
+        let response = match self.search(params).await {
+            Ok(response) => response,
+            Err(err) if is_location_error(&err) => {
+                self.check_locations(params).await?;
+                return Err(err);
+            }
+            Err(err) => return Err(err),
+        };
+        if !response.get("data").is_some_and(Value::is_array) {
+            return Err(
+                KiwiToolError::parse("The API response was in an unexpected format").into(),
+            );
+        }
+
+        if response["data"].as_array().is_some_and(Vec::is_empty) {
+            self.check_locations(params).await?;
+        }
+        Ok(response)
+    }
+
+    /// Fails with suggestions when `fly_from` or `fly_to` holds a code the
+    /// locations API doesn't know. Lookup failures are only logged.
+    async fn check_locations(&self, params: &[(&str, String)]) -> Result<()> {
+        let codes = params
+            .iter()
+            .filter(|(name, _)| matches!(*name, "fly_from" | "fly_to"))
+            .flat_map(|(_, value)| value.split(','))
+            .map(str::trim)
+            // Country codes and radius or prefixed locations aren't plain
+            // codes the lookup can confirm
+            .filter(|code| code.len() > 2 && code.chars().all(|c| c.is_ascii_alphabetic()));
+
+        let mut unknown = Vec::new();
+        for code in codes {
+            let mut candidates = match self.locations_query(code, 10).await {
+                Ok(response) => locations::parse(&response),
+                Err(err) => {
+                    log::warn!("Failed to look up location {}: {}", code, err);
+                    continue;
+                }
+            };
+            if locations::contains(&candidates, code) {
+                continue;
+            }
+
+            // Nothing matched the whole string; try places starting like it
+            if candidates.is_empty()
+                && code.len() > 3
+                && let Ok(response) = self.locations_query(&code[..3], 10).await
+            {
+                candidates = locations::parse(&response);
+            }
+
+            let suggestions: Vec<String> = locations::closest(code, &candidates, 3)
+                .into_iter()
+                .map(|location| format!("{} ({})", location.code, location.name))
+                .collect();
+            unknown.push(if suggestions.is_empty() {
+                format!("unknown location code '{}'", code)
+            } else {
+                format!(
+                    "unknown location code '{}' (did you mean {}?)",
+                    code,
+                    suggestions.join(" or ")
+                )
+            });
+        }
+
+        if unknown.is_empty() {
+            return Ok(());
+        }
+        Err(KiwiToolError::validation(format!(
+            "No flights found: {}. Use IATA airport or city codes (e.g. LHR, LON) or country codes (e.g. GB)",
+            unknown.join("; ")
+        ))
+        .into())
+    }
+
+    /// The request [`KiwiClient::search`] sends for `params`, as a `curl`
+    /// command with the configured headers. The API key travels in a header
+    /// and is left as `$KIWI_API_KEY` for the shell to expand; header values
+    /// other than `User-Agent` and `Accept` are redacted.
+    pub fn search_request(&self, params: &[(&str, String)]) -> Result<String> {
+        let url = self.url("/v2/search", params)?;
+        let mut command =
+            String::from("curl -H \"apikey: $KIWI_API_KEY\" -H 'Accept: application/json'");
+        for (name, value) in self.headers.read().iter() {
+            command.push_str(&format!(
+                " -H {}",
+                shell_quote(&format!("{}: {}", name, redact::header_value(name, value)))
+            ));
+        }
+        command.push_str(&format!(" {}", shell_quote(url.as_str())));
+        Ok(command)
+    }
+
+    /// Calls `/locations/query` to look up places matching `term`.
+    pub async fn locations_query(&self, term: &str, limit: u64) -> Result<Value> {
+        self.get(
+            "/locations/query",
+            &[("term", term.to_string()), ("limit", limit.to_string())],
+        )
+        .await
+    }
+
+    /// Calls `/locations/query` for places of one type, e.g. `airport`.
+    pub async fn locations_of_type(
+        &self,
+        term: &str,
+        location_type: &str,
+        limit: u64,
+    ) -> Result<Value> {
+        self.get(
+            "/locations/query",
+            &[
+                ("term", term.to_string()),
+                ("location_types", location_type.to_string()),
+                ("limit", limit.to_string()),
+            ],
+        )
+        .await
+    }
+
+    /// The airports serving the city `city_code`, looked up by the city's
+    /// name.
+    pub async fn city_airports(&self, city: &str, city_code: &str) -> Result<Vec<Airport>> {
+        let response = self
+            .locations_of_type(city, "airport", CITY_AIRPORTS_LIMIT)
+            .await?;
+        Ok(locations::parse_airports(&response)
+            .into_iter()
+            .filter(|airport| airport.city_code == city_code)
+            .collect())
+    }
+
+    /// Up to `limit` airports within `radius_km` of `position`, a
+    /// `(longitude, latitude)`, from `/locations/radius`.
+    pub async fn nearby_airports(
+        &self,
+        position: (f64, f64),
+        radius_km: u64,
+        limit: u64,
+    ) -> Result<Vec<Airport>> {
+        let response = self
+            .get(
+                "/locations/radius",
+                &[
+                    ("lat", position.1.to_string()),
+                    ("lon", position.0.to_string()),
+                    ("radius", radius_km.to_string()),
+                    ("location_types", "airport".to_string()),
+                    ("limit", limit.to_string()),
+                ],
+            )
+            .await?;
+        Ok(locations::parse_airports(&response))
+    }
+
+    /// Looks up an airport by IATA code and returns its `(longitude, latitude)`.
+    pub async fn airport_coordinates(&self, code: &str) -> Result<Option<(f64, f64)>> {
+        let response = self.locations_of_type(code, "airport", 1).await?;
+
+        let location = response
+            .get("locations")
+            .and_then(|locations| locations.as_array())
+            .and_then(|locations| {
+                locations
+                    .iter()
+                    .find(|location| location.get("code").and_then(|c| c.as_str()) == Some(code))
+            })
+            .and_then(|location| location.get("location"));
+
+        Ok(location.and_then(|location| {
+            Some((
+                location.get("lon")?.as_f64()?,
+                location.get("lat")?.as_f64()?,
+            ))
+        }))
+    }
+
+    /// Checks that `api_key` is accepted by the API without switching to it.
+    pub async fn verify_api_key(&self, api_key: &str) -> Result<()> {
+        redact::register_secret(api_key);
+        let url = self.url(
+            "/locations/query",
+            &[("term", "PRG".to_string()), ("limit", "1".to_string())],
+        )?;
+
+        self.send(url, api_key.to_string(), Priority::Interactive)
+            .await
+            .map(|_| ())
+    }
+
+    pub fn cache(&self) -> &ResponseCache {
+        &self.cache
+    }
+
+    pub fn history(&self) -> &SearchHistory {
+        &self.history
+    }
+
+    pub fn bookmarks(&self) -> &Bookmarks {
+        &self.bookmarks
+    }
+
+    /// Where tools report interim findings while a call is in progress.
+    pub fn progress(&self) -> &ProgressHub {
+        &self.progress
+    }
+
+    /// Calls and API requests counted so far.
+    pub fn usage(&self) -> &Usage {
+        &self.usage
+    }
+
+    /// The configured request budgets and how much of each is used.
+    pub fn budget(&self) -> &Budget {
+        &self.budget
+    }
+
+    /// The API request quota, once a response has reported it.
+    pub fn quota(&self) -> Option<Quota> {
+        self.rate_limiter.quota()
+    }
+
+    async fn get(&self, path: &str, params: &[(&str, String)]) -> Result<Value> {
+        self.fetch(path, params, Priority::Interactive).await
+    }
+
+    async fn fetch(
+        &self,
+        path: &str,
+        params: &[(&str, String)],
+        priority: Priority,
+    ) -> Result<Value> {
+        let url = self.url(path, params)?;
+        dry_run::intercept(&url, &self.headers.read())?;
+        let key = url.to_string();
+
+        if let Some(value) = self.cache.get(&key) {
+            log::debug!("Cache hit for {}", url);
+            self.usage.record_request(true);
+            return Ok(value);
+        }
+
+        let api_key = self.api_key.read().clone();
+        let value = self.send(url, api_key, priority).await?;
+
+        self.cache
+            .insert(key, route(params), departs(params), value.clone());
+
+        Ok(value)
+    }
+
+    fn url(&self, path: &str, params: &[(&str, String)]) -> Result<Url> {
+        Ok(Url::parse_with_params(
+            &format!("{}{}", self.base_url.read(), path),
+            params,
+        )?)
+    }
+
+    async fn send(&self, url: Url, api_key: String, priority: Priority) -> Result<Value> {
+        self.budget.check(priority)?;
+        self.budget.record();
+        self.usage.record_request(false);
+        let outcome = self.request(&url, api_key, priority).await;
+        diagnostics::record_exchange(&url, &outcome);
+        outcome
+    }
+
+    async fn request(&self, url: &Url, api_key: String, priority: Priority) -> Result<Value> {
+        self.rate_limiter.acquire(priority).await;
+
+        log::debug!("GET {}", url);
+
+        let mut request = Request::builder()
+            .method("GET")
+            .uri(url.as_str())
+            .header("Accept", "application/json");
+        for (name, value) in self.headers.read().iter() {
+            request = request.header(name.as_str(), value.as_str());
+        }
+
+        // Make the request to Kiwi API
+        let request = self
+            .http_client
+            .send(request.header("apikey", api_key).end()?);
+        let response = tokio::time::timeout(REQUEST_TIMEOUT, request)
+            .await
+            .map_err(|_| KiwiToolError::Timeout(REQUEST_TIMEOUT))??;
+
+        self.rate_limiter.record_headers(|name| {
+            let value = response.headers().get(name)?;
+            value.to_str().ok().map(String::from)
+        });
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = redact::redact(&response.text().await.unwrap_or_default());
+            log::error!("Kiwi API returned {}: {}", status, body);
+            if status.as_u16() == 429 {
+                let retry_after = self
+                    .rate_limiter
+                    .quota()
+                    .and_then(|quota| quota.resets_at)
+                    .map(|resets_at| {
+                        resets_at.saturating_duration_since(tokio::time::Instant::now())
+                    });
+                return Err(KiwiToolError::Quota { retry_after }.into());
+            }
+            return Err(KiwiToolError::Upstream {
+                status: status.as_u16(),
+                message: body,
+            }
+            .into());
+        }
+
+        // Parse the response
+        let value: Value = response.json().await.map_err(|err| {
+            log::error!("Failed to parse API response: {}", err);
+            KiwiToolError::parse(format!("Failed to parse API response: {}", err))
+        })?;
+
+        // Some invalid requests are answered with 200 and an `error` field
+        if let Some(message) = payload_error(&value) {
+            let message = redact::redact(&message);
+            log::error!("Kiwi API returned an error: {}", message);
+            return Err(KiwiToolError::validation(format!(
+                "The Kiwi API rejected the request: {}",
+                message
+            ))
+            .into());
+        }
+
+        Ok(value)
+    }
+}
+
+/// Whether the API rejected a request over one of its locations.
+fn is_location_error(err: &anyhow::Error) -> bool {
+    KiwiToolError::find(err).is_some_and(|err| {
+        let message = match err {
+            KiwiToolError::Validation(message) => message,
+            KiwiToolError::Upstream { status, message } if *status < 500 => message,
+            _ => return false,
+        };
+        let message = message.to_lowercase();
+        ["location", "fly_from", "fly_to"]
+            .iter()
+            .any(|hint| message.contains(hint))
+    })
+}
+
+/// The message of an error reported in a successful response's body, as a
+/// string or an object with a `message`.
+fn payload_error(value: &Value) -> Option<String> {
+    let error = value.get("error")?;
+    let message = match error {
+        Value::Null => return None,
+        Value::String(message) => message.clone(),
+        Value::Object(error) => match error.get("message").and_then(Value::as_str) {
+            Some(message) => message.to_string(),
+            None => error
+                .values()
+                .map(Value::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+        },
+        error => error.to_string(),
+    };
+    (!message.trim().is_empty()).then_some(message)
+}
+
+fn route(params: &[(&str, String)]) -> Option<Route> {
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.clone())
+    };
+
+    Some(Route {
+        fly_from: param("fly_from")?,
+        fly_to: param("fly_to")?,
+    })
+}
+
+fn departs(params: &[(&str, String)]) -> Option<NaiveDate> {
+    params
+        .iter()
+        .find(|(key, _)| *key == "date_from")
+        .and_then(|(_, value)| parse_date(value).ok())
+}
+
+/// `text` single-quoted for a POSIX shell.
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
@@ -0,0 +1,207 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    config::SearchDefaults,
+    input::{self, ToolInput},
+    model::{Itinerary, format_local_time},
+    search::{SearchArgs, SearchOptions},
+};
+
+#[derive(Deserialize, JsonSchema)]
+struct CompareBaggageFaresArgs {
+    /// IATA code of departure location (e.g., 'LHR', 'NYC', 'UK')
+    fly_from: String,
+    /// IATA code of arrival location
+    fly_to: String,
+    /// Departure date in format dd/mm/yyyy
+    date_from: String,
+    /// Latest departure date in format dd/mm/yyyy
+    date_to: String,
+    /// Return departure date in format dd/mm/yyyy (for round trips)
+    return_from: Option<String>,
+    /// Latest return departure date in format dd/mm/yyyy (for round trips)
+    return_to: Option<String>,
+    /// Which fare to sort by (default with_bags)
+    #[serde(default)]
+    sort_by: FareSort,
+    #[serde(flatten)]
+    search: SearchArgs,
+}
+
+impl ToolInput for CompareBaggageFaresArgs {}
+
+/// Which fare the results are ordered by.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum FareSort {
+    Basic,
+    #[default]
+    WithBags,
+}
+
+/// Compares each itinerary's basic fare with its fare including one cabin bag
+/// and one checked bag per passenger.
+pub struct CompareBaggageFaresTool {
+    client: Arc<KiwiClient>,
+    defaults: SearchDefaults,
+}
+
+impl CompareBaggageFaresTool {
+    pub fn new(client: Arc<KiwiClient>, defaults: SearchDefaults) -> Self {
+        Self { client, defaults }
+    }
+}
+
+struct Fare<'a> {
+    itinerary: &'a Itinerary,
+    basic: f64,
+    with_bags: Option<f64>,
+}
+
+#[async_trait]
+impl ToolExecutor for CompareBaggageFaresTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing CompareBaggageFaresTool");
+        let args: CompareBaggageFaresArgs = input::parse(arguments)?;
+
+        let fly_from = args.fly_from.as_str();
+        let fly_to = args.fly_to.as_str();
+        let date_from = args.date_from.as_str();
+        let date_to = args.date_to.as_str();
+        let return_from = args.return_from.as_deref();
+        let return_to = args.return_to.as_deref();
+        let sort_by = args.sort_by;
+
+        let options = SearchOptions::from_args(&args.search, &self.defaults)?;
+
+        let mut params = vec![
+            ("fly_from", fly_from.to_string()),
+            ("fly_to", fly_to.to_string()),
+            ("date_from", date_from.to_string()),
+            ("date_to", date_to.to_string()),
+        ];
+        params.extend(options.params());
+        if let Some(return_from) = return_from {
+            params.push(("return_from", return_from.to_string()));
+        }
+        if let Some(return_to) = return_to {
+            params.push(("return_to", return_to.to_string()));
+        }
+
+        // The API prices the requested bags into the fare: one of each per passenger
+        let mut bag_params = params.clone();
+        for (hand_bag, hold_bag, passengers) in [
+            ("adult_hand_bag", "adult_hold_bag", options.adults),
+            ("child_hand_bag", "child_hold_bag", options.children),
+        ] {
+            if passengers > 0 {
+                let bags = vec!["1"; passengers as usize].join(",");
+                bag_params.push((hand_bag, bags.clone()));
+                bag_params.push((hold_bag, bags));
+            }
+        }
+
+        log::info!("Comparing baggage fares from {} to {}", fly_from, fly_to);
+
+        let (mut basic, with_bags) = tokio::try_join!(
+            self.client.search_itineraries(&params),
+            self.client.search_itineraries(&bag_params),
+        )?;
+
+        options.apply_filters(&mut basic.data);
+
+        if basic.data.is_empty() {
+            return Ok(vec![ToolContent::Text {
+                text: String::from("No flights found matching your criteria."),
+            }]);
+        }
+
+        let bag_fares: HashMap<String, f64> = with_bags
+            .data
+            .iter()
+            .map(|itinerary| (itinerary.flights_key(), itinerary.price))
+            .collect();
+
+        let mut fares: Vec<Fare> = basic
+            .data
+            .iter()
+            .map(|itinerary| Fare {
+                itinerary,
+                basic: itinerary.price,
+                with_bags: bag_fares.get(&itinerary.flights_key()).copied(),
+            })
+            .collect();
+
+        if sort_by == FareSort::WithBags {
+            // Fares the bags couldn't be priced for go last
+            fares.sort_by(|a, b| {
+                let a = a.with_bags.unwrap_or(f64::INFINITY);
+                let b = b.with_bags.unwrap_or(f64::INFINITY);
+                a.total_cmp(&b)
+            });
+        } else {
+            fares.sort_by(|a, b| a.basic.total_cmp(&b.basic));
+        }
+
+        let mut result = format!(
+            "Basic fares vs fares with 1 cabin bag + 1 checked bag per passenger, sorted by {} fare:\n\n",
+            if sort_by == FareSort::Basic {
+                "basic"
+            } else {
+                "with-bags"
+            }
+        );
+
+        for (i, fare) in fares.iter().enumerate() {
+            let itinerary = fare.itinerary;
+            result.push_str(&format!(
+                "Flight {}: {} ({}) → {} ({}), {}, {}, {}\n",
+                i + 1,
+                itinerary.city_from,
+                itinerary.fly_from,
+                itinerary.city_to,
+                itinerary.fly_to,
+                itinerary
+                    .local_departure
+                    .as_deref()
+                    .map(format_local_time)
+                    .unwrap_or_else(|| "Unknown".to_string()),
+                options.duration(itinerary.duration.total),
+                itinerary.airlines.join(", ")
+            ));
+            result.push_str(&format!("  Basic fare: {}\n", options.price(fare.basic)));
+            match fare.with_bags {
+                Some(with_bags) => result.push_str(&format!(
+                    "  With bags: {} ({})\n",
+                    options.price(with_bags),
+                    options.price_format.signed(with_bags - fare.basic)
+                )),
+                None => result.push_str("  With bags: not available for these flights\n"),
+            }
+        }
+
+        options.push_notes(&mut result);
+        self.client.push_quota_warning(&mut result);
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "compare_baggage_fares".into(),
+            description: Some(
+                "Compare each flight's basic fare with its fare including one cabin bag and one checked bag per passenger"
+                    .into(),
+            ),
+            input_schema: input::schema::<CompareBaggageFaresArgs>(),
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    config::SearchDefaults,
+    input::{self, ToolInput},
+    model::cabin_name,
+    progress::join_with_progress,
+    search::{SearchArgs, SearchOptions},
+};
+
+const CABINS: [&str; 4] = ["M", "W", "C", "F"];
+
+/// The cabins compared while a request budget is tight.
+const SPARING_CABINS: [&str; 2] = ["M", "C"];
+
+#[derive(Deserialize, JsonSchema)]
+struct CompareCabinsArgs {
+    /// IATA code of departure location (e.g., 'LHR', 'NYC', 'UK')
+    fly_from: String,
+    /// IATA code of arrival location
+    fly_to: String,
+    /// Departure date in format dd/mm/yyyy
+    date_from: String,
+    /// Latest departure date in format dd/mm/yyyy
+    date_to: String,
+    /// Return departure date in format dd/mm/yyyy (for round trips)
+    return_from: Option<String>,
+    /// Latest return departure date in format dd/mm/yyyy (for round trips)
+    return_to: Option<String>,
+    #[serde(flatten)]
+    search: SearchArgs,
+}
+
+impl ToolInput for CompareCabinsArgs {}
+
+/// Runs the same search in every cabin class and compares the results.
+pub struct CompareCabinsTool {
+    client: Arc<KiwiClient>,
+    defaults: SearchDefaults,
+}
+
+impl CompareCabinsTool {
+    pub fn new(client: Arc<KiwiClient>, defaults: SearchDefaults) -> Self {
+        Self { client, defaults }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for CompareCabinsTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing CompareCabinsTool");
+        let args: CompareCabinsArgs = input::parse(arguments)?;
+
+        let fly_from = args.fly_from.as_str();
+        let fly_to = args.fly_to.as_str();
+        let date_from = args.date_from.as_str();
+        let date_to = args.date_to.as_str();
+        let return_from = args.return_from.as_deref();
+        let return_to = args.return_to.as_deref();
+        let options = SearchOptions::from_args(&args.search, &self.defaults)?;
+
+        let sparing = self.client.budget().is_tight();
+        let cabins: &[&str] = if sparing { &SPARING_CABINS } else { &CABINS };
+        let searches = cabins.iter().map(|cabin| {
+            let mut params = vec![
+                ("fly_from", fly_from.to_string()),
+                ("fly_to", fly_to.to_string()),
+                ("date_from", date_from.to_string()),
+                ("date_to", date_to.to_string()),
+            ];
+            params.extend(
+                SearchOptions {
+                    selected_cabins: cabin.to_string(),
+                    mix_with_cabins: None,
+                    sort: "price".into(),
+                    ..options.clone()
+                }
+                .params(),
+            );
+            if let Some(return_from) = return_from {
+                params.push(("return_from", return_from.to_string()));
+            }
+            if let Some(return_to) = return_to {
+                params.push(("return_to", return_to.to_string()));
+            }
+
+            async move { (*cabin, self.client.search_itineraries(&params).await) }
+        });
+
+        log::info!(
+            "Comparing cabins for flights from {} to {}",
+            fly_from,
+            fly_to
+        );
+
+        let responses =
+            join_with_progress(self.client.progress(), searches, |(cabin, response)| {
+                let response = response.as_ref().ok()?;
+                let cheapest = response
+                    .data
+                    .iter()
+                    .map(|itinerary| itinerary.price)
+                    .min_by(f64::total_cmp)?;
+                Some(format!(
+                    "{} from {}",
+                    cabin_name(cabin),
+                    options.price(cheapest)
+                ))
+            })
+            .await;
+
+        let mut economy_price = None;
+        let mut result = format!(
+            "Cabin comparison for {} → {} (prices in {}):\n\n",
+            fly_from, fly_to, options.curr
+        );
+
+        for (cabin, response) in responses {
+            let name = cabin_name(cabin);
+            let flights = match response {
+                Ok(mut response) => {
+                    options.apply_filters(&mut response.data);
+                    response.data
+                }
+                Err(err) => {
+                    log::warn!("Search for cabin {} failed: {}", cabin, err);
+                    result.push_str(&format!("{}: search failed\n", name));
+                    continue;
+                }
+            };
+
+            let Some(cheapest) = flights.iter().min_by(|a, b| a.price.total_cmp(&b.price)) else {
+                result.push_str(&format!("{}: no flights found\n", name));
+                continue;
+            };
+            let fastest = flights
+                .iter()
+                .map(|flight| flight.duration.total)
+                .min()
+                .unwrap_or(cheapest.duration.total);
+
+            result.push_str(&format!(
+                "{}: from {}, {} ({}), fastest option {}",
+                name,
+                options.price_format.amount(cheapest.price),
+                options.duration(cheapest.duration.total),
+                cheapest.airlines.join(", "),
+                options.duration(fastest)
+            ));
+
+            match economy_price {
+                None if cabin == "M" => economy_price = Some(cheapest.price),
+                Some(economy) if economy > 0.0 => result.push_str(&format!(
+                    ", {} ({:+.0}%) vs economy",
+                    options.price_format.signed(cheapest.price - economy),
+                    (cheapest.price - economy) / economy * 100.0
+                )),
+                _ => {}
+            }
+            result.push('\n');
+        }
+        if sparing {
+            result.push_str(
+                "Premium economy and first class were skipped to save the request budget.\n",
+            );
+        }
+
+        options.push_notes(&mut result);
+        self.client.push_quota_warning(&mut result);
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "compare_cabins".into(),
+            description: Some(
+                "Compare the cheapest price and duration of the same trip in economy, premium economy, business and first class"
+                    .into(),
+            ),
+            input_schema: input::schema::<CompareCabinsArgs>(),
+        }
+    }
+}
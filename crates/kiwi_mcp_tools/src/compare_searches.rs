@@ -0,0 +1,133 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    diff::ResultDiff,
+    error::KiwiToolError,
+    input::{self, ToolInput},
+    money::PriceFormat,
+    store::{SearchStore, StoredSearch},
+};
+
+#[derive(Deserialize, JsonSchema)]
+struct CompareSearchesArgs {
+    /// Search ID of the baseline results
+    first_search_id: String,
+    /// Search ID of the results to compare against it
+    second_search_id: String,
+}
+
+impl ToolInput for CompareSearchesArgs {}
+
+/// Diffs the results of two stored searches.
+pub struct CompareSearchesTool {
+    store: Arc<SearchStore>,
+    price_format: PriceFormat,
+}
+
+impl CompareSearchesTool {
+    pub fn new(store: Arc<SearchStore>, price_format: PriceFormat) -> Self {
+        Self {
+            store,
+            price_format,
+        }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for CompareSearchesTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing CompareSearchesTool");
+        let args: CompareSearchesArgs = input::parse(arguments)?;
+
+        let first = self.store.get(args.first_search_id.as_str())?;
+        let second = self.store.get(args.second_search_id.as_str())?;
+
+        if first.currency != second.currency {
+            return Err(KiwiToolError::validation(format!(
+                "Search {} is priced in {} and search {} in {}; compare searches in the same currency",
+                first.id, first.currency, second.id, second.currency
+            ))
+            .into());
+        }
+
+        let mut result = format!(
+            "Search {} ({}) vs search {} ({}):\n\n",
+            first.id,
+            describe(&first),
+            second.id,
+            describe(&second)
+        );
+
+        let cheapest = |search: &StoredSearch| {
+            search
+                .itineraries
+                .iter()
+                .map(|itinerary| itinerary.price)
+                .reduce(f64::min)
+        };
+        if let (Some(a), Some(b)) = (cheapest(&first), cheapest(&second)) {
+            result.push_str(&format!(
+                "Cheapest: {} vs {} ({})\n\n",
+                self.price_format.price(a, &first.currency),
+                self.price_format.price(b, &first.currency),
+                self.price_format.signed(b - a)
+            ));
+        }
+
+        result.push_str(
+            &ResultDiff::new(&first.itineraries, &second.itineraries)
+                .format(&first.currency, &self.price_format),
+        );
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "compare_searches".into(),
+            description: Some(
+                "Compare the results of two earlier searches: new, removed and repriced flights and the change in the cheapest price"
+                    .into(),
+            ),
+            input_schema: input::schema::<CompareSearchesArgs>(),
+        }
+    }
+}
+
+/// Route, dates and cabin a search was made with.
+fn describe(search: &StoredSearch) -> String {
+    let param = |name: &str| {
+        search
+            .params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    let mut description = format!(
+        "{} → {}, {}-{}",
+        param("fly_from").unwrap_or("?"),
+        param("fly_to").unwrap_or("?"),
+        param("date_from").unwrap_or("?"),
+        param("date_to").unwrap_or("?")
+    );
+    if let Some(return_from) = param("return_from") {
+        description.push_str(&format!(
+            ", returning {}-{}",
+            return_from,
+            param("return_to").unwrap_or("?")
+        ));
+    }
+    if let Some(cabin) = param("selected_cabins") {
+        description.push_str(&format!(", cabin {}", cabin));
+    }
+
+    description
+}
@@ -0,0 +1,1041 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    encryption::Cipher, error::KiwiToolError, formatter, money::PriceFormat, paths,
+    search::validate_passengers,
+};
+
+/// Server configuration, read from a TOML file.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    /// Tequila API key. `KIWI_API_KEY` takes precedence when set.
+    pub api_key: Option<String>,
+    /// Values used for optional search parameters the caller leaves out.
+    pub defaults: SearchDefaults,
+    /// Names of the tools to expose. Every tool is enabled when unset.
+    pub enabled_tools: Option<Vec<String>>,
+    pub rate_limit: RateLimitConfig,
+    pub http: HttpConfig,
+    pub cache: CacheConfig,
+    /// Directory for persisted data such as the search history. Defaults to
+    /// `$XDG_DATA_HOME/kiwi-mcp` or the platform equivalent.
+    pub data_dir: Option<PathBuf>,
+    pub airline_quality: AirlineQualityConfig,
+    pub api: ApiConfig,
+    pub prefetch: PrefetchConfig,
+    pub runtime: RuntimeConfig,
+    pub idle: IdleConfig,
+    pub encryption: EncryptionConfig,
+    pub tools: ToolsConfig,
+    pub budget: BudgetConfig,
+    pub saved_searches: SavedSearchesConfig,
+    pub notifications: NotificationsConfig,
+}
+
+/// The life of saved searches with a target price.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SavedSearchesConfig {
+    /// Checks in a row at or below the target price after which a saved
+    /// search expires. 0 keeps it triggered.
+    pub expire_after_checks: u32,
+}
+
+impl Default for SavedSearchesConfig {
+    fn default() -> Self {
+        Self {
+            expire_after_checks: 3,
+        }
+    }
+}
+
+/// Limits on the notifications the background refresh sends.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// Hour, in the server's local time, from which notifications are held
+    /// back. Both hours must be set for quiet hours.
+    pub quiet_start_hour: Option<u32>,
+    /// Hour at which quiet hours end and held notifications are sent.
+    pub quiet_end_hour: Option<u32>,
+    /// Most notifications sent per day. 0 for no limit.
+    pub max_per_day: u32,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            quiet_start_hour: None,
+            quiet_end_hour: None,
+            max_per_day: 10,
+        }
+    }
+}
+
+impl NotificationsConfig {
+    /// Whether `hour` falls in the quiet hours, which may span midnight.
+    pub fn is_quiet(&self, hour: u32) -> bool {
+        match (self.quiet_start_hour, self.quiet_end_hour) {
+            (Some(start), Some(end)) if start <= end => (start..end).contains(&hour),
+            (Some(start), Some(end)) => hour >= start || hour < end,
+            _ => false,
+        }
+    }
+}
+
+/// Limits on tool calls.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ToolsConfig {
+    /// Seconds a tool call may run before it is abandoned. 0 for no limit.
+    pub timeout_secs: u64,
+    /// Limits for particular tools, by name, in place of `timeout_secs`.
+    pub timeouts: BTreeMap<String, u64>,
+}
+
+impl Default for ToolsConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 300,
+            timeouts: BTreeMap::new(),
+        }
+    }
+}
+
+impl ToolsConfig {
+    /// The time limit for calls to `tool`, if any.
+    pub fn timeout(&self, tool: &str) -> Option<Duration> {
+        let secs = self
+            .timeouts
+            .get(tool)
+            .copied()
+            .unwrap_or(self.timeout_secs);
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PrefetchConfig {
+    /// Refresh saved searches and popular routes in the background.
+    pub enabled: bool,
+    /// Seconds between prefetch rounds.
+    pub interval_secs: u64,
+    /// Only prefetch after this many seconds without a search.
+    pub idle_secs: u64,
+    /// Searches of a route in the last day that make it worth prefetching.
+    pub min_searches: usize,
+    /// Most popular routes prefetched per round, besides saved searches.
+    pub max_routes: usize,
+    /// Once a day, after refreshing the saved searches, tell the client the
+    /// saved searches digest is ready.
+    pub notify_digest: bool,
+    /// Tell the client when a refreshed saved search reaches its trigger.
+    pub notify_triggers: bool,
+}
+
+impl Default for PrefetchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 600,
+            idle_secs: 60,
+            min_searches: 3,
+            max_routes: 5,
+            notify_digest: false,
+            notify_triggers: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// Base URL of the Tequila API, e.g. for a proxy or a mock server.
+    pub base_url: Option<String>,
+    /// `User-Agent` sent with requests to the Kiwi API.
+    pub user_agent: Option<String>,
+    /// Extra headers sent with every request, e.g. for an API gateway.
+    pub headers: BTreeMap<String, String>,
+}
+
+impl ApiConfig {
+    /// Every configured header, the user agent included.
+    pub fn request_headers(&self) -> Vec<(String, String)> {
+        let mut headers: Vec<(String, String)> = self
+            .headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        if let Some(user_agent) = &self.user_agent {
+            headers.push(("User-Agent".to_string(), user_agent.clone()));
+        }
+        headers
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AirlineQualityConfig {
+    /// JSON file of airline ratings used to annotate results.
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// How long API responses are reused, in seconds. Zero disables caching.
+    pub ttl_secs: u64,
+    /// Longest a response is reused, in seconds. Responses for departures
+    /// further ahead are kept longer than `ttl_secs`, up to this.
+    pub max_ttl_secs: u64,
+    /// Also keep cached responses on disk so they survive restarts.
+    pub persist: bool,
+    /// Directory for the on-disk cache. Defaults to
+    /// `$XDG_CACHE_HOME/kiwi-mcp` or the platform equivalent.
+    pub dir: Option<PathBuf>,
+    /// Largest the on-disk cache may grow, in megabytes. Expired entries are
+    /// removed first, then the oldest.
+    pub max_disk_mb: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: 300,
+            max_ttl_secs: 6 * 60 * 60,
+            persist: false,
+            dir: None,
+            max_disk_mb: 100,
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Where the on-disk cache lives, or `None` when it is disabled.
+    pub fn disk_dir(&self) -> Option<PathBuf> {
+        if !self.persist {
+            return None;
+        }
+
+        self.dir.clone().or_else(paths::cache_dir)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// Maximum requests sent to the Kiwi API per minute. Unlimited when unset.
+    pub requests_per_minute: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// Bearer token that unlocks the admin tools over HTTP.
+    /// `KIWI_MCP_AUTH_TOKEN` takes precedence when set.
+    pub auth_token: Option<String>,
+    /// Serve over HTTP on this address when `serve` is run without
+    /// `--http`.
+    pub listen: Option<SocketAddr>,
+}
+
+/// Encryption of saved searches and the search history on disk.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EncryptionConfig {
+    pub enabled: bool,
+    pub key_source: KeySource,
+    /// Environment variable holding the passphrase, for the `passphrase`
+    /// key source.
+    pub passphrase_env: String,
+}
+
+impl Default for EncryptionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            key_source: KeySource::Passphrase,
+            passphrase_env: DEFAULT_PASSPHRASE_ENV.into(),
+        }
+    }
+}
+
+impl EncryptionConfig {
+    /// The cipher to store data with, or `None` when encryption is off.
+    /// Passphrase-derived keys use the salt kept at `salt_path`, so every
+    /// run derives the same key.
+    pub fn cipher(&self, salt_path: Option<&Path>) -> Result<Option<Cipher>> {
+        if !self.enabled {
+            return Ok(None);
+        }
+
+        match self.key_source {
+            KeySource::Passphrase => {
+                let passphrase = env::var(&self.passphrase_env)
+                    .ok()
+                    .filter(|passphrase| !passphrase.is_empty())
+                    .ok_or_else(|| {
+                        KiwiToolError::config(format!(
+                            "Encryption is enabled but {} is not set",
+                            self.passphrase_env
+                        ))
+                    })?;
+                let cipher = Cipher::from_passphrase(&passphrase);
+                match salt_path {
+                    Some(path) => Ok(Some(cipher.with_salt_file(path)?)),
+                    None => Ok(Some(cipher)),
+                }
+            }
+            #[cfg(feature = "keyring")]
+            KeySource::Keyring => Ok(Some(Cipher::from_keyring()?)),
+            #[cfg(not(feature = "keyring"))]
+            KeySource::Keyring => Err(KiwiToolError::config(
+                "This build has no OS keyring support; rebuild with the `keyring` feature or use a passphrase",
+            )
+            .into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySource {
+    /// Derive the key from a passphrase in an environment variable.
+    Passphrase,
+    /// Keep a generated key in the OS keyring.
+    Keyring,
+}
+
+/// Caps on Kiwi API requests, for plans with a limited allowance.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BudgetConfig {
+    /// Requests allowed per UTC day. No limit when unset.
+    pub daily_requests: Option<u64>,
+    /// Requests allowed per calendar month. No limit when unset.
+    pub monthly_requests: Option<u64>,
+    pub on_exceeded: BudgetAction,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetAction {
+    /// Keep searching, with a warning in every result.
+    #[default]
+    Warn,
+    /// Refuse searches the cache can't answer until the budget resets.
+    Refuse,
+}
+
+/// What happens when no client has sent a request for a while.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct IdleConfig {
+    /// Minutes without a request before `action` is taken. Never when unset.
+    pub timeout_mins: Option<u64>,
+    pub action: IdleAction,
+}
+
+impl IdleConfig {
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_mins
+            .filter(|mins| *mins > 0)
+            .map(|mins| Duration::from_secs(mins * 60))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdleAction {
+    /// End the process.
+    #[default]
+    Exit,
+    /// Stop background work until the next request.
+    Pause,
+}
+
+/// Async runtime sizing. Read once at startup; tokio's defaults apply to
+/// anything left unset.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RuntimeConfig {
+    /// Threads running async tasks. Defaults to one per CPU core.
+    pub worker_threads: Option<usize>,
+    /// Most threads kept for blocking work such as file I/O.
+    pub max_blocking_threads: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SearchDefaults {
+    pub adults: u64,
+    pub children: u64,
+    pub infants: u64,
+    pub selected_cabins: String,
+    pub curr: String,
+    pub max_stopovers: u64,
+    pub sort: String,
+    pub limit: u64,
+    /// Connections shorter than this, in minutes, are flagged as tight.
+    pub tight_connection_minutes: u64,
+    /// The same, for connections where checked bags must be collected and
+    /// checked in again (typically on entering a new country).
+    pub tight_recheck_minutes: u64,
+    /// Local hour from which departures and arrivals count as red-eye.
+    pub night_start_hour: u32,
+    /// Local hour at which the red-eye window ends.
+    pub night_end_hour: u32,
+    /// Passenger nationality (ISO country code) for travel document hints.
+    pub nationality: Option<String>,
+    /// Airline rules for travelling with infants, flagged in results when a
+    /// search includes infants.
+    pub infant_policies: Vec<InfantPolicy>,
+    /// Airports or cities tried for self-connections when a search finds
+    /// nothing.
+    pub hubs: Vec<String>,
+    /// Long-haul gateways tried for split tickets on intercontinental trips.
+    /// Only those on the traveller's continent are used.
+    pub gateways: Vec<String>,
+    /// Locale prices are formatted for, e.g. `en-GB` or `de-DE`.
+    pub locale: String,
+    /// Decimal places prices are rounded to.
+    pub price_decimals: usize,
+    /// How trips of several tickets are written when a call doesn't say:
+    /// `text`, `markdown`, `json` or `compact`.
+    pub output_format: String,
+}
+
+impl SearchDefaults {
+    pub fn price_format(&self) -> Result<PriceFormat> {
+        PriceFormat::new(&self.locale, self.price_decimals)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct InfantPolicy {
+    /// IATA airline code.
+    pub airline: String,
+    /// Whether infant fares can be booked online.
+    #[serde(default = "default_true")]
+    pub sells_online: bool,
+    pub note: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for SearchDefaults {
+    fn default() -> Self {
+        Self {
+            adults: 1,
+            children: 0,
+            infants: 0,
+            selected_cabins: "M".into(),
+            curr: "EUR".into(),
+            max_stopovers: 2,
+            sort: "price".into(),
+            limit: 5,
+            tight_connection_minutes: 60,
+            tight_recheck_minutes: 120,
+            night_start_hour: 22,
+            night_end_hour: 6,
+            nationality: None,
+            infant_policies: Vec::new(),
+            hubs: ["LON", "PAR", "FRA", "AMS", "MAD", "IST", "DXB", "DOH"]
+                .map(String::from)
+                .to_vec(),
+            gateways: [
+                "LON", "PAR", "FRA", "AMS", "MAD", "IST", "NYC", "CHI", "LAX", "MIA", "YTO", "DXB",
+                "DOH", "SIN", "BKK", "HKG", "TYO", "SYD", "SAO", "JNB",
+            ]
+            .map(String::from)
+            .to_vec(),
+            locale: "en".into(),
+            price_decimals: 2,
+            output_format: "text".into(),
+        }
+    }
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/kiwi-mcp/config.toml` or the platform equivalent.
+    pub fn default_path() -> Option<PathBuf> {
+        paths::config_file()
+    }
+
+    /// Returns the file to read: `explicit` if given, otherwise the default
+    /// path when it exists.
+    pub fn locate(explicit: Option<&Path>) -> Option<PathBuf> {
+        match explicit {
+            Some(path) => Some(path.to_path_buf()),
+            None => Self::default_path().filter(|path| path.exists()),
+        }
+    }
+
+    /// Loads the located configuration file, falling back to defaults when
+    /// there is none. `KIWI_MCP_*` environment variables apply either way.
+    pub fn load(explicit: Option<&Path>) -> Result<Self> {
+        match Self::locate(explicit) {
+            Some(path) => Self::from_file(&path),
+            None => Self::from_table(toml::Table::new(), "the environment"),
+        }
+    }
+
+    /// Reads the file with `KIWI_MCP_*` environment variables layered over
+    /// it.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+
+        let table: toml::Table = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))?;
+
+        Self::from_table(table, &format!("config file {}", path.display()))
+    }
+
+    /// Applies the environment to `table` and validates the result,
+    /// reporting every problem found at once rather than failing on first
+    /// use of a bad setting. `source` names where `table` came from.
+    fn from_table(mut table: toml::Table, source: &str) -> Result<Self> {
+        let overrides = apply_env_overrides(&mut table, env::vars(), |table| {
+            table.clone().try_into::<Self>().is_ok()
+        })?;
+        let source = if overrides.is_empty() {
+            source.to_string()
+        } else {
+            let names: Vec<&str> = overrides.iter().map(|(name, _)| name.as_str()).collect();
+            format!("{} with {}", source, names.join(", "))
+        };
+
+        let config: Self = table
+            .clone()
+            .try_into()
+            .with_context(|| format!("Failed to parse {}", source))?;
+
+        // Other tools may use the prefix too, so a variable naming no
+        // setting is only warned about
+        let mut problems = Vec::new();
+        for key in config.unknown_keys(&table)? {
+            match overrides
+                .iter()
+                .find(|(_, path)| *path == key || path.starts_with(&format!("{}.", key)))
+            {
+                Some((name, _)) => log::warn!("Ignoring {}, which names no setting", name),
+                None => problems.push(format!("unknown key `{}`", key)),
+            }
+        }
+        problems.extend(config.problems());
+        if !problems.is_empty() {
+            let list: Vec<String> = problems
+                .iter()
+                .map(|problem| format!("  - {}", problem))
+                .collect();
+            return Err(KiwiToolError::config(format!(
+                "Invalid settings in {}:\n{}",
+                source,
+                list.join("\n")
+            ))
+            .into());
+        }
+
+        Ok(config)
+    }
+
+    /// Keys in `file` that no setting reads, such as misspellings.
+    fn unknown_keys(&self, file: &toml::Table) -> Result<Vec<String>> {
+        let known = toml::Table::try_from(self)?;
+        let mut unknown = Vec::new();
+        collect_unknown_keys(file, &known, "", &mut unknown);
+        Ok(unknown)
+    }
+
+    /// Settings that parse but can't work.
+    fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut check = |ok: bool, problem: String| {
+            if !ok {
+                problems.push(problem);
+            }
+        };
+        let defaults = &self.defaults;
+
+        check(
+            is_code(&defaults.curr, 3),
+            format!(
+                "defaults.curr: `{}` is not a three-letter currency code such as EUR",
+                defaults.curr
+            ),
+        );
+        check(
+            ["M", "W", "C", "F"].contains(&defaults.selected_cabins.as_str()),
+            format!(
+                "defaults.selected_cabins: `{}` is not one of M, W, C or F",
+                defaults.selected_cabins
+            ),
+        );
+        check(
+            ["price", "duration", "date", "quality"].contains(&defaults.sort.as_str()),
+            format!(
+                "defaults.sort: `{}` is not one of price, duration, date or quality",
+                defaults.sort
+            ),
+        );
+        check(
+            defaults.limit > 0,
+            "defaults.limit: must be at least 1".into(),
+        );
+        for (name, limit) in [
+            ("daily_requests", self.budget.daily_requests),
+            ("monthly_requests", self.budget.monthly_requests),
+        ] {
+            check(
+                limit != Some(0),
+                format!(
+                    "budget.{}: must be at least 1, or left out for no limit",
+                    name
+                ),
+            );
+        }
+        check(
+            formatter::find(&defaults.output_format).is_some(),
+            format!(
+                "defaults.output_format: `{}` is not one of {}",
+                defaults.output_format,
+                formatter::names().collect::<Vec<_>>().join(", ")
+            ),
+        );
+        if let Err(err) =
+            validate_passengers(defaults.adults, defaults.children, defaults.infants, false)
+        {
+            problems.push(format!("defaults: {}", err));
+        }
+        for (name, hour) in [
+            ("night_start_hour", defaults.night_start_hour),
+            ("night_end_hour", defaults.night_end_hour),
+        ] {
+            if hour > 23 {
+                problems.push(format!(
+                    "defaults.{}: {} is not an hour of the day",
+                    name, hour
+                ));
+            }
+        }
+        if let Err(err) = defaults.price_format() {
+            problems.push(format!("defaults.locale: {}", err));
+        }
+        if let Some(nationality) = &defaults.nationality
+            && !is_code(nationality, 2)
+        {
+            problems.push(format!(
+                "defaults.nationality: `{}` is not a two-letter country code",
+                nationality
+            ));
+        }
+        for hub in defaults.hubs.iter().filter(|hub| !is_code(hub, 3)) {
+            problems.push(format!(
+                "defaults.hubs: `{}` is not a three-letter airport or city code",
+                hub
+            ));
+        }
+        for gateway in defaults
+            .gateways
+            .iter()
+            .filter(|gateway| !is_code(gateway, 3))
+        {
+            problems.push(format!(
+                "defaults.gateways: `{}` is not a three-letter airport or city code",
+                gateway
+            ));
+        }
+        for policy in &defaults.infant_policies {
+            if policy.airline.len() != 2
+                || !policy.airline.chars().all(|c| c.is_ascii_alphanumeric())
+            {
+                problems.push(format!(
+                    "defaults.infant_policies: `{}` is not a two-character airline code",
+                    policy.airline
+                ));
+            }
+        }
+
+        let notifications = &self.notifications;
+        for (name, hour) in [
+            ("quiet_start_hour", notifications.quiet_start_hour),
+            ("quiet_end_hour", notifications.quiet_end_hour),
+        ] {
+            if let Some(hour) = hour
+                && hour > 23
+            {
+                problems.push(format!(
+                    "notifications.{}: {} is not an hour of the day",
+                    name, hour
+                ));
+            }
+        }
+        match (notifications.quiet_start_hour, notifications.quiet_end_hour) {
+            (Some(_), None) | (None, Some(_)) => problems.push(
+                "notifications: set both quiet_start_hour and quiet_end_hour, or neither".into(),
+            ),
+            (Some(start), Some(end)) if start == end => problems
+                .push("notifications: quiet_start_hour and quiet_end_hour must differ".into()),
+            _ => {}
+        }
+
+        if let Some(base_url) = &self.api.base_url
+            && let Err(err) = Url::parse(base_url)
+        {
+            problems.push(format!(
+                "api.base_url: `{}` is not a URL ({})",
+                base_url, err
+            ));
+        }
+        for (name, value) in self.api.request_headers() {
+            if name.is_empty() || !name.chars().all(is_header_name_char) {
+                problems.push(format!(
+                    "api.headers: `{}` is not a valid header name",
+                    name
+                ));
+            }
+            if value.chars().any(|c| c.is_ascii_control() && c != '\t') {
+                problems.push(format!(
+                    "api.headers: the value of `{}` contains control characters",
+                    name
+                ));
+            }
+        }
+
+        if let Some(file) = &self.airline_quality.file
+            && !file.exists()
+        {
+            problems.push(format!(
+                "airline_quality.file: {} does not exist",
+                file.display()
+            ));
+        }
+
+        if self.encryption.enabled
+            && self.encryption.key_source == KeySource::Passphrase
+            && env::var(&self.encryption.passphrase_env).is_err()
+        {
+            problems.push(format!(
+                "encryption: the passphrase variable {} is not set",
+                self.encryption.passphrase_env
+            ));
+        }
+        if self.encryption.key_source == KeySource::Keyring && !cfg!(feature = "keyring") {
+            problems.push(
+                "encryption.key_source: this build has no OS keyring support (the `keyring` feature)"
+                    .into(),
+            );
+        }
+
+        for (name, threads) in [
+            ("worker_threads", self.runtime.worker_threads),
+            ("max_blocking_threads", self.runtime.max_blocking_threads),
+        ] {
+            if threads == Some(0) {
+                problems.push(format!("runtime.{}: must be at least 1", name));
+            }
+        }
+
+        problems
+    }
+
+    pub fn api_key(&self) -> Option<String> {
+        env::var("KIWI_API_KEY")
+            .ok()
+            .or_else(|| self.api_key.clone())
+    }
+
+    pub fn auth_token(&self) -> Option<String> {
+        env::var("KIWI_MCP_AUTH_TOKEN")
+            .ok()
+            .or_else(|| self.http.auth_token.clone())
+            .filter(|token| !token.is_empty())
+    }
+
+    pub fn data_dir(&self) -> Option<PathBuf> {
+        self.data_dir.clone().or_else(paths::data_dir)
+    }
+
+    pub fn history_path(&self) -> Option<PathBuf> {
+        self.data_dir().map(|dir| dir.join(paths::HISTORY_FILE))
+    }
+
+    pub fn bookmarks_path(&self) -> Option<PathBuf> {
+        self.data_dir().map(|dir| dir.join(paths::BOOKMARKS_FILE))
+    }
+
+    pub fn request_counts_path(&self) -> Option<PathBuf> {
+        self.data_dir()
+            .map(|dir| dir.join(paths::REQUEST_COUNTS_FILE))
+    }
+
+    pub fn salt_path(&self) -> Option<PathBuf> {
+        self.data_dir().map(|dir| dir.join(paths::SALT_FILE))
+    }
+
+    pub fn is_tool_enabled(&self, name: &str) -> bool {
+        self.enabled_tools
+            .as_ref()
+            .is_none_or(|enabled| enabled.iter().any(|tool| tool == name))
+    }
+}
+
+/// Adds the keys of `file` missing from `known`, a fully populated
+/// configuration, to `unknown` as dotted paths.
+fn collect_unknown_keys(
+    file: &toml::Table,
+    known: &toml::Table,
+    prefix: &str,
+    unknown: &mut Vec<String>,
+) {
+    for (key, value) in file {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+
+        match (value, known.get(key)) {
+            (_, None) => unknown.push(path),
+            (toml::Value::Table(file), Some(toml::Value::Table(known))) => {
+                collect_unknown_keys(file, known, &path, unknown)
+            }
+            (toml::Value::Array(file), Some(toml::Value::Array(known))) => {
+                for (file, known) in file.iter().zip(known) {
+                    if let (toml::Value::Table(file), toml::Value::Table(known)) = (file, known) {
+                        collect_unknown_keys(file, known, &path, unknown);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Prefix of the environment variables that override settings, with `__`
+/// between nested keys: `KIWI_MCP_DEFAULTS__CURR=GBP` sets `defaults.curr`.
+const ENV_PREFIX: &str = "KIWI_MCP_";
+
+/// Variables under [`ENV_PREFIX`] that are read directly instead, along
+/// with the configured passphrase variable.
+const RESERVED_ENV: [&str; 2] = ["KIWI_MCP_AUTH_TOKEN", "KIWI_MCP_HOME"];
+
+const DEFAULT_PASSPHRASE_ENV: &str = "KIWI_MCP_PASSPHRASE";
+
+/// Sets the keys named by `KIWI_MCP_*` variables in `table`. Values are
+/// read as TOML where they parse (numbers, booleans, arrays) and `fits`
+/// accepts the table with them, and as plain strings otherwise, so
+/// `KIWI_MCP_HTTP__AUTH_TOKEN=12345678` stays a string. Returns the
+/// variables applied with the keys they set.
+fn apply_env_overrides(
+    table: &mut toml::Table,
+    vars: impl Iterator<Item = (String, String)>,
+    fits: impl Fn(&toml::Table) -> bool,
+) -> Result<Vec<(String, String)>> {
+    let vars: Vec<(String, String)> = vars.collect();
+    let passphrase_env = vars
+        .iter()
+        .find(|(name, _)| name == "KIWI_MCP_ENCRYPTION__PASSPHRASE_ENV")
+        .map(|(_, raw)| raw.clone())
+        .or_else(|| {
+            table
+                .get("encryption")
+                .and_then(|encryption| encryption.get("passphrase_env"))
+                .and_then(|name| name.as_str())
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| DEFAULT_PASSPHRASE_ENV.to_string());
+
+    let mut applied = Vec::new();
+    for (name, raw) in vars {
+        let Some(key) = name.strip_prefix(ENV_PREFIX) else {
+            continue;
+        };
+        if key.is_empty() || RESERVED_ENV.contains(&name.as_str()) || name == passphrase_env {
+            continue;
+        }
+
+        let path: Vec<String> = key.split("__").map(str::to_ascii_lowercase).collect();
+        let (last, sections) = path.split_last().expect("split yields at least one part");
+
+        let mut section = &mut *table;
+        for part in sections {
+            section = section
+                .entry(part.clone())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                .as_table_mut()
+                .ok_or_else(|| {
+                    KiwiToolError::config(format!("{}: `{}` is not a section", name, part))
+                })?;
+        }
+        let value = env_value(&raw);
+        let typed = !value.is_str();
+        section.insert(last.clone(), value);
+
+        if typed && !fits(table) {
+            let mut as_string = table.clone();
+            set(&mut as_string, &path, toml::Value::String(raw));
+            if fits(&as_string) {
+                *table = as_string;
+            }
+        }
+        applied.push((name, path.join(".")));
+    }
+
+    Ok(applied)
+}
+
+fn env_value(raw: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+/// Sets the key at `path`, whose sections exist, in `table`.
+fn set(table: &mut toml::Table, path: &[String], value: toml::Value) {
+    let (last, sections) = path.split_last().expect("paths are never empty");
+    let section = sections.iter().fold(table, |section, part| {
+        section
+            .get_mut(part)
+            .and_then(toml::Value::as_table_mut)
+            .expect("sections were created when the key was first set")
+    });
+    section.insert(last.clone(), value);
+}
+
+/// Whether `code` is `len` ASCII letters.
+fn is_code(code: &str, len: usize) -> bool {
+    code.len() == len && code.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+/// Characters allowed in an HTTP header name.
+fn is_header_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(vars: &[(&str, &str)]) -> impl Iterator<Item = (String, String)> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    fn fits_config(table: &toml::Table) -> bool {
+        table.clone().try_into::<Config>().is_ok()
+    }
+
+    fn overridden(env: &[(&str, &str)]) -> (toml::Table, Vec<(String, String)>) {
+        let mut table = toml::Table::new();
+        let applied = apply_env_overrides(&mut table, vars(env), fits_config).unwrap();
+        (table, applied)
+    }
+
+    #[test]
+    fn env_overrides_set_nested_keys() {
+        let (table, applied) = overridden(&[
+            ("KIWI_MCP_DEFAULTS__CURR", "GBP"),
+            ("KIWI_MCP_DEFAULTS__HUBS", r#"["LON", "AMS"]"#),
+            ("KIWI_MCP_RATE_LIMIT__REQUESTS_PER_MINUTE", "30"),
+            ("PATH", "/usr/bin"),
+        ]);
+        let config: Config = table.try_into().unwrap();
+
+        assert_eq!(config.defaults.curr, "GBP");
+        assert_eq!(config.defaults.hubs, ["LON", "AMS"]);
+        assert_eq!(config.rate_limit.requests_per_minute, Some(30));
+        assert_eq!(
+            applied,
+            [
+                ("KIWI_MCP_DEFAULTS__CURR".into(), "defaults.curr".into()),
+                ("KIWI_MCP_DEFAULTS__HUBS".into(), "defaults.hubs".into()),
+                (
+                    "KIWI_MCP_RATE_LIMIT__REQUESTS_PER_MINUTE".into(),
+                    "rate_limit.requests_per_minute".into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn env_values_that_only_fit_as_strings_stay_strings() {
+        let (table, _) = overridden(&[
+            ("KIWI_MCP_HTTP__AUTH_TOKEN", "12345678"),
+            ("KIWI_MCP_API__HEADERS__X_TENANT", "42"),
+        ]);
+        let config: Config = table.try_into().unwrap();
+
+        assert_eq!(config.http.auth_token.as_deref(), Some("12345678"));
+        assert_eq!(config.api.headers["x_tenant"], "42");
+    }
+
+    #[test]
+    fn env_overrides_skip_reserved_and_passphrase_variables() {
+        let (table, applied) = overridden(&[
+            ("KIWI_MCP_AUTH_TOKEN", "token"),
+            ("KIWI_MCP_HOME", "/srv/kiwi"),
+            ("KIWI_MCP_PASSPHRASE", "secret"),
+        ]);
+        assert!(table.is_empty());
+        assert!(applied.is_empty());
+
+        let mut table: toml::Table =
+            toml::from_str("[encryption]\npassphrase_env = \"KIWI_MCP_KEY\"").unwrap();
+        apply_env_overrides(&mut table, vars(&[("KIWI_MCP_KEY", "secret")]), fits_config).unwrap();
+        assert!(!table.contains_key("key"));
+    }
+
+    #[test]
+    fn env_overrides_refuse_to_nest_under_a_value() {
+        let mut table: toml::Table = toml::from_str("[defaults]\ncurr = \"EUR\"").unwrap();
+        let err = apply_env_overrides(
+            &mut table,
+            vars(&[("KIWI_MCP_DEFAULTS__CURR__CODE", "GBP")]),
+            fits_config,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("`curr` is not a section"));
+    }
+
+    #[test]
+    fn validation_lists_every_problem() {
+        let table = toml::from_str(
+            "[defaults]\ncurr = \"EURO\"\nselected_cabins = \"X\"\nlimit = 0\n[defautls]\nadults = 2",
+        )
+        .unwrap();
+        let err = Config::from_table(table, "test").unwrap_err().to_string();
+
+        assert!(err.contains("unknown key `defautls`"), "{}", err);
+        assert!(err.contains("defaults.curr: `EURO`"), "{}", err);
+        assert!(err.contains("defaults.selected_cabins: `X`"), "{}", err);
+        assert!(
+            err.contains("defaults.limit: must be at least 1"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        assert!(Config::from_table(toml::Table::new(), "test").is_ok());
+    }
+}
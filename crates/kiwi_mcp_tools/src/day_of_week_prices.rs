@@ -0,0 +1,216 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Datelike, NaiveDate, Utc, Weekday};
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    config::SearchDefaults,
+    error::KiwiToolError,
+    input::{self, ToolInput},
+    search::{SearchArgs, SearchOptions, format_date, month_days, parse_month},
+};
+
+/// Results asked for: one per departure date, with room for filters.
+const MONTH_LIMIT: u64 = 62;
+
+#[derive(Deserialize, JsonSchema)]
+struct DayOfWeekPricesArgs {
+    /// IATA code of departure location (e.g., 'LHR', 'NYC')
+    fly_from: String,
+    /// IATA code of arrival location
+    fly_to: String,
+    /// Departure month in mm/yyyy format
+    month: String,
+    /// Nights at the destination, to compare round trips (one-way when omitted)
+    nights: Option<u64>,
+    #[serde(flatten)]
+    search: SearchArgs,
+}
+
+impl ToolInput for DayOfWeekPricesArgs {
+    fn validate(&self) -> Result<()> {
+        parse_month(&self.month)?;
+        Ok(())
+    }
+}
+
+/// Finds the cheapest fare for every departure date in a month and
+/// averages them by weekday, to show which days are cheapest to leave on.
+pub struct DayOfWeekPricesTool {
+    client: Arc<KiwiClient>,
+    defaults: SearchDefaults,
+}
+
+impl DayOfWeekPricesTool {
+    pub fn new(client: Arc<KiwiClient>, defaults: SearchDefaults) -> Self {
+        Self { client, defaults }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for DayOfWeekPricesTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing DayOfWeekPricesTool");
+        let args: DayOfWeekPricesArgs = input::parse(arguments)?;
+
+        let month = parse_month(&args.month)?;
+        let Some((first, last)) = month_days(month, Utc::now().date_naive()) else {
+            return Err(KiwiToolError::validation(format!(
+                "{} is over; choose a month with departures still ahead",
+                month.format("%B %Y")
+            ))
+            .into());
+        };
+        let options = SearchOptions {
+            limit: MONTH_LIMIT,
+            sort: "price".into(),
+            ..SearchOptions::from_args(&args.search, &self.defaults)?
+        };
+
+        let mut params = vec![
+            ("fly_from", args.fly_from.clone()),
+            ("fly_to", args.fly_to.clone()),
+            ("date_from", format_date(first)),
+            ("date_to", format_date(last)),
+            ("one_per_date", "1".to_string()),
+        ];
+        if let Some(nights) = args.nights {
+            params.push(("flight_type", "round".to_string()));
+            params.push(("nights_in_dst_from", nights.to_string()));
+            params.push(("nights_in_dst_to", nights.to_string()));
+        }
+        params.extend(options.params());
+
+        log::info!(
+            "Sampling {} fares by weekday from {} to {}",
+            month.format("%B %Y"),
+            args.fly_from,
+            args.fly_to
+        );
+
+        let mut response = self.client.search_itineraries(&params).await?;
+        options.apply_filters(&mut response.data);
+
+        // The cheapest fare on each departure date
+        let mut days: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        for itinerary in &response.data {
+            let Some(date) = itinerary.local_departure_date() else {
+                continue;
+            };
+            days.entry(date)
+                .and_modify(|price| *price = price.min(itinerary.price))
+                .or_insert(itinerary.price);
+        }
+        if days.is_empty() {
+            return Ok(vec![ToolContent::Text {
+                text: String::from("No flights found matching your criteria."),
+            }]);
+        }
+
+        let mut weekdays: Vec<(Weekday, Vec<f64>)> = (0..7)
+            .map(|day| {
+                (
+                    Weekday::try_from(day).expect("0-6 are weekdays"),
+                    Vec::new(),
+                )
+            })
+            .collect();
+        for (date, price) in &days {
+            weekdays[date.weekday().num_days_from_monday() as usize]
+                .1
+                .push(*price);
+        }
+        let mut averages: Vec<(Weekday, f64, usize)> = weekdays
+            .iter()
+            .filter(|(_, prices)| !prices.is_empty())
+            .map(|(weekday, prices)| {
+                (
+                    *weekday,
+                    prices.iter().sum::<f64>() / prices.len() as f64,
+                    prices.len(),
+                )
+            })
+            .collect();
+        averages.sort_by(|(_, a, _), (_, b, _)| a.total_cmp(b));
+
+        let mut result = format!(
+            "Fares by departure weekday for {} → {}{} in {} (prices in {}):\n\n",
+            args.fly_from,
+            args.fly_to,
+            args.nights
+                .map(|nights| format!(", {} nights", nights))
+                .unwrap_or_default(),
+            month.format("%B %Y"),
+            options.curr
+        );
+        for (rank, (weekday, average, count)) in averages.iter().enumerate() {
+            result.push_str(&format!(
+                "{}. {}: {} on average over {} {}\n",
+                rank + 1,
+                weekday_name(*weekday),
+                options.price_format.amount(*average),
+                count,
+                if *count == 1 { "date" } else { "dates" }
+            ));
+        }
+        if let (Some((cheapest, low, _)), Some((priciest, high, _))) =
+            (averages.first(), averages.last())
+            && averages.len() > 1
+        {
+            result.push_str(&format!(
+                "\nLeaving on a {} costs {} less on average than on a {}.\n",
+                weekday_name(*cheapest),
+                options.price(high - low),
+                weekday_name(*priciest)
+            ));
+        }
+
+        result.push_str(
+            "\nCheapest fare per departure date:\n\n| Date | Day | Cheapest |\n|---|---|---|\n",
+        );
+        for date in first.iter_days().take_while(|date| *date <= last) {
+            result.push_str(&format!(
+                "| {} | {} | {} |\n",
+                date.format("%d %b"),
+                date.format("%a"),
+                days.get(&date)
+                    .map(|price| options.price_format.amount(*price))
+                    .unwrap_or_else(|| "no flights".to_string())
+            ));
+        }
+        result.push_str("\nAverages use the cheapest fare on each date, so a weekday with few dates left in the month is a small sample.\n");
+        options.push_notes(&mut result);
+        self.client.push_quota_warning(&mut result);
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "day_of_week_prices".into(),
+            description: Some(
+                "Show which departure weekdays are cheapest on a route in a month, from the cheapest fare on every date, with the per-date table"
+                    .into(),
+            ),
+            input_schema: input::schema::<DayOfWeekPricesArgs>(),
+        }
+    }
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
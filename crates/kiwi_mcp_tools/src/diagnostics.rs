@@ -0,0 +1,227 @@
+//! Diagnostic reports for bug reports: when a tool fails internally, or the
+//! Kiwi API keeps failing, the recent API exchanges, the configuration and
+//! the version are written to a file the error message points at.
+//!
+//! Everything in a report goes through [`redact`] first, and the API key and
+//! auth token are left out of the configuration, so reports can be attached
+//! to an issue as they are.
+
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use serde_json::Value;
+use url::Url;
+
+use crate::{config::Config, error::KiwiToolError, redact::redact, storage};
+
+/// API exchanges kept for the next report.
+const RECENT_EXCHANGES: usize = 20;
+
+/// Response bodies are cut to this many characters.
+const MAX_BODY_CHARS: usize = 2000;
+
+/// Consecutive failed API requests that trigger a report.
+const REPEATED_FAILURES: usize = 3;
+
+/// Reports kept; older ones are deleted as new ones are written.
+const MAX_REPORTS: usize = 10;
+
+const REPORTS_DIR: &str = "diagnostics";
+
+static DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+static CONFIG: RwLock<Option<Value>> = RwLock::new(None);
+static EXCHANGES: Mutex<VecDeque<Exchange>> = Mutex::new(VecDeque::new());
+static LAST_PANIC: Mutex<Option<String>> = Mutex::new(None);
+static FAILURES: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone, Serialize)]
+struct Exchange {
+    at: DateTime<Utc>,
+    request: String,
+    ok: bool,
+    /// The start of the response body, or the error.
+    response: String,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    version: &'static str,
+    os: &'static str,
+    arch: &'static str,
+    created_at: DateTime<Utc>,
+    reason: &'a str,
+    last_panic: Option<String>,
+    config: Option<Value>,
+    recent_requests: Vec<Exchange>,
+}
+
+/// Reports are written under `data_dir` from now on; `None` (ephemeral
+/// sessions) turns them off.
+pub fn set_data_dir(data_dir: Option<PathBuf>) {
+    *DIR.write() = data_dir.map(|dir| dir.join(REPORTS_DIR));
+}
+
+/// Keeps a redacted snapshot of `config` for reports.
+pub fn set_config(config: &Config) {
+    let mut config = config.clone();
+    config.api_key = None;
+    config.http.auth_token = None;
+
+    *CONFIG.write() = serde_json::to_string(&config)
+        .ok()
+        .and_then(|json| serde_json::from_str(&redact(&json)).ok());
+}
+
+/// Records the outcome of an API request, and counts it towards
+/// [`REPEATED_FAILURES`] when the API itself failed.
+pub fn record_exchange(url: &Url, outcome: &Result<Value>) {
+    let response = match outcome {
+        Ok(value) => value.to_string().chars().take(MAX_BODY_CHARS).collect(),
+        Err(err) => format!("{:#}", err),
+    };
+
+    let mut exchanges = EXCHANGES.lock();
+    if exchanges.len() == RECENT_EXCHANGES {
+        exchanges.pop_front();
+    }
+    exchanges.push_back(Exchange {
+        at: Utc::now(),
+        request: redact(&format!("GET {}", url)),
+        ok: outcome.is_ok(),
+        response: redact(&response),
+    });
+
+    match outcome {
+        Err(err) if is_upstream_failure(err) => FAILURES.fetch_add(1, Ordering::Relaxed),
+        _ => FAILURES.swap(0, Ordering::Relaxed),
+    };
+}
+
+/// Keeps the (already redacted) report of the latest panic for the next
+/// report.
+pub fn record_panic(report: String) {
+    *LAST_PANIC.lock() = Some(report);
+}
+
+/// Writes a report when `err` calls for one, an internal error or the last
+/// of several API failures in a row, and returns where.
+pub fn report(err: &anyhow::Error) -> Option<PathBuf> {
+    let reason = match KiwiToolError::find(err) {
+        Some(KiwiToolError::Internal(_)) => "internal error".to_string(),
+        _ if FAILURES.load(Ordering::Relaxed) >= REPEATED_FAILURES => format!(
+            "{} failed API requests in a row",
+            FAILURES.swap(0, Ordering::Relaxed)
+        ),
+        _ => return None,
+    };
+
+    write_report(&format!("{}: {:#}", reason, err))
+}
+
+/// Writes a report explaining `reason`, and returns where. Failing to
+/// write one is logged, not reported: the original error matters more.
+pub fn write_report(reason: &str) -> Option<PathBuf> {
+    let dir = DIR.read().clone()?;
+    let created_at = Utc::now();
+    let path = dir.join(format!(
+        "report-{}.json",
+        created_at.format("%Y%m%d-%H%M%S%.3f")
+    ));
+
+    let report = Report {
+        version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        created_at,
+        reason: &redact(reason),
+        last_panic: LAST_PANIC.lock().clone(),
+        config: CONFIG.read().clone(),
+        recent_requests: EXCHANGES.lock().iter().cloned().collect(),
+    };
+
+    let written = serde_json::to_vec_pretty(&report)
+        .map_err(anyhow::Error::from)
+        .and_then(|contents| storage::current().write(&path, &contents));
+    if let Err(err) = written {
+        log::warn!("Failed to write a diagnostic report: {:#}", err);
+        return None;
+    }
+    log::info!("Wrote a diagnostic report to {}", path.display());
+
+    if let Err(err) = prune(&dir) {
+        log::warn!("Failed to remove old diagnostic reports: {:#}", err);
+    }
+    Some(path)
+}
+
+/// Deletes every report, and forgets the exchanges and panic kept for the
+/// next one. Returns how many reports were deleted.
+pub fn purge() -> Result<usize> {
+    EXCHANGES.lock().clear();
+    *LAST_PANIC.lock() = None;
+    FAILURES.store(0, Ordering::Relaxed);
+
+    let Some(dir) = DIR.read().clone() else {
+        return Ok(0);
+    };
+    let storage = storage::current();
+    let reports: Vec<PathBuf> = storage
+        .list(&dir)?
+        .into_iter()
+        .filter(|path| is_report(path))
+        .collect();
+    for path in &reports {
+        storage.remove(path)?;
+    }
+    Ok(reports.len())
+}
+
+/// A sentence pointing at the report at `path`.
+pub fn mention(path: &Path) -> String {
+    format!(
+        "A diagnostic report was saved to {}; attach it to a bug report.",
+        path.display()
+    )
+}
+
+/// Whether `err` is the API failing rather than the request being wrong.
+fn is_upstream_failure(err: &anyhow::Error) -> bool {
+    match KiwiToolError::find(err) {
+        Some(KiwiToolError::Upstream { status, .. }) => *status >= 500,
+        Some(KiwiToolError::Timeout(_) | KiwiToolError::Parse(_)) => true,
+        Some(_) => false,
+        // Connection failures
+        None => true,
+    }
+}
+
+/// Whether `path` is named like a report, `report-*.json`.
+fn is_report(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("report-") && name.ends_with(".json"))
+}
+
+/// Deletes all but the newest [`MAX_REPORTS`] reports in `dir`.
+fn prune(dir: &Path) -> Result<()> {
+    let storage = storage::current();
+    let mut reports: Vec<PathBuf> = storage
+        .list(dir)?
+        .into_iter()
+        .filter(|path| is_report(path))
+        .collect();
+    // Names sort by the time they were written
+    reports.sort();
+    let excess = reports.len().saturating_sub(MAX_REPORTS);
+    for path in &reports[..excess] {
+        storage.remove(path)?;
+    }
+    Ok(())
+}
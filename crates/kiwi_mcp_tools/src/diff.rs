@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use crate::{format::format_itinerary_summary, model::Itinerary, money::PriceFormat};
+
+/// How one set of results differs from another, matching itineraries by the
+/// flights they fly.
+pub struct ResultDiff<'a> {
+    /// `(before, after)` pairs whose price went down.
+    pub cheaper: Vec<(&'a Itinerary, &'a Itinerary)>,
+    /// `(before, after)` pairs whose price went up.
+    pub pricier: Vec<(&'a Itinerary, &'a Itinerary)>,
+    pub removed: Vec<&'a Itinerary>,
+    pub added: Vec<&'a Itinerary>,
+    pub unchanged: usize,
+}
+
+impl<'a> ResultDiff<'a> {
+    pub fn new(before: &'a [Itinerary], after: &'a [Itinerary]) -> Self {
+        let after_by_key: HashMap<String, &Itinerary> = after
+            .iter()
+            .map(|itinerary| (itinerary.flights_key(), itinerary))
+            .collect();
+        let before_keys: Vec<String> = before.iter().map(Itinerary::flights_key).collect();
+
+        let mut diff = Self {
+            cheaper: Vec::new(),
+            pricier: Vec::new(),
+            removed: Vec::new(),
+            added: Vec::new(),
+            unchanged: 0,
+        };
+
+        for (old, key) in before.iter().zip(&before_keys) {
+            match after_by_key.get(key) {
+                Some(new) if new.price < old.price => diff.cheaper.push((old, new)),
+                Some(new) if new.price > old.price => diff.pricier.push((old, new)),
+                Some(_) => diff.unchanged += 1,
+                None => diff.removed.push(old),
+            }
+        }
+
+        diff.added = after
+            .iter()
+            .filter(|itinerary| !before_keys.contains(&itinerary.flights_key()))
+            .collect();
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cheaper.is_empty()
+            && self.pricier.is_empty()
+            && self.removed.is_empty()
+            && self.added.is_empty()
+    }
+
+    pub fn format(&self, currency: &str, price_format: &PriceFormat) -> String {
+        if self.is_empty() {
+            return format!("No changes: all {} options are the same.\n", self.unchanged);
+        }
+
+        let mut result = String::new();
+
+        for (title, pairs) in [("Cheaper", &self.cheaper), ("Pricier", &self.pricier)] {
+            if pairs.is_empty() {
+                continue;
+            }
+            result.push_str(&format!("{} ({}):\n", title, pairs.len()));
+            for (old, new) in pairs {
+                result.push_str(&format!(
+                    "  - {}: {} → {} ({})\n",
+                    format_itinerary_summary(new),
+                    price_format.amount(old.price),
+                    price_format.price(new.price, currency),
+                    price_format.signed(new.price - old.price)
+                ));
+            }
+        }
+
+        for (title, itineraries) in [("No longer available", &self.removed), ("New", &self.added)] {
+            if itineraries.is_empty() {
+                continue;
+            }
+            result.push_str(&format!("{} ({}):\n", title, itineraries.len()));
+            for itinerary in itineraries {
+                result.push_str(&format!(
+                    "  - {}: {}\n",
+                    format_itinerary_summary(itinerary),
+                    price_format.price(itinerary.price, currency)
+                ));
+            }
+        }
+
+        if self.unchanged > 0 {
+            result.push_str(&format!("Unchanged: {}\n", self.unchanged));
+        }
+
+        result
+    }
+}
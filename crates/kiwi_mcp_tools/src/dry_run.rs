@@ -0,0 +1,141 @@
+//! Dry runs: a tool called with `dry_run: true` validates its arguments and
+//! builds its requests as usual, but the client records each request instead
+//! of sending it, and the call returns what would have been sent.
+//!
+//! The argument is added to every tool's schema here, and removed before the
+//! tool sees its arguments, so tools need no changes to support it.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use context_server::{Tool, ToolContent, ToolExecutor};
+use parking_lot::Mutex;
+use serde_json::{Value, json};
+use url::Url;
+
+use crate::{
+    error::KiwiToolError,
+    redact::{header_value, redact},
+};
+
+const DRY_RUN: &str = "dry_run";
+
+tokio::task_local! {
+    /// The requests recorded by the dry run on this task.
+    static RECORDED: Arc<Mutex<Vec<String>>>;
+}
+
+/// Records the request for `url` with `headers` when a dry run is in
+/// progress, and returns the error that stands in for its response.
+pub fn intercept(url: &Url, headers: &[(String, String)]) -> Result<()> {
+    let recorded = RECORDED.try_with(|recorded| {
+        let mut request = format!("GET {}\n  Accept: application/json", url);
+        for (name, value) in headers {
+            request.push_str(&format!("\n  {}: {}", name, header_value(name, value)));
+        }
+        request.push_str("\n  apikey: [redacted]");
+        recorded.lock().push(redact(&request));
+    });
+
+    match recorded {
+        Ok(()) => Err(KiwiToolError::DryRun.into()),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Whether `arguments` ask for a dry run.
+pub fn is_requested(arguments: Option<&Value>) -> bool {
+    arguments.and_then(|arguments| arguments.get(DRY_RUN)) == Some(&Value::Bool(true))
+}
+
+/// Wraps a tool so calls with `dry_run: true` return the requests they
+/// would send.
+pub struct DryRunnable {
+    tool: Arc<dyn ToolExecutor>,
+}
+
+impl DryRunnable {
+    pub fn new(tool: Arc<dyn ToolExecutor>) -> Self {
+        Self { tool }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for DryRunnable {
+    async fn execute(&self, mut arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        let dry_run = match arguments.as_mut().and_then(Value::as_object_mut) {
+            Some(arguments) => match arguments.remove(DRY_RUN) {
+                None => false,
+                Some(Value::Bool(dry_run)) => dry_run,
+                Some(other) => {
+                    return Err(KiwiToolError::validation(format!(
+                        "Invalid arguments: {} must be true or false, not {}",
+                        DRY_RUN, other
+                    ))
+                    .into());
+                }
+            },
+            None => false,
+        };
+        if !dry_run {
+            return self.tool.execute(arguments).await;
+        }
+
+        let recorded = Arc::new(Mutex::new(Vec::new()));
+        let outcome = RECORDED
+            .scope(recorded.clone(), self.tool.execute(arguments))
+            .await;
+        let requests = recorded.lock().clone();
+
+        // Invalid arguments are reported as they would be without a dry run
+        if requests.is_empty() {
+            outcome?;
+        }
+
+        let name = self.tool.to_tool().name;
+        let mut result = match requests.len() {
+            0 => format!(
+                "Dry run: the arguments are valid, and {} would send no Kiwi API requests.\n",
+                name
+            ),
+            1 => format!(
+                "Dry run: the arguments are valid. Nothing was sent; {} would send this Kiwi API request:\n\n",
+                name
+            ),
+            count => format!(
+                "Dry run: the arguments are valid. Nothing was sent; {} would send these {} Kiwi API requests:\n\n",
+                name, count
+            ),
+        };
+        for request in &requests {
+            result.push_str(&format!("{}\n\n", request));
+        }
+        if !requests.is_empty() {
+            result.push_str(
+                "Requests that depend on the answers to these, such as follow-up searches, are not shown.\n",
+            );
+        }
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        let mut tool = self.tool.to_tool();
+        if let Some(properties) = tool
+            .input_schema
+            .get_mut("properties")
+            .and_then(Value::as_object_mut)
+        {
+            properties.insert(
+                DRY_RUN.into(),
+                json!({
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Check the arguments and return the Kiwi API requests the call would send, without sending them"
+                }),
+            );
+        }
+        tool
+    }
+}
@@ -0,0 +1,248 @@
+//! Optional encryption of persisted user data: saved searches and the
+//! search history, which can reveal travel plans and passenger details.
+//!
+//! Once a [`Cipher`] is installed with [`set_cipher`], stores pass what
+//! they write through [`seal`] and what they read through [`open`].
+//! Unencrypted data is still read, so turning encryption on keeps existing
+//! files usable.
+
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use aes_gcm::{
+    Aes256Gcm, Key, KeyInit, Nonce,
+    aead::{Aead, AeadCore, OsRng, rand_core::RngCore},
+};
+use anyhow::{Result, anyhow};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use parking_lot::{Mutex, RwLock};
+use sha2::Sha256;
+
+use crate::storage;
+
+/// Marks sealed text, with the format version.
+const PREFIX: &str = "enc1:";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// PBKDF2 iterations for passphrase-derived keys, per the OWASP guidance
+/// for HMAC-SHA256.
+const PBKDF2_ROUNDS: u32 = 600_000;
+
+static CIPHER: RwLock<Option<Arc<Cipher>>> = RwLock::new(None);
+
+/// Encrypts and decrypts with AES-256-GCM.
+pub struct Cipher {
+    secret: Secret,
+    /// Salt for newly sealed text; sealed text names the salt it was
+    /// sealed with.
+    salt: [u8; SALT_LEN],
+    /// Keys derived so far, by salt.
+    keys: Mutex<HashMap<Vec<u8>, Key<Aes256Gcm>>>,
+}
+
+enum Secret {
+    Passphrase(String),
+    Key(Key<Aes256Gcm>),
+}
+
+impl Cipher {
+    /// Derives keys from `passphrase`.
+    pub fn from_passphrase(passphrase: &str) -> Self {
+        Self::new(Secret::Passphrase(passphrase.to_string()))
+    }
+
+    /// Uses a 256-bit key as is.
+    pub fn from_key(key: [u8; 32]) -> Self {
+        Self::new(Secret::Key(key.into()))
+    }
+
+    /// Uses the key kept in the OS keyring, creating one on first use.
+    #[cfg(feature = "keyring")]
+    pub fn from_keyring() -> Result<Self> {
+        let entry = keyring::Entry::new("kiwi-mcp", "storage-key")?;
+        let encoded = match entry.get_password() {
+            Ok(encoded) => encoded,
+            Err(keyring::Error::NoEntry) => {
+                let key = Aes256Gcm::generate_key(OsRng);
+                let encoded = BASE64.encode(key);
+                entry.set_password(&encoded)?;
+                encoded
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let key: [u8; 32] = BASE64
+            .decode(encoded)?
+            .try_into()
+            .map_err(|_| anyhow!("The key in the OS keyring is not 256 bits"))?;
+        Ok(Self::from_key(key))
+    }
+
+    /// Seals with the salt kept in the file at `path`, writing this
+    /// cipher's random one there first if there is none yet. Reusing one
+    /// salt per data directory keeps opening sealed data to one key
+    /// derivation, however many runs wrote it.
+    pub fn with_salt_file(mut self, path: &Path) -> Result<Self> {
+        match storage::read_to_string(path)? {
+            Some(encoded) => {
+                self.salt = BASE64.decode(encoded.trim())?.try_into().map_err(|_| {
+                    anyhow!("The salt in {} is not {} bytes", path.display(), SALT_LEN)
+                })?;
+            }
+            None => storage::current().write(path, BASE64.encode(self.salt).as_bytes())?,
+        }
+        Ok(self)
+    }
+
+    fn new(secret: Secret) -> Self {
+        let mut salt = [0; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+
+        Self {
+            secret,
+            salt,
+            keys: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn key(&self, salt: &[u8]) -> Key<Aes256Gcm> {
+        let passphrase = match &self.secret {
+            Secret::Key(key) => return *key,
+            Secret::Passphrase(passphrase) => passphrase,
+        };
+
+        *self.keys.lock().entry(salt.to_vec()).or_insert_with(|| {
+            let mut key = Key::<Aes256Gcm>::default();
+            pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, PBKDF2_ROUNDS, &mut key);
+            key
+        })
+    }
+
+    /// `PREFIX`, the salt and the nonce followed by the ciphertext, both in
+    /// base64.
+    fn seal(&self, plaintext: &str) -> Result<String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = Aes256Gcm::new(&self.key(&self.salt))
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| anyhow!("Failed to encrypt data"))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend(ciphertext);
+        Ok(format!(
+            "{}{}:{}",
+            PREFIX,
+            BASE64.encode(self.salt),
+            BASE64.encode(sealed)
+        ))
+    }
+
+    fn open(&self, sealed: &str) -> Result<String> {
+        let invalid = || anyhow!("Encrypted data is malformed");
+        let (salt, sealed) = sealed
+            .strip_prefix(PREFIX)
+            .and_then(|sealed| sealed.split_once(':'))
+            .ok_or_else(invalid)?;
+        let salt = BASE64.decode(salt)?;
+        let sealed = BASE64.decode(sealed)?;
+        if sealed.len() < NONCE_LEN {
+            return Err(invalid());
+        }
+
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let plaintext = Aes256Gcm::new(&self.key(&salt))
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("Failed to decrypt data; is the key or passphrase right?"))?;
+        Ok(String::from_utf8(plaintext)?)
+    }
+}
+
+/// Encrypts what is written from now on with `cipher`; `None` writes
+/// plain text.
+pub fn set_cipher(cipher: Option<Cipher>) {
+    *CIPHER.write() = cipher.map(Arc::new);
+}
+
+pub fn is_enabled() -> bool {
+    CIPHER.read().is_some()
+}
+
+/// Whether `stored` was written by [`seal`] with encryption on.
+pub fn is_sealed(stored: &str) -> bool {
+    stored.starts_with(PREFIX)
+}
+
+/// `plaintext` encrypted for storage, or unchanged when encryption is off.
+pub fn seal(plaintext: &str) -> Result<String> {
+    let cipher = CIPHER.read().clone();
+    match cipher {
+        Some(cipher) => cipher.seal(plaintext),
+        None => Ok(plaintext.to_string()),
+    }
+}
+
+/// The installed cipher, if encryption is on.
+pub fn cipher() -> Option<Arc<Cipher>> {
+    CIPHER.read().clone()
+}
+
+/// The plain text of something [`seal`] wrote. Unencrypted text is
+/// returned as is.
+pub fn open(stored: &str) -> Result<String> {
+    open_with(cipher().as_deref(), stored)
+}
+
+/// Like [`open`], but with `cipher` rather than the installed one, to
+/// check data opens before a cipher is installed.
+pub fn open_with(cipher: Option<&Cipher>, stored: &str) -> Result<String> {
+    if !is_sealed(stored) {
+        return Ok(stored.to_string());
+    }
+
+    match cipher {
+        Some(cipher) => cipher.open(stored),
+        None => Err(anyhow!(
+            "Data is encrypted, but no key or passphrase is configured"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_text_opens_with_the_same_key() {
+        let cipher = Cipher::from_key([7; 32]);
+        let sealed = cipher.seal("LON → BCN, 2 adults").unwrap();
+
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.contains("BCN"));
+        assert_eq!(cipher.open(&sealed).unwrap(), "LON → BCN, 2 adults");
+    }
+
+    #[test]
+    fn sealing_twice_differs() {
+        let cipher = Cipher::from_key([7; 32]);
+        assert_ne!(cipher.seal("text").unwrap(), cipher.seal("text").unwrap());
+    }
+
+    #[test]
+    fn wrong_passphrase_fails_to_open() {
+        // Each passphrase costs a slow key derivation, so only two are made
+        let right = Cipher::from_passphrase("right");
+        let sealed = right.seal("text").unwrap();
+
+        assert_eq!(right.open(&sealed).unwrap(), "text");
+        let err = Cipher::from_passphrase("wrong").open(&sealed).unwrap_err();
+        assert!(err.to_string().contains("is the key or passphrase right?"));
+    }
+
+    #[test]
+    fn malformed_and_plain_text() {
+        let cipher = Cipher::from_key([7; 32]);
+        assert!(cipher.open("enc1:nonsense").is_err());
+        assert_eq!(open_with(Some(&cipher), "plain").unwrap(), "plain");
+        assert!(open_with(None, &cipher.seal("text").unwrap()).is_err());
+    }
+}
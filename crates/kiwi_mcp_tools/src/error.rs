@@ -0,0 +1,138 @@
+//! The errors tools fail with, by kind, so callers can tell a bad argument
+//! from an API outage without parsing messages.
+
+use std::{fmt, time::Duration};
+
+use context_server::ToolContent;
+
+#[derive(Debug)]
+pub enum KiwiToolError {
+    /// The arguments are missing, malformed or inconsistent, or refer to
+    /// something that doesn't exist.
+    Validation(String),
+    /// The Kiwi API answered with an error status.
+    Upstream { status: u16, message: String },
+    /// The API request quota is used up.
+    Quota { retry_after: Option<Duration> },
+    /// A configured request budget is used up.
+    Budget { period: &'static str, limit: u64 },
+    /// A request wasn't sent because the call is a dry run.
+    DryRun,
+    /// The Kiwi API didn't answer in time.
+    Timeout(Duration),
+    /// A tool call ran past its time limit and was abandoned.
+    ToolTimeout(Duration),
+    /// A tool failed in a way it doesn't handle, such as a panic.
+    Internal(String),
+    /// A response from the API couldn't be understood.
+    Parse(String),
+    /// The server's configuration is invalid.
+    Config(String),
+}
+
+impl KiwiToolError {
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::Validation(message.into())
+    }
+
+    pub fn parse(message: impl Into<String>) -> Self {
+        Self::Parse(message.into())
+    }
+
+    pub fn config(message: impl Into<String>) -> Self {
+        Self::Config(message.into())
+    }
+
+    /// Short name of the kind, e.g. `validation`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Validation(_) => "validation",
+            Self::Upstream { .. } => "upstream",
+            Self::Quota { .. } => "quota",
+            Self::Budget { .. } => "budget",
+            Self::DryRun => "dry_run",
+            Self::Timeout(_) | Self::ToolTimeout(_) => "timeout",
+            Self::Internal(_) => "internal",
+            Self::Parse(_) => "parse",
+            Self::Config(_) => "config",
+        }
+    }
+
+    /// The first error of this type in `err`'s chain.
+    pub fn find(err: &anyhow::Error) -> Option<&Self> {
+        err.chain().find_map(|cause| cause.downcast_ref())
+    }
+
+    /// Whether the caller can act on the error: fix the arguments, or try
+    /// again later. MCP asks for these to be reported in the tool result,
+    /// where the model sees them, rather than as a protocol error.
+    pub fn is_actionable(&self) -> bool {
+        match self {
+            Self::Validation(_)
+            | Self::Quota { .. }
+            | Self::Budget { .. }
+            | Self::DryRun
+            | Self::Timeout(_)
+            | Self::ToolTimeout(_) => true,
+            Self::Upstream { status, .. } => *status < 500,
+            Self::Parse(_) | Self::Config(_) | Self::Internal(_) => false,
+        }
+    }
+
+    /// The error as tool result content.
+    pub fn to_content(&self) -> Vec<ToolContent> {
+        let mut text = format!("Error ({}): {}", self.kind(), self);
+        match self {
+            Self::Validation(_) => text.push_str("\nFix the arguments and call the tool again."),
+            Self::Quota { .. } | Self::Timeout(_) => text.push_str("\nTry again later."),
+            Self::ToolTimeout(_) => {
+                text.push_str("\nTry a narrower search (fewer dates or places), or again later.")
+            }
+            Self::Budget { .. } => text.push_str(
+                "\nSearches already made can still be answered from the cache. The budget is set under [budget] in the server configuration.",
+            ),
+            _ => {}
+        }
+        vec![ToolContent::Text { text }]
+    }
+}
+
+impl fmt::Display for KiwiToolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Validation(message)
+            | Self::Parse(message)
+            | Self::Config(message)
+            | Self::Internal(message) => {
+                write!(f, "{}", message)
+            }
+            Self::Upstream { status, message } => {
+                write!(f, "Kiwi API request failed ({}): {}", status, message)
+            }
+            Self::Quota {
+                retry_after: Some(retry_after),
+            } => write!(
+                f,
+                "The Kiwi API request quota is used up; it resets in {}s",
+                retry_after.as_secs()
+            ),
+            Self::Quota { retry_after: None } => {
+                write!(f, "The Kiwi API request quota is used up")
+            }
+            Self::Budget { period, limit } => write!(
+                f,
+                "The {} budget of {} Kiwi API requests is used up",
+                period, limit
+            ),
+            Self::DryRun => write!(f, "Dry run: the request was not sent"),
+            Self::Timeout(after) => {
+                write!(f, "The Kiwi API didn't answer within {}s", after.as_secs())
+            }
+            Self::ToolTimeout(after) => {
+                write!(f, "The tool didn't finish within {}s", after.as_secs())
+            }
+        }
+    }
+}
+
+impl std::error::Error for KiwiToolError {}
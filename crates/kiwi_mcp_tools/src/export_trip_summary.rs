@@ -0,0 +1,317 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use context_server::{ResourceContent, Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    error::KiwiToolError,
+    format::format_duration,
+    input::{self, ToolInput},
+    model::{Itinerary, format_local_time},
+    money::PriceFormat,
+    store::SearchStore,
+    trip::Trip,
+};
+
+const MAX_ITINERARIES: usize = 10;
+
+#[derive(Deserialize, JsonSchema)]
+struct ExportTripSummaryArgs {
+    /// Reference codes of the itineraries to include, in order (e.g., ['KW-1A2B3C4D'])
+    references: Vec<String>,
+    /// Title of the document (default 'Trip summary')
+    title: Option<String>,
+    /// Document format (default markdown)
+    #[schemars(extend("enum" = ["markdown", "html"]))]
+    format: Option<String>,
+}
+
+impl ToolInput for ExportTripSummaryArgs {
+    fn validate(&self) -> Result<()> {
+        if self.references.is_empty() || self.references.len() > MAX_ITINERARIES {
+            return Err(KiwiToolError::validation(format!(
+                "references must list between 1 and {} itineraries",
+                MAX_ITINERARIES
+            ))
+            .into());
+        }
+        DocumentFormat::parse(self.format.as_deref())?;
+        Ok(())
+    }
+}
+
+/// Writes a document describing chosen itineraries, to share with the
+/// people travelling.
+pub struct ExportTripSummaryTool {
+    store: Arc<SearchStore>,
+    price_format: PriceFormat,
+}
+
+impl ExportTripSummaryTool {
+    pub fn new(store: Arc<SearchStore>, price_format: PriceFormat) -> Self {
+        Self {
+            store,
+            price_format,
+        }
+    }
+
+    fn describe(&self, format: DocumentFormat, itinerary: &Itinerary, currency: &str) -> String {
+        let mut section = format.heading(
+            2,
+            &format!(
+                "{} → {} ({})",
+                itinerary.city_from,
+                itinerary.city_to,
+                itinerary.reference()
+            ),
+        );
+        section.push_str(&format.paragraph(&format!(
+            "{} in total, {} of travel",
+            format.strong(&self.price_format.price(itinerary.price, currency)),
+            format_duration(itinerary.duration.total)
+        )));
+
+        let directions: &[(u8, &str)] = if itinerary.is_round_trip() {
+            &[(0, "Outbound"), (1, "Return")]
+        } else {
+            &[(0, "")]
+        };
+        for (direction, label) in directions {
+            if !label.is_empty() {
+                section.push_str(&format.heading(3, label));
+            }
+            let rows: Vec<Vec<String>> = itinerary
+                .route
+                .iter()
+                .filter(|leg| leg.return_leg == *direction)
+                .map(|leg| {
+                    let time = |timestamp: &Option<String>| {
+                        timestamp
+                            .as_deref()
+                            .map(format_local_time)
+                            .unwrap_or_default()
+                    };
+                    vec![
+                        leg.flight(),
+                        format!("{} ({})", leg.city_from, leg.departure_airport()),
+                        time(&leg.local_departure),
+                        format!("{} ({})", leg.city_to, leg.arrival_airport()),
+                        time(&leg.local_arrival),
+                    ]
+                })
+                .collect();
+            section.push_str(&format.table(&["Flight", "From", "Departs", "To", "Arrives"], &rows));
+        }
+
+        if let Some(link) = &itinerary.deep_link {
+            section.push_str(&format.paragraph(&format.link("Book on Kiwi.com", link)));
+        }
+        section
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for ExportTripSummaryTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing ExportTripSummaryTool");
+        let args: ExportTripSummaryArgs = input::parse(arguments)?;
+        let format = DocumentFormat::parse(args.format.as_deref())?;
+
+        let mut trip = Trip::new();
+        let mut currencies = Vec::new();
+        for reference in &args.references {
+            let (search, index) = self.store.find_reference(reference)?;
+            trip = trip.then(reference.as_str(), search.itinerary(index)?.clone());
+            currencies.push(search.currency);
+        }
+
+        let title = args.title.as_deref().unwrap_or("Trip summary");
+        let mut document = format.heading(1, title);
+        for (i, (ticket, currency)) in trip.tickets.iter().zip(&currencies).enumerate() {
+            if i > 0
+                && let Some(time) = trip.time_between(i - 1)
+            {
+                document.push_str(&format.paragraph(&format!(
+                    "{} in {} before the next flight.",
+                    format_duration(time.num_seconds()),
+                    ticket.itinerary.city_from
+                )));
+            }
+            document.push_str(&self.describe(format, &ticket.itinerary, currency));
+        }
+
+        if trip.tickets.len() > 1 && currencies.iter().all(|currency| *currency == currencies[0]) {
+            document.push_str(&format.paragraph(&format!(
+                "{} for all {} itineraries.",
+                format.strong(&self.price_format.price(trip.price(), &currencies[0])),
+                trip.tickets.len()
+            )));
+        }
+        document.push_str(&format.paragraph(&format!(
+            "Prices as found on {}. They change often, so check them before booking.",
+            Utc::now().format("%d %b %Y")
+        )));
+
+        let mut summary = format!(
+            "Trip summary of {} itineraries, as a {} document.",
+            trip.tickets.len(),
+            format.name()
+        );
+        summary.push_str(" Share it as it is; booking links open each itinerary on Kiwi.com.");
+
+        Ok(vec![
+            ToolContent::Text { text: summary },
+            ToolContent::Resource {
+                resource: ResourceContent {
+                    uri: format!(
+                        "kiwi://trip-summaries/{}.{}",
+                        args.references.join("+"),
+                        format.extension()
+                    ),
+                    mime_type: Some(format.mime_type().into()),
+                    text: Some(format.wrap(title, document)),
+                    blob: None,
+                },
+            },
+        ])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "export_trip_summary".into(),
+            description: Some(
+                "Write a shareable Markdown or HTML document of chosen itineraries, given by reference code: route, times, prices and booking links, e.g. to email to travel companions"
+                    .into(),
+            ),
+            input_schema: input::schema::<ExportTripSummaryArgs>(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum DocumentFormat {
+    Markdown,
+    Html,
+}
+
+impl DocumentFormat {
+    fn parse(format: Option<&str>) -> Result<Self> {
+        match format.unwrap_or("markdown") {
+            "markdown" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            other => Err(KiwiToolError::validation(format!(
+                "Unknown format {:?}; use markdown or html",
+                other
+            ))
+            .into()),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Markdown => "Markdown",
+            Self::Html => "HTML",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+
+    fn mime_type(self) -> &'static str {
+        match self {
+            Self::Markdown => "text/markdown",
+            Self::Html => "text/html",
+        }
+    }
+
+    /// The finished document: HTML gets a page around the body.
+    fn wrap(self, title: &str, body: String) -> String {
+        match self {
+            Self::Markdown => body,
+            Self::Html => format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>body {{ font-family: sans-serif; max-width: 48em; margin: auto; }} table {{ border-collapse: collapse; }} th, td {{ border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+                escape_html(title),
+                body
+            ),
+        }
+    }
+
+    fn heading(self, level: usize, text: &str) -> String {
+        match self {
+            Self::Markdown => format!("{} {}\n\n", "#".repeat(level), text),
+            Self::Html => format!("<h{0}>{1}</h{0}>\n", level, escape_html(text)),
+        }
+    }
+
+    /// A paragraph of `text`, which is already formatted.
+    fn paragraph(self, text: &str) -> String {
+        match self {
+            Self::Markdown => format!("{}\n\n", text),
+            Self::Html => format!("<p>{}</p>\n", text),
+        }
+    }
+
+    fn strong(self, text: &str) -> String {
+        match self {
+            Self::Markdown => format!("**{}**", text),
+            Self::Html => format!("<strong>{}</strong>", escape_html(text)),
+        }
+    }
+
+    fn link(self, text: &str, url: &str) -> String {
+        match self {
+            Self::Markdown => format!("[{}]({})", text, url),
+            Self::Html => format!("<a href=\"{}\">{}</a>", escape_html(url), escape_html(text)),
+        }
+    }
+
+    fn table(self, headers: &[&str], rows: &[Vec<String>]) -> String {
+        match self {
+            Self::Markdown => {
+                let row = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+                let mut table = row(headers.iter().map(|header| header.to_string()).collect());
+                table.push_str(&row(headers.iter().map(|_| "---".to_string()).collect()));
+                for cells in rows {
+                    table.push_str(&row(cells.clone()));
+                }
+                table.push('\n');
+                table
+            }
+            Self::Html => {
+                let row = |tag: &str, cells: Vec<String>| {
+                    let cells: String = cells
+                        .iter()
+                        .map(|cell| format!("<{0}>{1}</{0}>", tag, escape_html(cell)))
+                        .collect();
+                    format!("<tr>{}</tr>\n", cells)
+                };
+                let mut table = String::from("<table>\n");
+                table.push_str(&row(
+                    "th",
+                    headers.iter().map(|header| header.to_string()).collect(),
+                ));
+                for cells in rows {
+                    table.push_str(&row("td", cells.clone()));
+                }
+                table.push_str("</table>\n");
+                table
+            }
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
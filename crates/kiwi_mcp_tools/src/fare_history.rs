@@ -0,0 +1,228 @@
+//! Fares a route has shown in past searches, taken from the search history:
+//! the cheapest result of each day's searches for a departure month, and
+//! where a price stands among them. Only searches of a single departure date
+//! with the same cabin, party, stay and filters count, so the fares compare
+//! like with like.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::{
+    history::HistoryEntry,
+    search::{SearchOptions, parse_date},
+};
+
+/// Search parameters that change the fare, and so must be the same for two
+/// searches to count as the same route.
+const FARE_PARAMS: &[&str] = &[
+    "adults",
+    "children",
+    "infants",
+    "selected_cabins",
+    "mix_with_cabins",
+    "curr",
+    "max_stopovers",
+    "select_airlines",
+    "select_airlines_exclude",
+    "fly_days",
+    "ret_fly_days",
+    "one_for_city",
+    "nights_in_dst_from",
+    "nights_in_dst_to",
+];
+
+/// Days of searches needed before the fares say anything.
+pub const MIN_SAMPLES: usize = 3;
+
+/// The cheapest fare found by one day's searches.
+#[derive(Debug, Clone, Copy)]
+pub struct FareSample {
+    pub searched_on: NaiveDate,
+    pub price: f64,
+}
+
+/// Which past searches count as the same route.
+pub struct RouteQuery<'a> {
+    pub fly_from: &'a str,
+    pub fly_to: &'a str,
+    /// Nights at the destination for round trips, `None` for one-way.
+    pub nights: Option<u64>,
+    /// The cabin, party and filters searched with.
+    pub options: &'a SearchOptions,
+    /// Only departures in the month starting on this day.
+    pub month: Option<NaiveDate>,
+}
+
+impl RouteQuery<'_> {
+    /// The departure date of `entry` when it is a search of this route on
+    /// a single date, made with the same fare parameters.
+    fn matches(&self, entry: &HistoryEntry) -> Option<NaiveDate> {
+        let same = |param: &str, value: &str| {
+            entry
+                .param(param)
+                .is_some_and(|param| param.eq_ignore_ascii_case(value))
+        };
+        let round_trip =
+            entry.param("flight_type") == Some("round") || entry.param("return_from").is_some();
+        if !same("fly_from", self.fly_from)
+            || !same("fly_to", self.fly_to)
+            || entry.currency.as_deref() != Some(self.options.curr.as_str())
+            || round_trip != self.nights.is_some()
+        {
+            return None;
+        }
+
+        let mut expected: HashMap<&str, String> = self.options.params().into_iter().collect();
+        if let Some(nights) = self.nights {
+            expected.insert("nights_in_dst_from", nights.to_string());
+            expected.insert("nights_in_dst_to", nights.to_string());
+        }
+        if FARE_PARAMS
+            .iter()
+            .any(|param| entry.param(param) != expected.get(param).map(String::as_str))
+        {
+            return None;
+        }
+
+        // Ranges and whole months report the cheapest of many dates
+        let date_from = entry.param("date_from")?;
+        if entry.param("date_to") != Some(date_from) {
+            return None;
+        }
+        let departs = parse_date(date_from).ok()?;
+        let in_month = self
+            .month
+            .is_none_or(|month| departs.year() == month.year() && departs.month() == month.month());
+        in_month.then_some(departs)
+    }
+
+    /// The cheapest fare of each day the route was searched, oldest first.
+    pub fn fares(&self, entries: &[HistoryEntry]) -> Vec<FareSample> {
+        let mut days: BTreeMap<NaiveDate, FareSample> = BTreeMap::new();
+        for entry in entries {
+            let (Some(_), Some(price)) = (self.matches(entry), entry.cheapest_price) else {
+                continue;
+            };
+            let searched_on = entry.searched_at.date_naive();
+            let sample = FareSample { searched_on, price };
+            days.entry(searched_on)
+                .and_modify(|best| {
+                    if price < best.price {
+                        *best = sample;
+                    }
+                })
+                .or_insert(sample);
+        }
+        days.into_values().collect()
+    }
+}
+
+/// Where a price stands among the fares seen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Standing {
+    /// Below every fare seen.
+    Lowest,
+    /// In the cheapest quarter.
+    Low,
+    Typical,
+    /// In the priciest quarter, or above every fare seen.
+    High,
+}
+
+impl Standing {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::Lowest => "lower than any fare seen before: a very good deal",
+            Self::Low => "in the cheapest quarter of fares seen: a good deal",
+            Self::Typical => "around the typical fare",
+            Self::High => "in the priciest quarter of fares seen: above typical",
+        }
+    }
+}
+
+/// Which way fares have moved over the days sampled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Rising,
+    Steady,
+    Falling,
+}
+
+/// Change between the first and last third of the samples that counts as
+/// a trend.
+const TREND_SHARE: f64 = 0.05;
+
+/// How the latest third of `samples` compares with the earliest third, when
+/// there are enough samples to tell.
+pub fn trend(samples: &[FareSample]) -> Option<Trend> {
+    if samples.len() < MIN_SAMPLES + 1 {
+        return None;
+    }
+    let third = samples.len() / 3;
+    let average = |samples: &[FareSample]| {
+        samples.iter().map(|sample| sample.price).sum::<f64>() / samples.len() as f64
+    };
+    let earliest = average(&samples[..third]);
+    let latest = average(&samples[samples.len() - third..]);
+
+    Some(if latest > earliest * (1.0 + TREND_SHARE) {
+        Trend::Rising
+    } else if latest < earliest * (1.0 - TREND_SHARE) {
+        Trend::Falling
+    } else {
+        Trend::Steady
+    })
+}
+
+/// Summary of the fares seen on a route.
+#[derive(Debug, Clone, Copy)]
+pub struct FareStats {
+    pub samples: usize,
+    pub low: f64,
+    /// The fare a quarter of the samples are at or below.
+    pub lower_quartile: f64,
+    pub median: f64,
+    pub upper_quartile: f64,
+    pub high: f64,
+}
+
+impl FareStats {
+    /// `None` with fewer than [`MIN_SAMPLES`] samples.
+    pub fn new(samples: &[FareSample]) -> Option<Self> {
+        if samples.len() < MIN_SAMPLES {
+            return None;
+        }
+        let mut prices: Vec<f64> = samples.iter().map(|sample| sample.price).collect();
+        prices.sort_by(f64::total_cmp);
+
+        Some(Self {
+            samples: prices.len(),
+            low: prices[0],
+            lower_quartile: quantile(&prices, 0.25),
+            median: quantile(&prices, 0.5),
+            upper_quartile: quantile(&prices, 0.75),
+            high: prices[prices.len() - 1],
+        })
+    }
+
+    pub fn standing(&self, price: f64) -> Standing {
+        if price < self.low {
+            Standing::Lowest
+        } else if price <= self.lower_quartile {
+            Standing::Low
+        } else if price <= self.upper_quartile {
+            Standing::Typical
+        } else {
+            Standing::High
+        }
+    }
+}
+
+/// The `q` quantile of `sorted`, interpolating between neighbours.
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let below = position.floor() as usize;
+    let above = position.ceil() as usize;
+    sorted[below] + (sorted[above] - sorted[below]) * (position - below as f64)
+}
@@ -0,0 +1,754 @@
+use chrono::{DateTime, NaiveDate};
+
+use crate::{
+    mileage,
+    model::{Itinerary, format_local_time},
+    search::SearchOptions,
+    transfers, visas,
+};
+
+/// A flight with its 0-based position in the results.
+type Numbered<'a> = (usize, &'a Itinerary);
+
+/// Formats a result list the way every search tool presents it.
+pub fn format_flight_list(itineraries: &[Itinerary], options: &SearchOptions) -> String {
+    if itineraries.is_empty() {
+        return String::from("No flights found matching your criteria.");
+    }
+
+    let mut result = format!(
+        "Found {} flights matching your criteria:\n\n",
+        itineraries.len()
+    );
+    result.push_str(&format_top_picks(itineraries, options));
+    result.push_str(&format_summary(itineraries, options));
+    result.push('\n');
+
+    // Flights keep their position in the results as their number, so they
+    // can be referred to by it whatever the grouping
+    let numbered: Vec<(usize, &Itinerary)> = itineraries.iter().enumerate().collect();
+
+    let Some(group_by) = options.group_by else {
+        push_flights(&mut result, &numbered, options);
+        return result;
+    };
+
+    // Groups appear in the order of their first result
+    let mut groups: Vec<(String, Vec<(usize, &Itinerary)>)> = Vec::new();
+    for (i, flight) in numbered {
+        let key = group_by.key(flight);
+        match groups.iter_mut().find(|(group, _)| *group == key) {
+            Some((_, flights)) => flights.push((i, flight)),
+            None => groups.push((key, vec![(i, flight)])),
+        }
+    }
+
+    for (g, (group, flights)) in groups.iter().enumerate() {
+        let cheapest = flights
+            .iter()
+            .map(|(_, flight)| flight.price)
+            .fold(f64::INFINITY, f64::min);
+        result.push_str(&format!(
+            "=== {} ({} flights, from {}) ===\n\n",
+            group,
+            flights.len(),
+            options.price(cheapest)
+        ));
+        push_flights(&mut result, flights, options);
+
+        if g < groups.len() - 1 {
+            result.push_str("\n\n");
+        }
+    }
+
+    result
+}
+
+fn push_flights(result: &mut String, flights: &[(usize, &Itinerary)], options: &SearchOptions) {
+    let entries = if options.collapse_repeats {
+        collapse_repeats(flights)
+    } else {
+        flights.iter().map(|flight| (*flight, Vec::new())).collect()
+    };
+
+    for (n, ((i, flight), repeats)) in entries.iter().enumerate() {
+        result.push_str(&format_itinerary(
+            &format!("Flight {} [{}]", i + 1, flight.reference()),
+            flight,
+            options,
+        ));
+        if !repeats.is_empty() {
+            result.push_str(&format_repeats(flight, repeats, options));
+        }
+
+        // Add a separator between flights
+        if n < entries.len() - 1 {
+            result.push_str("\n---\n\n");
+        }
+    }
+}
+
+/// Pairs each flight shown with the other results of the same flights at
+/// the same times on other dates. Each set is shown once, as its cheapest
+/// result, where its first result was.
+fn collapse_repeats<'a>(flights: &[Numbered<'a>]) -> Vec<(Numbered<'a>, Vec<Numbered<'a>>)> {
+    let mut groups: Vec<(Option<String>, Vec<Numbered>)> = Vec::new();
+    for &(i, flight) in flights {
+        let key = schedule_key(flight);
+        match groups
+            .iter_mut()
+            .find(|(group, _)| key.is_some() && *group == key)
+        {
+            Some((_, members)) => members.push((i, flight)),
+            None => groups.push((key, vec![(i, flight)])),
+        }
+    }
+
+    let mut entries = Vec::new();
+    for (_, mut members) in groups {
+        let mut dates: Vec<Option<NaiveDate>> = members
+            .iter()
+            .map(|(_, flight)| flight.local_departure_date())
+            .collect();
+        dates.sort();
+        dates.dedup();
+
+        // The same date twice is a different fare, not a repeat
+        if dates.len() < 2 {
+            entries.extend(members.into_iter().map(|member| (member, Vec::new())));
+            continue;
+        }
+
+        members.sort_by(|(_, a), (_, b)| a.price.total_cmp(&b.price));
+        let cheapest = members.remove(0);
+        entries.push((cheapest, members));
+    }
+    entries
+}
+
+/// The flights flown and their local departure times, without dates, or
+/// `None` when a leg lacks a flight number or time.
+fn schedule_key(flight: &Itinerary) -> Option<String> {
+    let legs = flight
+        .route
+        .iter()
+        .map(|leg| {
+            leg.flight_no?;
+            let departs = DateTime::parse_from_rfc3339(leg.local_departure.as_deref()?).ok()?;
+            Some(format!("{}@{}", leg.flight(), departs.format("%H:%M")))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    (!legs.is_empty()).then(|| legs.join("/"))
+}
+
+/// e.g. `Flies daily at 07:15 from 12 Jun to 16 Jun, from €49 (cheapest on
+/// 14 Jun); other dates are Flights 3, 5, 8, 9`.
+fn format_repeats(cheapest: &Itinerary, repeats: &[Numbered], options: &SearchOptions) -> String {
+    let mut dates: Vec<NaiveDate> = std::iter::once(cheapest)
+        .chain(repeats.iter().map(|(_, flight)| *flight))
+        .filter_map(Itinerary::local_departure_date)
+        .collect();
+    dates.sort();
+    dates.dedup();
+
+    let (Some(first), Some(last)) = (dates.first(), dates.last()) else {
+        return String::new();
+    };
+    let time = cheapest
+        .local_departure
+        .as_deref()
+        .and_then(|departure| DateTime::parse_from_rfc3339(departure).ok())
+        .map(|departure| departure.format("%H:%M").to_string())
+        .unwrap_or_else(|| "the same time".to_string());
+    let daily = (*last - *first).num_days() + 1 == dates.len() as i64;
+
+    let mut line = if daily {
+        format!(
+            "Flies daily at {} from {} to {}",
+            time,
+            first.format("%d %b"),
+            last.format("%d %b")
+        )
+    } else {
+        format!(
+            "Flies on {} dates at {} between {} and {}",
+            dates.len(),
+            time,
+            first.format("%d %b"),
+            last.format("%d %b")
+        )
+    };
+    if let Some(date) = cheapest.local_departure_date() {
+        line.push_str(&format!(
+            ", from {} (cheapest on {})",
+            options.price(cheapest.price),
+            date.format("%d %b")
+        ));
+    }
+
+    let numbers: Vec<String> = repeats.iter().map(|(i, _)| (i + 1).to_string()).collect();
+    format!(
+        "{}; other dates are Flight{} {}\n",
+        line,
+        if numbers.len() == 1 { "" } else { "s" },
+        numbers.join(", ")
+    )
+}
+
+/// The cheapest, the fastest and the best balance of price and travel time,
+/// one line each with why it was picked. Empty for fewer than two results.
+fn format_top_picks(itineraries: &[Itinerary], options: &SearchOptions) -> String {
+    if itineraries.len() < 2 {
+        return String::new();
+    }
+
+    let cheapest = itineraries
+        .iter()
+        .min_by(|a, b| a.price.total_cmp(&b.price))
+        .expect("itineraries is not empty");
+    let fastest = itineraries
+        .iter()
+        .min_by_key(|flight| flight.duration.total)
+        .expect("itineraries is not empty");
+
+    // Price and time relative to the best of each, so a 10% dearer flight
+    // that is 10% quicker scores the same
+    let min_price = cheapest.price.max(1.0);
+    let min_duration = fastest.duration.total.max(1) as f64;
+    let score =
+        |flight: &Itinerary| flight.price / min_price + flight.duration.total as f64 / min_duration;
+    let balanced = itineraries
+        .iter()
+        .min_by(|a, b| score(a).total_cmp(&score(b)))
+        .expect("itineraries is not empty");
+
+    let position = |target: &Itinerary| {
+        itineraries
+            .iter()
+            .position(|flight| std::ptr::eq(flight, target))
+            .map_or(0, |i| i + 1)
+    };
+    let describe = |flight: &Itinerary| {
+        let stops = match flight.route.len().saturating_sub(1) {
+            0 => "direct".to_string(),
+            1 => "with 1 stop".to_string(),
+            n => format!("with {} stops", n),
+        };
+        format!(
+            "Flight {} at {}, {} {}",
+            position(flight),
+            options.price(flight.price),
+            options.duration(flight.duration.total),
+            stops
+        )
+    };
+
+    let mut picks = String::from("Top picks:\n");
+    if std::ptr::eq(cheapest, fastest) {
+        picks.push_str(&format!(
+            "  Cheapest and fastest: {}, nothing beats it on either\n",
+            describe(cheapest)
+        ));
+        picks.push('\n');
+        return picks;
+    }
+
+    picks.push_str(&format!(
+        "  Cheapest: {}, {} longer than the fastest\n",
+        describe(cheapest),
+        options.duration(cheapest.duration.total - fastest.duration.total)
+    ));
+    picks.push_str(&format!(
+        "  Fastest: {}, {} more than the cheapest\n",
+        describe(fastest),
+        options.price(fastest.price - cheapest.price)
+    ));
+    if !std::ptr::eq(balanced, cheapest) && !std::ptr::eq(balanced, fastest) {
+        picks.push_str(&format!(
+            "  Best balance: {}, {} more than the cheapest and {} longer than the fastest\n",
+            describe(balanced),
+            options.price(balanced.price - cheapest.price),
+            options.duration(balanced.duration.total - fastest.duration.total)
+        ));
+    } else {
+        let which = if std::ptr::eq(balanced, cheapest) {
+            "cheapest"
+        } else {
+            "fastest"
+        };
+        picks.push_str(&format!(
+            "  Best balance: the {}; no other flight trades price for time better\n",
+            which
+        ));
+    }
+    picks.push('\n');
+    picks
+}
+
+/// Statistics over the whole result set, so questions like "what's the
+/// price range?" don't need every entry read.
+pub fn format_summary(itineraries: &[Itinerary], options: &SearchOptions) -> String {
+    let mut prices: Vec<f64> = itineraries.iter().map(|flight| flight.price).collect();
+    prices.sort_by(f64::total_cmp);
+
+    let median = match prices.len() {
+        0 => return String::new(),
+        n if n % 2 == 0 => (prices[n / 2 - 1] + prices[n / 2]) / 2.0,
+        n => prices[n / 2],
+    };
+
+    // Positions are 1-based to match the flight numbers below
+    let position = |target: &Itinerary| {
+        itineraries
+            .iter()
+            .position(|flight| std::ptr::eq(flight, target))
+            .map_or(0, |i| i + 1)
+    };
+    let cheapest = itineraries
+        .iter()
+        .min_by(|a, b| a.price.total_cmp(&b.price))
+        .expect("itineraries is not empty");
+    let fastest = itineraries
+        .iter()
+        .min_by_key(|flight| flight.duration.total)
+        .expect("itineraries is not empty");
+    let direct = itineraries
+        .iter()
+        .filter(|flight| flight.is_direct())
+        .count();
+
+    let mut summary = String::from("Summary:\n");
+    summary.push_str(&format!(
+        "  Price range: {} - {} (median {})\n",
+        options.price(prices[0]),
+        options.price(prices[prices.len() - 1]),
+        options.price(median)
+    ));
+    summary.push_str(&format!(
+        "  Cheapest: Flight {} at {}\n",
+        position(cheapest),
+        options.price(cheapest.price)
+    ));
+    summary.push_str(&format!(
+        "  Fastest: Flight {} at {}\n",
+        position(fastest),
+        options.duration(fastest.duration.total)
+    ));
+    summary.push_str(&format!(
+        "  Direct flights: {} of {}\n",
+        direct,
+        itineraries.len()
+    ));
+
+    if options.one_per_city {
+        summary.push_str(&format_country_summary(itineraries, options));
+    }
+
+    summary
+}
+
+/// One line per destination country: how many cities, and the cheapest.
+fn format_country_summary(itineraries: &[Itinerary], options: &SearchOptions) -> String {
+    let mut countries: Vec<(String, Vec<&Itinerary>)> = Vec::new();
+    for flight in itineraries {
+        let country = flight
+            .country_to
+            .as_ref()
+            .map(|country| country.name.clone())
+            .unwrap_or_else(|| "Unknown country".to_string());
+        match countries.iter_mut().find(|(name, _)| *name == country) {
+            Some((_, flights)) => flights.push(flight),
+            None => countries.push((country, vec![flight])),
+        }
+    }
+
+    let mut summary = String::from("  By country:\n");
+    for (country, flights) in countries {
+        let Some(cheapest) = flights.iter().min_by(|a, b| a.price.total_cmp(&b.price)) else {
+            continue;
+        };
+        summary.push_str(&format!(
+            "    {}: {} {}, cheapest {} at {}\n",
+            country,
+            flights.len(),
+            if flights.len() == 1 { "city" } else { "cities" },
+            cheapest.city_to,
+            options.price(cheapest.price)
+        ));
+    }
+
+    summary
+}
+
+/// Formats a single itinerary under the given heading, e.g. `Flight 1`.
+pub fn format_itinerary(label: &str, flight: &Itinerary, options: &SearchOptions) -> String {
+    let mut result = String::new();
+
+    let departure = flight
+        .local_departure
+        .as_deref()
+        .map(format_local_time)
+        .unwrap_or_else(|| "Unknown".to_string());
+    let arrival = flight
+        .local_arrival
+        .as_deref()
+        .map(format_local_time)
+        .unwrap_or_else(|| "Unknown".to_string());
+
+    let airlines = if flight.airlines.is_empty() {
+        "Unknown".to_string()
+    } else {
+        flight.airlines.join(", ")
+    };
+
+    // Stopovers information
+    let stops = flight.route.len().saturating_sub(1);
+    let stop_description = match stops {
+        0 => "Direct flight".to_string(),
+        1 => "1 stopover".to_string(),
+        n => format!("{} stopovers", n),
+    };
+
+    // Baggage allowance
+    let baggage_info = match &flight.bags_price {
+        Some(bags_price) => format!(
+            "First checked bag: {}",
+            options.price(bags_price.get("1").copied().unwrap_or(0.0))
+        ),
+        None => "Baggage information not available".to_string(),
+    };
+
+    result.push_str(&format!(
+        "{}: {} ({}) → {} ({})\n",
+        label, flight.city_from, flight.fly_from, flight.city_to, flight.fly_to
+    ));
+    result.push_str(&format!("Price: {}\n", format_price(flight, options)));
+    result.push_str(&format!("Departure: {}\n", departure));
+    result.push_str(&format!("Arrival: {}\n", arrival));
+    let outbound: Vec<_> = flight
+        .route
+        .iter()
+        .filter(|leg| leg.return_leg == 0)
+        .collect();
+    if let (Some(first), Some(last)) = (outbound.first(), outbound.last())
+        && (first.terminal_from.is_some() || last.terminal_to.is_some())
+    {
+        result.push_str(&format!(
+            "Terminals: departs {}, arrives {}\n",
+            first.departure_airport(),
+            last.arrival_airport()
+        ));
+    }
+    if flight.is_round_trip() {
+        result.push_str(&format!(
+            "Duration: outbound {}, return {}\n",
+            format_direction_duration(flight, options, 0, flight.duration.departure),
+            format_direction_duration(flight, options, 1, flight.duration.return_)
+        ));
+    } else {
+        result.push_str(&format!(
+            "Duration: {}\n",
+            format_direction_duration(flight, options, 0, flight.duration.total)
+        ));
+    }
+    result.push_str(&format!("Airline(s): {}\n", airlines));
+    let ratings: Vec<String> = flight
+        .airlines
+        .iter()
+        .filter_map(|airline| {
+            let rating = options.airline_ratings.get(airline)?;
+            Some(format!("{} {}", airline, rating))
+        })
+        .collect();
+    if !ratings.is_empty() {
+        result.push_str(&format!("Airline quality: {}\n", ratings.join("; ")));
+    }
+    if let Some(program) = options.frequent_flyer_program
+        && let Some((award, status)) = mileage::estimate(program, flight)
+    {
+        result.push_str(&format!(
+            "Estimated {} miles: ~{} award, ~{} status\n",
+            program.name, award, status
+        ));
+    }
+    result.push_str(&format!("Stops: {}\n", stop_description));
+    for leg in flight.route.iter().filter(|leg| options.is_red_eye(leg)) {
+        result.push_str(&format!(
+            "Red-eye: {} → {} departs {}, arrives {}\n",
+            leg.fly_from,
+            leg.fly_to,
+            leg.local_departure
+                .as_deref()
+                .map(format_local_time)
+                .unwrap_or_else(|| "Unknown".to_string()),
+            leg.local_arrival
+                .as_deref()
+                .map(format_local_time)
+                .unwrap_or_else(|| "Unknown".to_string())
+        ));
+    }
+    result.push_str(&format!("{}\n", baggage_info));
+    for policy in options.infant_policies_for(flight) {
+        let mut line = format!("Infant policy ({}):", policy.airline);
+        if !policy.sells_online {
+            line.push_str(" infant fares can't be booked online; contact the airline");
+            if policy.note.is_some() {
+                line.push(';');
+            }
+        }
+        if let Some(note) = &policy.note {
+            line.push(' ');
+            line.push_str(note);
+        }
+        result.push_str(&format!("{}\n", line));
+    }
+    if let Some(nationality) = &options.nationality {
+        let hints = visas::hints(nationality, flight);
+        if !hints.is_empty() {
+            result.push_str("Travel documents (informational only, check official sources):\n");
+            for hint in hints {
+                result.push_str(&format!("  {}\n", hint));
+            }
+        }
+    }
+    result.push_str(&format!(
+        "Booking link: {}\n",
+        flight
+            .deep_link
+            .as_deref()
+            .unwrap_or("Booking link not available")
+    ));
+    if let Some(link) = route_map_link(flight) {
+        result.push_str(&format!("Route map: {}\n", link));
+    }
+
+    // Add route details for flights with stopovers
+    if stops > 0 {
+        result.push_str("Route details:\n");
+        for (j, leg) in flight.route.iter().enumerate() {
+            let carrier = match leg.cabin() {
+                Some(cabin) => format!("{}, {}", leg.airline, cabin),
+                None => leg.airline.clone(),
+            };
+            let terminals = if leg.has_terminals() {
+                format!(", {} → {}", leg.departure_airport(), leg.arrival_airport())
+            } else {
+                String::new()
+            };
+            result.push_str(&format!(
+                "  Leg {}: {} → {} ({}){}\n",
+                j + 1,
+                leg.city_from,
+                leg.city_to,
+                carrier,
+                terminals
+            ));
+        }
+
+        for layover in flight.layovers() {
+            let required = options.comfortable_connection_minutes(&layover);
+            let available = layover.duration.num_minutes();
+            let tight = if available < required as i64 {
+                "Tight connection! "
+            } else {
+                ""
+            };
+
+            if layover.changes_airport() {
+                let from = &layover.arriving.fly_to;
+                let to = &layover.departing.fly_from;
+                let distance = transfers::find(from, to)
+                    .map(|t| format!("about {} km, ", t.distance_km))
+                    .unwrap_or_default();
+
+                result.push_str(&format!(
+                    "  {}Airport change in {}: arrive {}, depart {} ({}allow at least {} between flights; you have {})\n",
+                    tight,
+                    layover.arriving.city_to,
+                    from,
+                    to,
+                    distance,
+                    options.duration(required as i64 * 60),
+                    options.duration(layover.duration.num_seconds())
+                ));
+            } else if !tight.is_empty() {
+                result.push_str(&format!(
+                    "  {}Only {} to connect in {} (at least {} recommended)\n",
+                    tight,
+                    options.duration(layover.duration.num_seconds()),
+                    layover.arriving.city_to,
+                    options.duration(required as i64 * 60)
+                ));
+            }
+        }
+    }
+
+    result
+}
+
+/// The itinerary's price, labelled as the party total when there is more
+/// than one passenger.
+fn format_price(flight: &Itinerary, options: &SearchOptions) -> String {
+    let passengers = options.seated() + options.infants;
+    if passengers <= 1 {
+        return options.price(flight.price);
+    }
+
+    let mut price = format!(
+        "{} total for {} passengers",
+        options.price(flight.price),
+        passengers
+    );
+    if options.per_person_prices {
+        if let Some(fare) = &flight.fare
+            && fare.adults > 0.0
+        {
+            price.push_str(&format!(", {} per adult", options.price(fare.adults)));
+        }
+        price.push_str(&format!(
+            ", {} per person on average",
+            options.price(flight.price / passengers as f64)
+        ));
+    }
+    price
+}
+
+/// Door-to-door time for one direction, split into time in the air and on
+/// the ground when there are connections.
+fn format_direction_duration(
+    flight: &Itinerary,
+    options: &SearchOptions,
+    direction: u8,
+    seconds: i64,
+) -> String {
+    let duration = options.duration(seconds);
+    match flight.air_seconds(direction) {
+        Some(air) if air < seconds => format!(
+            "{} ({} in the air, {} on the ground)",
+            duration,
+            options.duration(air),
+            options.duration(seconds - air)
+        ),
+        _ => duration,
+    }
+}
+
+/// One-line description of an itinerary for lists and diffs, e.g.
+/// `Prague → London, 01 Jun 2025, 10:30 (FR 1234)`.
+pub fn format_itinerary_summary(flight: &Itinerary) -> String {
+    let flights: Vec<String> = flight.route.iter().map(|leg| leg.flight()).collect();
+
+    format!(
+        "{} → {}, {} ({})",
+        flight.city_from,
+        flight.city_to,
+        flight
+            .local_departure
+            .as_deref()
+            .map(format_local_time)
+            .unwrap_or_else(|| "Unknown".to_string()),
+        if flights.is_empty() {
+            flight.airlines.join(", ")
+        } else {
+            flights.join(", ")
+        }
+    )
+}
+
+/// Great Circle Mapper link drawing every leg of the itinerary.
+pub fn route_map_link(flight: &Itinerary) -> Option<String> {
+    if flight.route.is_empty() {
+        return None;
+    }
+
+    let paths: Vec<String> = flight
+        .route
+        .iter()
+        .map(|leg| format!("{}-{}", leg.fly_from, leg.fly_to))
+        .collect();
+
+    Some(format!("http://www.gcmap.com/mapui?P={}", paths.join(",")))
+}
+
+/// Formats a duration given in seconds as `7h 30m`.
+pub fn format_duration(seconds: i64) -> String {
+    let minutes = seconds / 60;
+    format!("{}h {}m", minutes / 60, minutes % 60)
+}
+
+/// Formats a duration given in seconds the way it would be said: `45
+/// minutes`, `about 7½ hours`, `2 days 3 hours`. Long durations are
+/// rounded to the half hour, or to the hour past a day.
+pub fn format_duration_humanized(seconds: i64) -> String {
+    let minutes = seconds.max(0) / 60;
+    if minutes < 60 {
+        return plural(minutes, "minute");
+    }
+
+    let (text, rounded) = if minutes < 24 * 60 {
+        let halves = (minutes + 15) / 30;
+        let hours = match (halves / 2, halves % 2) {
+            (1, 0) => "1 hour".to_string(),
+            (hours, 0) => format!("{} hours", hours),
+            (hours, _) => format!("{}½ hours", hours),
+        };
+        (hours, halves * 30)
+    } else {
+        let hours = (minutes + 30) / 60;
+        let text = match (hours / 24, hours % 24) {
+            (days, 0) => plural(days, "day"),
+            (days, hours) => format!("{} {}", plural(days, "day"), plural(hours, "hour")),
+        };
+        (text, hours * 60)
+    };
+
+    if rounded == minutes {
+        text
+    } else {
+        format!("about {}", text)
+    }
+}
+
+fn plural(count: i64, unit: &str) -> String {
+    if count == 1 {
+        format!("1 {}", unit)
+    } else {
+        format!("{} {}s", count, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compact_durations() {
+        assert_eq!(format_duration(0), "0h 0m");
+        assert_eq!(format_duration(27_000), "7h 30m");
+        assert_eq!(format_duration(100_000), "27h 46m");
+    }
+
+    #[test]
+    fn humanized_durations() {
+        assert_eq!(format_duration_humanized(-60), "0 minutes");
+        assert_eq!(format_duration_humanized(60), "1 minute");
+        assert_eq!(format_duration_humanized(45 * 60), "45 minutes");
+        assert_eq!(format_duration_humanized(60 * 60), "1 hour");
+        assert_eq!(format_duration_humanized(7 * 3600 + 30 * 60), "7½ hours");
+        assert_eq!(
+            format_duration_humanized(7 * 3600 + 40 * 60),
+            "about 7½ hours"
+        );
+        assert_eq!(
+            format_duration_humanized(7 * 3600 + 50 * 60),
+            "about 8 hours"
+        );
+        assert_eq!(format_duration_humanized(24 * 3600), "1 day");
+        assert_eq!(format_duration_humanized(51 * 3600), "2 days 3 hours");
+        assert_eq!(
+            format_duration_humanized(51 * 3600 + 20 * 60),
+            "about 2 days 3 hours"
+        );
+    }
+}
@@ -0,0 +1,230 @@
+//! Output formats for trips. Each format is a [`ResultFormatter`]; the one
+//! used comes from a search's `output_format`, or `defaults.output_format`
+//! in the config. A new format is a new implementation added to
+//! [`FORMATTERS`].
+
+use std::fmt::Debug;
+
+use serde_json::{Value, json};
+
+use crate::{
+    format::format_itinerary,
+    model::{Itinerary, format_local_time},
+    search::SearchOptions,
+    trip::Trip,
+};
+
+/// Writes trips for the model to read. Each comes with the heading the
+/// tool gave it, e.g. `Option 1: via FRA, total €420.00`.
+pub trait ResultFormatter: Debug + Send + Sync {
+    fn format_trips(&self, trips: &[(String, &Trip)], options: &SearchOptions) -> String;
+}
+
+/// Every format, by the name calls and the config select it with.
+const FORMATTERS: &[(&str, &dyn ResultFormatter)] = &[
+    ("text", &TextFormatter),
+    ("markdown", &MarkdownFormatter),
+    ("json", &JsonFormatter),
+    ("compact", &CompactFormatter),
+];
+
+/// The format called `name`.
+pub fn find(name: &str) -> Option<&'static dyn ResultFormatter> {
+    FORMATTERS
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map(|(_, formatter)| *formatter)
+}
+
+/// Names of every format, for error messages.
+pub fn names() -> impl Iterator<Item = &'static str> {
+    FORMATTERS.iter().map(|(name, _)| *name)
+}
+
+/// The detailed plain text view, with the time spent between tickets.
+#[derive(Debug)]
+pub struct TextFormatter;
+
+impl ResultFormatter for TextFormatter {
+    fn format_trips(&self, trips: &[(String, &Trip)], options: &SearchOptions) -> String {
+        let sections: Vec<String> = trips
+            .iter()
+            .map(|(heading, trip)| {
+                let mut section = format!("{}\n\n", heading);
+                for (i, ticket) in trip.tickets.iter().enumerate() {
+                    if i > 0 {
+                        match trip.time_between(i - 1) {
+                            Some(time) => section.push_str(&format!(
+                                "\nTime in {} between tickets: {}\n\n",
+                                ticket.itinerary.city_from,
+                                options.duration(time.num_seconds())
+                            )),
+                            None => section.push('\n'),
+                        }
+                    }
+                    section.push_str(&format_itinerary(&ticket.label, &ticket.itinerary, options));
+                }
+                section
+            })
+            .collect();
+        sections.join("\n---\n\n")
+    }
+}
+
+/// Headings and a table of flights per ticket, for clients that render
+/// Markdown.
+#[derive(Debug)]
+pub struct MarkdownFormatter;
+
+impl ResultFormatter for MarkdownFormatter {
+    fn format_trips(&self, trips: &[(String, &Trip)], options: &SearchOptions) -> String {
+        let mut result = String::new();
+        for (heading, trip) in trips {
+            let mut lines = heading.lines();
+            result.push_str(&format!("### {}\n\n", lines.next().unwrap_or_default()));
+            for line in lines {
+                result.push_str(&format!("{}\n\n", line));
+            }
+
+            for (i, ticket) in trip.tickets.iter().enumerate() {
+                if i > 0
+                    && let Some(time) = trip.time_between(i - 1)
+                {
+                    result.push_str(&format!(
+                        "_{} in {} between tickets_\n\n",
+                        options.duration(time.num_seconds()),
+                        ticket.itinerary.city_from
+                    ));
+                }
+                let itinerary = &ticket.itinerary;
+                result.push_str(&format!(
+                    "**{}** [{}]: {} → {}, {}, {}\n\n",
+                    ticket.label,
+                    itinerary.reference(),
+                    itinerary.city_from,
+                    itinerary.city_to,
+                    options.price(itinerary.price),
+                    options.duration(itinerary.duration.total)
+                ));
+                result.push_str("| Flight | From | Departs | To | Arrives |\n");
+                result.push_str("|---|---|---|---|---|\n");
+                for leg in &itinerary.route {
+                    result.push_str(&format!(
+                        "| {} | {} | {} | {} | {} |\n",
+                        leg.flight(),
+                        leg.departure_airport(),
+                        local_time(&leg.local_departure),
+                        leg.arrival_airport(),
+                        local_time(&leg.local_arrival)
+                    ));
+                }
+                if let Some(link) = &itinerary.deep_link {
+                    result.push_str(&format!("\n[Book]({})\n", link));
+                }
+                result.push('\n');
+            }
+        }
+        result
+    }
+}
+
+/// Structured data, for clients that process results rather than show
+/// them.
+#[derive(Debug)]
+pub struct JsonFormatter;
+
+impl ResultFormatter for JsonFormatter {
+    fn format_trips(&self, trips: &[(String, &Trip)], options: &SearchOptions) -> String {
+        let trips: Vec<Value> = trips
+            .iter()
+            .map(|(heading, trip)| {
+                let between: Vec<Option<i64>> = (0..trip.tickets.len().saturating_sub(1))
+                    .map(|i| trip.time_between(i).map(|time| time.num_seconds()))
+                    .collect();
+                json!({
+                    "heading": heading.trim(),
+                    "price": trip.price(),
+                    "currency": options.curr,
+                    "tickets": trip
+                        .tickets
+                        .iter()
+                        .map(|ticket| ticket_json(&ticket.label, &ticket.itinerary))
+                        .collect::<Vec<_>>(),
+                    "seconds_between_tickets": between,
+                })
+            })
+            .collect();
+        let mut result = serde_json::to_string_pretty(&json!({ "trips": trips }))
+            .unwrap_or_else(|_| "{}".to_string());
+        result.push('\n');
+        result
+    }
+}
+
+fn ticket_json(label: &str, itinerary: &Itinerary) -> Value {
+    json!({
+        "label": label,
+        "reference": itinerary.reference(),
+        "from": itinerary.fly_from,
+        "to": itinerary.fly_to,
+        "local_departure": itinerary.local_departure,
+        "local_arrival": itinerary.local_arrival,
+        "duration_seconds": itinerary.duration.total,
+        "price": itinerary.price,
+        "airlines": itinerary.airlines,
+        "booking_link": itinerary.deep_link,
+        "flights": itinerary
+            .route
+            .iter()
+            .map(|leg| {
+                json!({
+                    "flight": leg.flight(),
+                    "from": leg.fly_from,
+                    "to": leg.fly_to,
+                    "terminal_from": leg.terminal_from,
+                    "terminal_to": leg.terminal_to,
+                    "local_departure": leg.local_departure,
+                    "local_arrival": leg.local_arrival,
+                })
+            })
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// One line per ticket, to keep long result lists short.
+#[derive(Debug)]
+pub struct CompactFormatter;
+
+impl ResultFormatter for CompactFormatter {
+    fn format_trips(&self, trips: &[(String, &Trip)], options: &SearchOptions) -> String {
+        let mut result = String::new();
+        for (heading, trip) in trips {
+            let heading: Vec<&str> = heading.lines().filter(|line| !line.is_empty()).collect();
+            result.push_str(&format!("{}\n", heading.join("; ")));
+            for ticket in &trip.tickets {
+                let itinerary = &ticket.itinerary;
+                let flights: Vec<String> = itinerary.route.iter().map(|leg| leg.flight()).collect();
+                result.push_str(&format!(
+                    "  {} [{}]: {} {} → {} {}, {}, {} ({})\n",
+                    ticket.label,
+                    itinerary.reference(),
+                    itinerary.fly_from,
+                    local_time(&itinerary.local_departure),
+                    itinerary.fly_to,
+                    local_time(&itinerary.local_arrival),
+                    options.duration(itinerary.duration.total),
+                    options.price_format.amount(itinerary.price),
+                    flights.join(", ")
+                ));
+            }
+        }
+        result
+    }
+}
+
+fn local_time(timestamp: &Option<String>) -> String {
+    timestamp
+        .as_deref()
+        .map(format_local_time)
+        .unwrap_or_else(|| "Unknown".to_string())
+}
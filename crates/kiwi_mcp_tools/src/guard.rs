@@ -0,0 +1,94 @@
+//! Keeps one misbehaving tool from taking the server down: a call that runs
+//! past its time limit is abandoned, and a tool that panics fails its call
+//! instead of the process.
+
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use context_server::{Tool, ToolContent, ToolExecutor};
+use futures::FutureExt;
+use serde_json::Value;
+
+use crate::{diagnostics, error::KiwiToolError, redact::redact};
+
+/// Wraps a tool with a time limit and panic isolation.
+pub struct Guarded {
+    tool: Arc<dyn ToolExecutor>,
+    /// No limit when `None`.
+    timeout: Option<Duration>,
+}
+
+impl Guarded {
+    pub fn new(tool: Arc<dyn ToolExecutor>, timeout: Option<Duration>) -> Self {
+        Self { tool, timeout }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for Guarded {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        let call = AssertUnwindSafe(self.tool.execute(arguments)).catch_unwind();
+        let outcome = match self.timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, call).await {
+                Ok(outcome) => outcome,
+                Err(_) => {
+                    log::warn!(
+                        "Tool {} didn't finish within {}s; abandoned",
+                        self.tool.to_tool().name,
+                        timeout.as_secs()
+                    );
+                    return Err(KiwiToolError::ToolTimeout(timeout).into());
+                }
+            },
+            None => call.await,
+        };
+
+        // The panic hook has already reported the details
+        outcome.unwrap_or_else(|_| {
+            let name = self.tool.to_tool().name;
+            log::error!("Tool {} panicked", name);
+            Err(KiwiToolError::Internal(format!(
+                "The {} tool failed unexpectedly. The server is still running; other tools and later calls are unaffected",
+                name
+            ))
+            .into())
+        })
+    }
+
+    fn to_tool(&self) -> Tool {
+        self.tool.to_tool()
+    }
+}
+
+/// Logs panics as errors with secrets redacted, in place of the default
+/// report on stderr: panic messages often quote the payload that caused them.
+pub fn install_panic_hook() {
+    panic::set_hook(Box::new(|info| {
+        let location = info
+            .location()
+            .map(|location| format!(" at {}:{}", location.file(), location.line()))
+            .unwrap_or_default();
+        let report = format!(
+            "Internal error{}: {}",
+            location,
+            redact(&panic_message(info.payload()))
+        );
+        log::error!("{}. The request failed; the server keeps running", report);
+        diagnostics::record_panic(report);
+    }));
+}
+
+/// The message a panic was raised with, when it has one.
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "no message".to_string())
+}
@@ -0,0 +1,194 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Days, NaiveDate};
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient, airlines,
+    error::KiwiToolError,
+    input::{self, ToolInput},
+    search::{format_date, parse_date},
+};
+
+/// Days covered when no `date_to` is given: a week shows the pattern.
+const DEFAULT_WINDOW_DAYS: u64 = 7;
+const MAX_WINDOW_DAYS: u64 = 31;
+
+/// Nonstop flights sampled to tell carriers and frequency.
+const SAMPLE_SIZE: u64 = 200;
+
+#[derive(Deserialize, JsonSchema)]
+struct HasDirectFlightsArgs {
+    /// IATA code of departure airport or city (e.g., 'LHR', 'LON')
+    fly_from: String,
+    /// IATA code of arrival airport or city
+    fly_to: String,
+    /// First day of the window, in format dd/mm/yyyy
+    date_from: String,
+    /// Last day of the window, in format dd/mm/yyyy (default a week from date_from, at most 31 days)
+    date_to: Option<String>,
+}
+
+impl HasDirectFlightsArgs {
+    fn window(&self) -> Result<(NaiveDate, NaiveDate)> {
+        let from = parse_date(&self.date_from)?;
+        let to = match &self.date_to {
+            Some(date_to) => parse_date(date_to)?,
+            None => from + Days::new(DEFAULT_WINDOW_DAYS - 1),
+        };
+        Ok((from, to))
+    }
+}
+
+impl ToolInput for HasDirectFlightsArgs {
+    fn validate(&self) -> Result<()> {
+        let (from, to) = self.window()?;
+        if to < from {
+            return Err(KiwiToolError::validation("date_to must not be before date_from").into());
+        }
+        if (to - from).num_days() >= MAX_WINDOW_DAYS as i64 {
+            return Err(KiwiToolError::validation(format!(
+                "The window can span at most {} days",
+                MAX_WINDOW_DAYS
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Answers whether a nonstop connection exists between two places, and if
+/// so who flies it and how often.
+pub struct HasDirectFlightsTool {
+    client: Arc<KiwiClient>,
+}
+
+impl HasDirectFlightsTool {
+    pub fn new(client: Arc<KiwiClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for HasDirectFlightsTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing HasDirectFlightsTool");
+        let args: HasDirectFlightsArgs = input::parse(arguments)?;
+        let (from, to) = args.window()?;
+        let days = (to - from).num_days() + 1;
+
+        let params = |limit: u64| {
+            vec![
+                ("fly_from", args.fly_from.clone()),
+                ("fly_to", args.fly_to.clone()),
+                ("date_from", format_date(from)),
+                ("date_to", format_date(to)),
+                ("max_stopovers", "0".to_string()),
+                ("limit", limit.to_string()),
+            ]
+        };
+        let window = format!("between {} and {}", format_date(from), format_date(to));
+
+        // Most questions are answered "no" by a single cheap request
+        log::info!(
+            "Checking nonstop flights from {} to {}",
+            args.fly_from,
+            args.fly_to
+        );
+        let probe = self.client.search_itineraries(&params(1)).await?;
+        if probe.data.is_empty() {
+            let mut result = format!(
+                "No nonstop flights from {} to {} {}. Connecting flights may still be available; search with max_stopovers to find them.\n",
+                args.fly_from, args.fly_to, window
+            );
+            self.client.push_quota_warning(&mut result);
+            return Ok(vec![ToolContent::Text { text: result }]);
+        }
+
+        let sample = self.client.search_itineraries(&params(SAMPLE_SIZE)).await?;
+
+        // Flights by carrier, each one once however many fares it has
+        let mut flights: BTreeMap<&str, BTreeSet<(String, String)>> = BTreeMap::new();
+        let mut airports: BTreeSet<String> = BTreeSet::new();
+        for leg in sample
+            .data
+            .iter()
+            .filter(|itinerary| itinerary.route.len() == 1)
+            .flat_map(|itinerary| &itinerary.route)
+        {
+            airports.insert(format!("{} → {}", leg.fly_from, leg.fly_to));
+            flights.entry(&leg.airline).or_default().insert((
+                leg.flight(),
+                leg.local_departure.clone().unwrap_or_default(),
+            ));
+        }
+
+        let mut result = format!(
+            "Yes, there are nonstop flights from {} to {} {}.\n\nCarriers:\n",
+            args.fly_from, args.fly_to, window
+        );
+        let mut carriers: Vec<_> = flights.into_iter().collect();
+        carriers.sort_by_key(|(_, flights)| std::cmp::Reverse(flights.len()));
+        let mut total = 0;
+        for (code, flights) in &carriers {
+            total += flights.len();
+            let name = airlines::all()
+                .iter()
+                .find(|airline| airline.code == *code)
+                .map(|airline| format!("{} ({})", airline.name, code))
+                .unwrap_or_else(|| code.to_string());
+            let dates: BTreeSet<&str> = flights
+                .iter()
+                .map(|(_, departure)| departure.get(..10).unwrap_or_default())
+                .collect();
+            result.push_str(&format!(
+                "- {}: {} flights on {} of {} days, {}\n",
+                name,
+                flights.len(),
+                dates.len(),
+                days,
+                frequency(flights.len(), days)
+            ));
+        }
+        result.push_str(&format!(
+            "\nAcross all carriers: {}, flying {}.\n",
+            frequency(total, days),
+            airports.into_iter().collect::<Vec<_>>().join(", ")
+        ));
+        if sample.data.len() as u64 >= SAMPLE_SIZE {
+            result.push_str("\nThe counts come from a sample of the flights found, so the route may be busier still.\n");
+        }
+
+        self.client.push_quota_warning(&mut result);
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "has_direct_flights".into(),
+            description: Some(
+                "Check quickly whether nonstop flights exist between two places in a date window, and if so which airlines fly them and how often"
+                    .into(),
+            ),
+            input_schema: input::schema::<HasDirectFlightsArgs>(),
+        }
+    }
+}
+
+/// `flights` over `days` in words: "about 3 a day" or "about 2 a week".
+fn frequency(flights: usize, days: i64) -> String {
+    let per_day = flights as f64 / days as f64;
+    if per_day >= 1.0 {
+        format!("about {:.0} a day", per_day)
+    } else {
+        format!("about {:.0} a week", (per_day * 7.0).max(1.0))
+    }
+}
@@ -0,0 +1,217 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    encryption::{self, Cipher},
+    session, storage,
+};
+
+/// One search made through the server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub searched_at: DateTime<Utc>,
+    /// Query parameters sent to `/v2/search`.
+    pub params: BTreeMap<String, String>,
+    pub results: usize,
+    pub cheapest_price: Option<f64>,
+    pub currency: Option<String>,
+    /// Session the search was made in, when the transport has sessions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+}
+
+impl HistoryEntry {
+    pub fn from_search(params: &[(&str, String)], response: &Value) -> Self {
+        let data = response.get("data").and_then(|d| d.as_array());
+
+        Self {
+            searched_at: Utc::now(),
+            params: params
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect(),
+            results: data.map_or(0, |data| data.len()),
+            cheapest_price: data.and_then(|data| {
+                data.iter()
+                    .filter_map(|flight| flight.get("price").and_then(|p| p.as_f64()))
+                    .reduce(f64::min)
+            }),
+            currency: response
+                .get("currency")
+                .and_then(|c| c.as_str())
+                .map(String::from),
+            session: session::current(),
+        }
+    }
+
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.get(name).map(String::as_str)
+    }
+
+    pub fn route(&self) -> (&str, &str) {
+        (
+            self.param("fly_from").unwrap_or("?"),
+            self.param("fly_to").unwrap_or("?"),
+        )
+    }
+}
+
+/// Every search made through the server, kept in memory and appended to a
+/// JSON Lines file when a path is configured.
+#[derive(Default)]
+pub struct SearchHistory {
+    path: RwLock<Option<PathBuf>>,
+    entries: RwLock<Vec<HistoryEntry>>,
+}
+
+impl SearchHistory {
+    /// Switches to the history file at `path`, loading the entries it
+    /// already holds. `None` keeps history in memory only. Fails, rather
+    /// than risk losing entries, when the file can't be read or holds
+    /// entries that can't be decrypted or parsed.
+    pub fn set_path(&self, path: Option<PathBuf>) -> Result<()> {
+        if *self.path.read() == path {
+            return Ok(());
+        }
+
+        let (entries, unsealed) = match path.as_deref() {
+            Some(path) => load(path, encryption::cipher().as_deref())?,
+            None => (Vec::new(), false),
+        };
+
+        // Encrypt entries written before encryption was turned on
+        if let Some(path) = &path
+            && unsealed
+            && encryption::is_enabled()
+        {
+            rewrite(path, &entries)?;
+        }
+
+        *self.entries.write() = entries;
+        *self.path.write() = path;
+
+        Ok(())
+    }
+
+    /// Fails if the history file at `path` couldn't be loaded with
+    /// `cipher`, as [`set_path`](Self::set_path) would.
+    pub fn check(path: &Path, cipher: Option<&Cipher>) -> Result<()> {
+        load(path, cipher).map(|_| ())
+    }
+
+    pub fn record(&self, entry: HistoryEntry) {
+        if let Some(path) = self.path.read().as_ref()
+            && let Err(err) = append(path, &entry)
+        {
+            log::warn!(
+                "Failed to write search history to {}: {}",
+                path.display(),
+                err
+            );
+        }
+
+        self.entries.write().push(entry);
+    }
+
+    /// Adds `entries` that aren't recorded yet, keeping the history in
+    /// chronological order. Returns how many were added.
+    pub fn import(&self, entries: Vec<HistoryEntry>) -> Result<usize> {
+        let mut current = self.entries.write();
+        let new: Vec<HistoryEntry> = entries
+            .into_iter()
+            .filter(|entry| {
+                !current.iter().any(|existing| {
+                    existing.searched_at == entry.searched_at && existing.params == entry.params
+                })
+            })
+            .collect();
+
+        if let Some(path) = self.path.read().as_ref() {
+            for entry in &new {
+                append(path, entry)?;
+            }
+        }
+
+        let added = new.len();
+        current.extend(new);
+        current.sort_by_key(|entry| entry.searched_at);
+        Ok(added)
+    }
+
+    /// Deletes every entry, in memory and on disk. Returns how many there
+    /// were.
+    pub fn purge(&self) -> Result<usize> {
+        if let Some(path) = self.path.read().as_ref() {
+            storage::current().remove(path)?;
+        }
+
+        Ok(std::mem::take(&mut *self.entries.write()).len())
+    }
+
+    /// Entries in chronological order.
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        self.entries.read().clone()
+    }
+
+    /// Entries made in the current session, in chronological order.
+    pub fn session_entries(&self) -> Vec<HistoryEntry> {
+        let session = session::current();
+        self.entries
+            .read()
+            .iter()
+            .filter(|entry| entry.session == session)
+            .cloned()
+            .collect()
+    }
+}
+
+/// The entries in the history file at `path`, opened with `cipher`, and
+/// whether any of them were written unencrypted.
+fn load(path: &Path, cipher: Option<&Cipher>) -> Result<(Vec<HistoryEntry>, bool)> {
+    let contents = storage::read_to_string(path)
+        .with_context(|| format!("Failed to read the search history {}", path.display()))?
+        .unwrap_or_default();
+
+    let mut entries = Vec::new();
+    let mut unsealed = false;
+    for (number, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        unsealed |= !encryption::is_sealed(line);
+        let entry =
+            serde_json::from_str(&encryption::open_with(cipher, line)?).with_context(|| {
+                format!(
+                    "Line {} of the search history {} is corrupt; fix or remove it",
+                    number + 1,
+                    path.display()
+                )
+            })?;
+        entries.push(entry);
+    }
+    Ok((entries, unsealed))
+}
+
+fn append(path: &Path, entry: &HistoryEntry) -> Result<()> {
+    let line = format!("{}\n", encryption::seal(&serde_json::to_string(entry)?)?);
+    storage::current().append(path, line.as_bytes())
+}
+
+/// Replaces the file at `path` with `entries`.
+fn rewrite(path: &Path, entries: &[HistoryEntry]) -> Result<()> {
+    let mut contents = String::new();
+    for entry in entries {
+        contents.push_str(&encryption::seal(&serde_json::to_string(entry)?)?);
+        contents.push('\n');
+    }
+
+    storage::current().write(path, contents.as_bytes())
+}
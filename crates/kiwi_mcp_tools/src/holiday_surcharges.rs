@@ -0,0 +1,341 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Datelike, Days, Months, NaiveDate, Utc};
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    config::SearchDefaults,
+    error::KiwiToolError,
+    holidays::{self, HolidayPeriod},
+    input::{self, ToolInput},
+    progress::join_with_progress,
+    search::{SearchArgs, SearchOptions, format_date, month_days},
+};
+
+const DEFAULT_MONTHS: u64 = 6;
+const MAX_MONTHS: u64 = 12;
+
+/// Months sampled, at most, while a request budget is tight.
+const SPARING_MONTHS: u64 = 3;
+
+/// Results asked for per month: one per departure date, with room for
+/// filters.
+const MONTH_LIMIT: u64 = 62;
+
+/// How much dearer than usual a period's fares must be to be flagged.
+const SURCHARGE_SHARE: f64 = 0.15;
+
+/// Dates sampled in a period before its fares count.
+const MIN_PERIOD_DATES: usize = 2;
+
+/// Days either side of a period searched for a cheaper date to move to.
+const SHIFT_DAYS: u64 = 7;
+
+#[derive(Deserialize, JsonSchema)]
+struct HolidaySurchargesArgs {
+    /// IATA code of departure location (e.g., 'LHR', 'NYC')
+    fly_from: String,
+    /// IATA code of arrival location
+    fly_to: String,
+    /// How many months to sample, starting with the current one (default 6, at most 12)
+    #[serde(default = "default_months")]
+    months: u64,
+    /// Nights at the destination, to compare round trips (one-way when omitted)
+    nights: Option<u64>,
+    #[serde(flatten)]
+    search: SearchArgs,
+}
+
+fn default_months() -> u64 {
+    DEFAULT_MONTHS
+}
+
+impl ToolInput for HolidaySurchargesArgs {
+    fn validate(&self) -> Result<()> {
+        if !(1..=MAX_MONTHS).contains(&self.months) {
+            return Err(KiwiToolError::validation(format!(
+                "months must be between 1 and {}",
+                MAX_MONTHS
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// How a holiday period's fares compare with the rest of the dates.
+struct Surcharge {
+    period: HolidayPeriod,
+    dates: usize,
+    average: f64,
+    /// Share above the usual fare, e.g. 0.3 for 30% dearer.
+    share: f64,
+    /// The cheapest date near the period but outside every period.
+    shift: Option<(NaiveDate, f64)>,
+}
+
+/// Samples the cheapest fare on every date over the coming months and flags
+/// the holiday periods (Christmas, Easter and school breaks) where they
+/// rise, with a cheaper date nearby to move to.
+pub struct HolidaySurchargesTool {
+    client: Arc<KiwiClient>,
+    defaults: SearchDefaults,
+}
+
+impl HolidaySurchargesTool {
+    pub fn new(client: Arc<KiwiClient>, defaults: SearchDefaults) -> Self {
+        Self { client, defaults }
+    }
+
+    /// The cheapest fare on each date from `first` to `last`.
+    async fn sample(
+        &self,
+        route: &[(&'static str, String)],
+        first: NaiveDate,
+        last: NaiveDate,
+        options: &SearchOptions,
+    ) -> Result<BTreeMap<NaiveDate, f64>> {
+        let mut params = route.to_vec();
+        params.push(("date_from", format_date(first)));
+        params.push(("date_to", format_date(last)));
+        params.push(("one_per_date", "1".to_string()));
+        params.extend(options.params());
+
+        let mut response = self.client.search_itineraries(&params).await?;
+        options.apply_filters(&mut response.data);
+
+        let mut days = BTreeMap::new();
+        for itinerary in &response.data {
+            if let Some(date) = itinerary.local_departure_date() {
+                days.entry(date)
+                    .and_modify(|price: &mut f64| *price = price.min(itinerary.price))
+                    .or_insert(itinerary.price);
+            }
+        }
+        Ok(days)
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for HolidaySurchargesTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing HolidaySurchargesTool");
+        let args: HolidaySurchargesArgs = input::parse(arguments)?;
+
+        let options = SearchOptions {
+            limit: MONTH_LIMIT,
+            sort: "price".into(),
+            ..SearchOptions::from_args(&args.search, &self.defaults)?
+        };
+        let mut route = vec![
+            ("fly_from", args.fly_from.clone()),
+            ("fly_to", args.fly_to.clone()),
+        ];
+        if let Some(nights) = args.nights {
+            route.push(("flight_type", "round".to_string()));
+            route.push(("nights_in_dst_from", nights.to_string()));
+            route.push(("nights_in_dst_to", nights.to_string()));
+        }
+
+        let today = Utc::now().date_naive();
+        let first_month = today.with_day(1).unwrap_or(today);
+        let sparing = self.client.budget().is_tight() && args.months > SPARING_MONTHS;
+        let wanted = if sparing { SPARING_MONTHS } else { args.months };
+        let months: Vec<(NaiveDate, NaiveDate)> = (0..wanted as u32)
+            .filter_map(|i| first_month.checked_add_months(Months::new(i)))
+            .filter_map(|month| month_days(month, today))
+            .collect();
+
+        log::info!(
+            "Sampling {} months of fares from {} to {} for holiday surcharges",
+            months.len(),
+            args.fly_from,
+            args.fly_to
+        );
+
+        let searches: Vec<_> = months
+            .iter()
+            .map(|(first, last)| self.sample(&route, *first, *last, &options))
+            .collect();
+        let mut sampled = 0;
+        let samples = join_with_progress(self.client.progress(), searches, |_| {
+            sampled += 1;
+            Some(format!("{} of {} months sampled", sampled, months.len()))
+        })
+        .await;
+
+        let mut days: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        let mut first_error = None;
+        let mut any_sampled = false;
+        for sample in samples {
+            match sample {
+                Ok(sample) => {
+                    any_sampled = true;
+                    days.extend(sample);
+                }
+                Err(err) => {
+                    log::warn!("Holiday surcharge sample failed: {}", err);
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+        // Every month failing is most likely the request, e.g. an unknown
+        // location, rather than the API
+        if !any_sampled && let Some(err) = first_error {
+            return Err(err);
+        }
+        let (Some(first), Some(last)) = (
+            days.keys().next().copied(),
+            days.keys().next_back().copied(),
+        ) else {
+            return Ok(vec![ToolContent::Text {
+                text: String::from("No flights found matching your criteria."),
+            }]);
+        };
+
+        let country = options.holiday_country.as_deref();
+        let periods = holidays::holiday_periods(country, first, last);
+        let in_period = |date: &NaiveDate| periods.iter().any(|period| period.contains(*date));
+
+        let mut usual: Vec<f64> = days
+            .iter()
+            .filter(|(date, _)| !in_period(date))
+            .map(|(_, price)| *price)
+            .collect();
+        usual.sort_by(f64::total_cmp);
+        let Some(usual) = usual.get(usual.len() / 2).copied() else {
+            return Ok(vec![ToolContent::Text {
+                text: "Every sampled date falls in a holiday period, so there is nothing to compare their fares with.".to_string(),
+            }]);
+        };
+
+        let surcharges: Vec<Surcharge> = periods
+            .iter()
+            .filter_map(|period| {
+                let prices: Vec<f64> = days
+                    .range(period.start..=period.end)
+                    .map(|(_, price)| *price)
+                    .collect();
+                if prices.len() < MIN_PERIOD_DATES {
+                    return None;
+                }
+                let average = prices.iter().sum::<f64>() / prices.len() as f64;
+                let before = period.start.checked_sub_days(Days::new(SHIFT_DAYS))?;
+                let after = period.end.checked_add_days(Days::new(SHIFT_DAYS))?;
+                let shift = days
+                    .range(before..=after)
+                    .filter(|(date, _)| !in_period(date))
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                    .map(|(date, price)| (*date, *price));
+                Some(Surcharge {
+                    period: *period,
+                    dates: prices.len(),
+                    average,
+                    share: average / usual - 1.0,
+                    shift,
+                })
+            })
+            .collect();
+
+        let mut result = format!(
+            "Holiday fares for {} → {}{} from {} to {} (prices in {}):\n\n",
+            args.fly_from,
+            args.fly_to,
+            args.nights
+                .map(|nights| format!(", {} nights", nights))
+                .unwrap_or_default(),
+            first.format("%d %b %Y"),
+            last.format("%d %b %Y"),
+            options.curr
+        );
+        if sparing {
+            result.push_str(&format!(
+                "Sampled only {} of the {} months asked for, to save the request budget.\n",
+                SPARING_MONTHS, args.months
+            ));
+        }
+        result.push_str(&format!(
+            "Usual fare outside holiday periods: {} (median of {} dates).\n\n",
+            options.price(usual),
+            days.keys().filter(|date| !in_period(date)).count()
+        ));
+
+        if surcharges.is_empty() {
+            result.push_str(
+                "No holiday period in these months had enough sampled dates to compare.\n",
+            );
+        }
+        for surcharge in &surcharges {
+            let period = &surcharge.period;
+            let dates = format!(
+                "{} ({}–{})",
+                period.name,
+                period.start.format("%d %b"),
+                period.end.format("%d %b %Y")
+            );
+            if surcharge.share < SURCHARGE_SHARE {
+                result.push_str(&format!(
+                    "{}: {} on average over {} dates, {}.\n",
+                    dates,
+                    options.price(surcharge.average),
+                    surcharge.dates,
+                    if surcharge.share > 0.0 {
+                        format!(
+                            "{:.0}% above usual, no marked surcharge",
+                            surcharge.share * 100.0
+                        )
+                    } else {
+                        "no dearer than usual".to_string()
+                    }
+                ));
+                continue;
+            }
+
+            result.push_str(&format!(
+                "⚠ {}: {} on average over {} dates, {:.0}% above usual.",
+                dates,
+                options.price(surcharge.average),
+                surcharge.dates,
+                surcharge.share * 100.0
+            ));
+            match surcharge.shift {
+                Some((date, price)) if price < surcharge.average => result.push_str(&format!(
+                    " Leaving on {} instead costs {}, {} less than the period's average.\n",
+                    date.format("%a %d %b"),
+                    options.price(price),
+                    options.price(surcharge.average - price)
+                )),
+                _ => result.push_str(" No cheaper date found within a week either side.\n"),
+            }
+        }
+
+        result.push_str(&format!(
+            "\nPeriods checked: Christmas and Easter{}. School breaks are typical dates and vary by region and year.\n",
+            match country {
+                Some(country) => format!(", and school breaks in {}", country),
+                None => ", without school breaks; set holiday_country to include a country's".to_string(),
+            }
+        ));
+        options.push_notes(&mut result);
+        self.client.push_quota_warning(&mut result);
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "holiday_surcharges".into(),
+            description: Some(
+                "Flag holiday periods (Christmas, Easter, school breaks in holiday_country) when a route's fares rise above usual over the coming months, with a cheaper date nearby to shift to"
+                    .into(),
+            ),
+            input_schema: input::schema::<HolidaySurchargesArgs>(),
+        }
+    }
+}
@@ -0,0 +1,392 @@
+//! A small bundled calendar of national public holidays, used by the date
+//! filters, and of the holiday periods when fares tend to peak: Christmas,
+//! Easter and the main school breaks. Regional holidays are not included,
+//! and school breaks that differ by region are given at their widest.
+
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+
+enum Rule {
+    /// The same month and day every year.
+    Fixed(u32, u32),
+    /// Days after Easter Sunday (negative for before).
+    Easter(i64),
+    /// The nth weekday of a month; `-1` is the last one.
+    Nth(u32, Weekday, i32),
+}
+
+use Rule::{Easter, Fixed, Nth};
+
+const CALENDARS: &[(&str, &[Rule])] = &[
+    (
+        "GB",
+        &[
+            Fixed(1, 1),
+            Easter(-2),
+            Easter(1),
+            Nth(5, Weekday::Mon, 1),
+            Nth(5, Weekday::Mon, -1),
+            Nth(8, Weekday::Mon, -1),
+            Fixed(12, 25),
+            Fixed(12, 26),
+        ],
+    ),
+    (
+        "IE",
+        &[
+            Fixed(1, 1),
+            Fixed(3, 17),
+            Easter(1),
+            Nth(5, Weekday::Mon, 1),
+            Nth(6, Weekday::Mon, 1),
+            Nth(8, Weekday::Mon, 1),
+            Nth(10, Weekday::Mon, -1),
+            Fixed(12, 25),
+            Fixed(12, 26),
+        ],
+    ),
+    (
+        "US",
+        &[
+            Fixed(1, 1),
+            Nth(1, Weekday::Mon, 3),
+            Nth(2, Weekday::Mon, 3),
+            Nth(5, Weekday::Mon, -1),
+            Fixed(6, 19),
+            Fixed(7, 4),
+            Nth(9, Weekday::Mon, 1),
+            Nth(10, Weekday::Mon, 2),
+            Fixed(11, 11),
+            Nth(11, Weekday::Thu, 4),
+            Fixed(12, 25),
+        ],
+    ),
+    (
+        "DE",
+        &[
+            Fixed(1, 1),
+            Easter(-2),
+            Easter(1),
+            Fixed(5, 1),
+            Easter(39),
+            Easter(50),
+            Fixed(10, 3),
+            Fixed(12, 25),
+            Fixed(12, 26),
+        ],
+    ),
+    (
+        "FR",
+        &[
+            Fixed(1, 1),
+            Easter(1),
+            Fixed(5, 1),
+            Fixed(5, 8),
+            Easter(39),
+            Easter(50),
+            Fixed(7, 14),
+            Fixed(8, 15),
+            Fixed(11, 1),
+            Fixed(11, 11),
+            Fixed(12, 25),
+        ],
+    ),
+    (
+        "IT",
+        &[
+            Fixed(1, 1),
+            Fixed(1, 6),
+            Easter(1),
+            Fixed(4, 25),
+            Fixed(5, 1),
+            Fixed(6, 2),
+            Fixed(8, 15),
+            Fixed(11, 1),
+            Fixed(12, 8),
+            Fixed(12, 25),
+            Fixed(12, 26),
+        ],
+    ),
+    (
+        "ES",
+        &[
+            Fixed(1, 1),
+            Fixed(1, 6),
+            Easter(-2),
+            Fixed(5, 1),
+            Fixed(8, 15),
+            Fixed(10, 12),
+            Fixed(11, 1),
+            Fixed(12, 6),
+            Fixed(12, 8),
+            Fixed(12, 25),
+        ],
+    ),
+    (
+        "NL",
+        &[
+            Fixed(1, 1),
+            Easter(1),
+            Fixed(4, 27),
+            Easter(39),
+            Easter(50),
+            Fixed(12, 25),
+            Fixed(12, 26),
+        ],
+    ),
+    (
+        "CZ",
+        &[
+            Fixed(1, 1),
+            Easter(-2),
+            Easter(1),
+            Fixed(5, 1),
+            Fixed(5, 8),
+            Fixed(7, 5),
+            Fixed(7, 6),
+            Fixed(9, 28),
+            Fixed(10, 28),
+            Fixed(11, 17),
+            Fixed(12, 24),
+            Fixed(12, 25),
+            Fixed(12, 26),
+        ],
+    ),
+];
+
+/// A holiday period: a name, the rule for its first day, and its number of
+/// days.
+type Period = (&'static str, Rule, u64);
+
+/// Holiday periods everywhere.
+const PERIODS: &[Period] = &[
+    ("Christmas and New Year", Fixed(12, 20), 18),
+    ("Easter", Easter(-9), 18),
+];
+
+/// The summer holidays, for travel in general rather than one country.
+const SUMMER: Period = ("the summer holidays", Fixed(7, 1), 62);
+
+/// School breaks by country, besides Christmas and Easter.
+const SCHOOL_BREAKS: &[(&str, &[Period])] = &[
+    (
+        "GB",
+        &[
+            ("February half term", Nth(2, Weekday::Mon, 3), 7),
+            ("May half term", Nth(5, Weekday::Mon, -1), 7),
+            ("summer school holidays", Fixed(7, 20), 43),
+            ("October half term", Nth(10, Weekday::Mon, -1), 7),
+        ],
+    ),
+    (
+        "IE",
+        &[
+            ("February mid-term", Nth(2, Weekday::Mon, 3), 7),
+            ("summer school holidays", Fixed(7, 1), 62),
+            ("Halloween mid-term", Nth(10, Weekday::Mon, -1), 7),
+        ],
+    ),
+    (
+        "US",
+        &[
+            ("spring break", Fixed(3, 7), 24),
+            ("summer vacation", Fixed(6, 15), 78),
+            ("Thanksgiving", Nth(11, Weekday::Wed, 4), 5),
+        ],
+    ),
+    (
+        "DE",
+        &[
+            ("summer school holidays", Fixed(6, 20), 85),
+            ("autumn school holidays", Fixed(10, 1), 31),
+        ],
+    ),
+    (
+        "FR",
+        &[
+            ("February school holidays", Fixed(2, 7), 32),
+            ("summer school holidays", Fixed(7, 5), 58),
+            ("Toussaint school holidays", Fixed(10, 18), 17),
+        ],
+    ),
+    ("IT", &[("summer school holidays", Fixed(6, 10), 96)]),
+    ("ES", &[("summer school holidays", Fixed(6, 21), 77)]),
+    (
+        "NL",
+        &[
+            ("spring school holidays", Fixed(2, 14), 17),
+            ("May school holidays", Fixed(4, 26), 9),
+            ("summer school holidays", Fixed(7, 5), 65),
+            ("autumn school holidays", Fixed(10, 11), 16),
+        ],
+    ),
+    (
+        "CZ",
+        &[
+            ("spring school holidays", Fixed(2, 1), 42),
+            ("summer school holidays", Fixed(7, 1), 62),
+        ],
+    ),
+];
+
+/// A holiday period in a particular year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HolidayPeriod {
+    pub name: &'static str,
+    pub start: NaiveDate,
+    /// The last day of the period.
+    pub end: NaiveDate,
+}
+
+impl HolidayPeriod {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        (self.start..=self.end).contains(&date)
+    }
+}
+
+/// The holiday periods overlapping `from` to `to`: Christmas and Easter,
+/// and the school breaks of `country` when it has a calendar.
+pub fn holiday_periods(
+    country: Option<&str>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<HolidayPeriod> {
+    let breaks = country
+        .and_then(|country| {
+            SCHOOL_BREAKS
+                .iter()
+                .find(|(code, _)| code.eq_ignore_ascii_case(country))
+        })
+        .map_or(&[][..], |(_, breaks)| *breaks);
+
+    let mut periods: Vec<HolidayPeriod> = PERIODS
+        .iter()
+        .chain(breaks)
+        .flat_map(|period| {
+            (from.year() - 1..=to.year()).filter_map(move |year| resolve_period(period, year))
+        })
+        .filter(|period| period.start <= to && period.end >= from)
+        .collect();
+    periods.sort_by_key(|period| period.start);
+    periods
+}
+
+fn resolve_period(
+    &(name, ref rule, days): &(&'static str, Rule, u64),
+    year: i32,
+) -> Option<HolidayPeriod> {
+    let start = resolve(rule, year)?;
+    Some(HolidayPeriod {
+        name,
+        start,
+        end: start.checked_add_days(Days::new(days - 1))?,
+    })
+}
+
+/// Country codes with a bundled calendar.
+pub fn countries() -> impl Iterator<Item = &'static str> {
+    CALENDARS.iter().map(|(code, _)| *code)
+}
+
+pub fn is_supported(country: &str) -> bool {
+    countries().any(|code| code.eq_ignore_ascii_case(country))
+}
+
+/// Whether `date` is a public holiday in `country`. Unknown countries have
+/// no holidays.
+pub fn is_holiday(country: &str, date: NaiveDate) -> bool {
+    let Some((_, rules)) = CALENDARS
+        .iter()
+        .find(|(code, _)| code.eq_ignore_ascii_case(country))
+    else {
+        return false;
+    };
+
+    rules
+        .iter()
+        .any(|rule| resolve(rule, date.year()) == Some(date))
+}
+
+fn resolve(rule: &Rule, year: i32) -> Option<NaiveDate> {
+    match *rule {
+        Fixed(month, day) => NaiveDate::from_ymd_opt(year, month, day),
+        Easter(offset) => {
+            let easter = easter_sunday(year)?;
+            if offset >= 0 {
+                easter.checked_add_days(Days::new(offset as u64))
+            } else {
+                easter.checked_sub_days(Days::new(offset.unsigned_abs()))
+            }
+        }
+        Nth(month, weekday, n) if n > 0 => {
+            NaiveDate::from_weekday_of_month_opt(year, month, weekday, n as u8)
+        }
+        Nth(month, weekday, _) => {
+            // The last one is either the fifth or the fourth
+            NaiveDate::from_weekday_of_month_opt(year, month, weekday, 5)
+                .or_else(|| NaiveDate::from_weekday_of_month_opt(year, month, weekday, 4))
+        }
+    }
+}
+
+/// Gregorian Easter Sunday (anonymous Gregorian algorithm).
+fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+/// The peak travel period `date` falls in, when demand and fares are
+/// highest: Christmas and New Year, the weeks around Easter, or the summer
+/// holidays.
+pub fn peak_period(date: NaiveDate) -> Option<&'static str> {
+    PERIODS
+        .iter()
+        .chain([&SUMMER])
+        .flat_map(|period| {
+            [date.year() - 1, date.year()]
+                .into_iter()
+                .filter_map(|year| resolve_period(period, year))
+        })
+        .find(|period| period.contains(date))
+        .map(|period| period.name)
+}
+
+pub fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// Whether a stay from `arrival` to `departure` covers a long weekend in
+/// `country`: a Saturday and Sunday joined to a public holiday on the Friday
+/// before or the Monday after.
+pub fn covers_long_weekend(country: &str, arrival: NaiveDate, departure: NaiveDate) -> bool {
+    arrival
+        .iter_days()
+        .take_while(|date| *date <= departure)
+        .filter(|date| is_holiday(country, *date))
+        .any(|holiday| {
+            let weekend = match holiday.weekday() {
+                Weekday::Fri => holiday
+                    .succ_opt()
+                    .zip(holiday.checked_add_days(Days::new(2))),
+                Weekday::Mon => holiday
+                    .checked_sub_days(Days::new(2))
+                    .zip(holiday.pred_opt()),
+                _ => None,
+            };
+            weekend.is_some_and(|(first, last)| {
+                first.min(holiday) >= arrival && last.max(holiday) <= departure
+            })
+        })
+}
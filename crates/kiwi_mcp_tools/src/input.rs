@@ -0,0 +1,68 @@
+//! Tool arguments as typed structs. Each tool's input is a struct deriving
+//! `Deserialize` and `JsonSchema` and implementing [`ToolInput`]: the
+//! advertised input schema is generated from it and the arguments are
+//! deserialized into it, so the two can't drift apart. Doc comments on the
+//! fields become the parameter descriptions.
+//!
+//! Required parameters are plain fields, optional ones `Option`s, and
+//! defaults are `#[serde(default)]` attributes, which also show in the
+//! schema. Anything the types can't express goes in
+//! [`ToolInput::validate`].
+
+use anyhow::Result;
+use schemars::{JsonSchema, Schema, generate::SchemaSettings, transform::RecursiveTransform};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::error::KiwiToolError;
+
+/// A tool's arguments.
+pub trait ToolInput: DeserializeOwned + JsonSchema {
+    /// Rejects values and combinations the types allow but the tool
+    /// doesn't. Runs before the tool sees the arguments.
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// The input schema for `T`, self-contained and without the metadata MCP
+/// clients don't expect.
+pub fn schema<T: ToolInput>() -> Value {
+    let generator = SchemaSettings::draft07()
+        .with(|settings| settings.inline_subschemas = true)
+        .with_transform(RecursiveTransform(drop_null_type))
+        .into_generator();
+
+    let mut schema = generator.into_root_schema_for::<T>().to_value();
+    if let Some(schema) = schema.as_object_mut() {
+        schema.remove("$schema");
+        schema.remove("title");
+        schema.remove("description");
+    }
+    schema
+}
+
+/// Deserializes and validates a tool's arguments. Missing arguments are
+/// treated as an empty object, so tools whose parameters are all optional
+/// can be called without any.
+pub fn parse<T: ToolInput>(arguments: Option<Value>) -> Result<T> {
+    let arguments = arguments.unwrap_or_else(|| Value::Object(Map::new()));
+    let input: T = serde_json::from_value(arguments)
+        .map_err(|err| KiwiToolError::validation(format!("Invalid arguments: {}", err)))?;
+    input.validate()?;
+    Ok(input)
+}
+
+/// Optional parameters are left out rather than sent as null, so their
+/// schemas don't need to allow it.
+fn drop_null_type(schema: &mut Schema) {
+    let Some(Value::Array(types)) = schema.get_mut("type") else {
+        return;
+    };
+
+    types.retain(|ty| ty != "null");
+    if let [ty] = types.as_slice() {
+        let ty = ty.clone();
+        schema.insert("type".into(), ty);
+    }
+}
@@ -0,0 +1,196 @@
+//! Places from `/locations/query`, and suggestions for location codes the
+//! API doesn't know, ranked by how close they are to what was given.
+
+use std::f64::consts::PI;
+
+use serde_json::Value;
+
+/// An airport, city, country or other place the API can search from or to.
+#[derive(Debug, Clone)]
+pub struct Location {
+    pub code: String,
+    pub name: String,
+    /// `(longitude, latitude)`, when known.
+    pub position: Option<(f64, f64)>,
+}
+
+/// An airport, with the city and country it serves.
+#[derive(Debug, Clone)]
+pub struct Airport {
+    pub code: String,
+    pub name: String,
+    pub city: String,
+    pub city_code: String,
+    pub country: String,
+    pub country_code: String,
+    pub timezone: Option<String>,
+    /// `(longitude, latitude)`, when known.
+    pub position: Option<(f64, f64)>,
+}
+
+/// The airports in a `/locations/query` response.
+pub fn parse_airports(response: &Value) -> Vec<Airport> {
+    let text = |value: &Value, pointer: &str| {
+        value
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    response
+        .get("locations")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|location| location.get("type").and_then(Value::as_str) == Some("airport"))
+        .filter_map(|location| {
+            Some(Airport {
+                code: location.get("code")?.as_str()?.to_string(),
+                name: text(location, "/name"),
+                city: text(location, "/city/name"),
+                city_code: text(location, "/city/code"),
+                country: text(location, "/city/country/name"),
+                country_code: text(location, "/city/country/code"),
+                timezone: location
+                    .get("timezone")
+                    .and_then(Value::as_str)
+                    .map(String::from),
+                position: position(location),
+            })
+        })
+        .collect()
+}
+
+fn position(location: &Value) -> Option<(f64, f64)> {
+    let position = location.get("location")?;
+    Some((
+        position.get("lon")?.as_f64()?,
+        position.get("lat")?.as_f64()?,
+    ))
+}
+
+/// Great-circle distance in kilometres between two `(longitude, latitude)`
+/// positions.
+pub fn distance_km(a: (f64, f64), b: (f64, f64)) -> f64 {
+    const EARTH_RADIUS_KM: f64 = 6371.0;
+
+    let radians = |degrees: f64| degrees * PI / 180.0;
+    let (lon1, lat1) = (radians(a.0), radians(a.1));
+    let (lon2, lat2) = (radians(b.0), radians(b.1));
+
+    let h = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * h.sqrt().asin()
+}
+
+/// The locations in a `/locations/query` response.
+pub fn parse(response: &Value) -> Vec<Location> {
+    response
+        .get("locations")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|location| {
+            Some(Location {
+                code: location.get("code")?.as_str()?.to_string(),
+                name: location.get("name")?.as_str()?.to_string(),
+                position: position(location),
+            })
+        })
+        .collect()
+}
+
+/// The continent of the place `code` in a `/locations/query` response, e.g.
+/// `Europe`. Airports carry it on their city, cities and countries on
+/// themselves.
+pub fn continent(response: &Value, code: &str) -> Option<String> {
+    let location = find(response, code)?;
+    ["/continent/name", "/city/continent/name"]
+        .iter()
+        .find_map(|pointer| location.pointer(pointer)?.as_str())
+        .map(String::from)
+}
+
+/// The ISO code of the country the place `code` is in, in a
+/// `/locations/query` response, e.g. `GB`.
+pub fn country(response: &Value, code: &str) -> Option<String> {
+    let location = find(response, code)?;
+    if location.get("type").and_then(Value::as_str) == Some("country") {
+        return Some(code.to_ascii_uppercase());
+    }
+    ["/country/code", "/city/country/code"]
+        .iter()
+        .find_map(|pointer| location.pointer(pointer)?.as_str())
+        .map(String::from)
+}
+
+/// The place `code` in a `/locations/query` response.
+fn find<'a>(response: &'a Value, code: &str) -> Option<&'a Value> {
+    response
+        .get("locations")?
+        .as_array()?
+        .iter()
+        .find(|location| {
+            location
+                .get("code")
+                .and_then(Value::as_str)
+                .is_some_and(|found| found.eq_ignore_ascii_case(code))
+        })
+}
+
+/// Whether `code` names one of `locations`.
+pub fn contains(locations: &[Location], code: &str) -> bool {
+    locations
+        .iter()
+        .any(|location| location.code.eq_ignore_ascii_case(code))
+}
+
+/// Up to `limit` of `locations`, closest to `given` first, by edit distance
+/// to either their code or their name: `LODNON` is two edits from London.
+pub fn closest<'a>(given: &str, locations: &'a [Location], limit: usize) -> Vec<&'a Location> {
+    let given = normalize(given);
+    let mut ranked: Vec<(usize, &Location)> = locations
+        .iter()
+        .map(|location| {
+            let distance = edit_distance(&given, &normalize(&location.code))
+                .min(edit_distance(&given, &normalize(&location.name)));
+            (distance, location)
+        })
+        .collect();
+    // Stable, so the API's own order breaks ties
+    ranked.sort_by_key(|(distance, _)| *distance);
+
+    let mut closest: Vec<&Location> = Vec::new();
+    for (_, location) in ranked {
+        if closest.len() == limit {
+            break;
+        }
+        if !closest.iter().any(|known| known.code == location.code) {
+            closest.push(location);
+        }
+    }
+    closest
+}
+
+/// Upper case letters only, so `New York` compares with `NEWYORK`.
+fn normalize(text: &str) -> Vec<char> {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// Levenshtein distance.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
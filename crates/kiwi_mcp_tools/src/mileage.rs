@@ -0,0 +1,119 @@
+//! Rough frequent flyer mileage estimates. Programs earn by distance flown on
+//! their alliance's airlines, scaled by cabin; real earning also depends on
+//! the fare's booking class, which the API doesn't expose, so these are
+//! approximations only.
+
+use crate::{alliances, model::Itinerary};
+
+const KM_PER_MILE: f64 = 1.609_344;
+
+#[derive(Debug)]
+pub struct Program {
+    pub id: &'static str,
+    pub name: &'static str,
+    alliance: &'static str,
+    /// Redeemable miles earned per mile flown in M, W, C and F.
+    award: [f64; 4],
+    /// Status (tier) miles earned per mile flown in M, W, C and F.
+    status: [f64; 4],
+}
+
+const PROGRAMS: &[Program] = &[
+    Program {
+        id: "miles_and_more",
+        name: "Miles & More",
+        alliance: "star_alliance",
+        award: [0.5, 1.0, 1.5, 2.0],
+        status: [0.25, 0.5, 1.0, 1.5],
+    },
+    Program {
+        id: "mileageplus",
+        name: "United MileagePlus",
+        alliance: "star_alliance",
+        award: [0.5, 0.75, 1.5, 2.0],
+        status: [0.5, 0.75, 1.5, 2.0],
+    },
+    Program {
+        id: "aadvantage",
+        name: "American AAdvantage",
+        alliance: "oneworld",
+        award: [0.5, 1.0, 1.5, 2.0],
+        status: [0.5, 1.0, 1.5, 2.0],
+    },
+    Program {
+        id: "executive_club",
+        name: "British Airways Executive Club",
+        alliance: "oneworld",
+        award: [0.25, 1.0, 1.5, 2.0],
+        status: [0.25, 0.5, 1.0, 1.5],
+    },
+    Program {
+        id: "flying_blue",
+        name: "Flying Blue",
+        alliance: "skyteam",
+        award: [0.5, 0.75, 1.25, 1.5],
+        status: [0.25, 0.5, 1.0, 1.5],
+    },
+    Program {
+        id: "skymiles",
+        name: "Delta SkyMiles",
+        alliance: "skyteam",
+        award: [0.5, 0.75, 1.5, 2.0],
+        status: [0.5, 0.75, 1.5, 2.0],
+    },
+];
+
+pub fn find(id: &str) -> Option<&'static Program> {
+    PROGRAMS
+        .iter()
+        .find(|program| program.id.eq_ignore_ascii_case(id))
+}
+
+pub fn program_ids() -> impl Iterator<Item = &'static str> {
+    PROGRAMS.iter().map(|program| program.id)
+}
+
+/// Estimated `(award, status)` miles for the itinerary, or `None` when the
+/// API didn't report its distance.
+pub fn estimate(program: &Program, itinerary: &Itinerary) -> Option<(u64, u64)> {
+    if itinerary.distance <= 0.0 {
+        return None;
+    }
+
+    let partners = alliances::members(program.alliance).unwrap_or_default();
+    let round_trip = itinerary.route.iter().any(|leg| leg.return_leg == 1);
+    let total_miles = itinerary.distance / KM_PER_MILE * if round_trip { 2.0 } else { 1.0 };
+
+    // The API gives one distance for the whole trip; share it out by flight time
+    let flight_secs: Vec<i64> = itinerary
+        .route
+        .iter()
+        .map(|leg| match (leg.utc_departure, leg.utc_arrival) {
+            (Some(departure), Some(arrival)) => (arrival - departure).num_seconds().max(0),
+            _ => 0,
+        })
+        .collect();
+    let total_secs: i64 = flight_secs.iter().sum();
+    if total_secs == 0 {
+        return None;
+    }
+
+    let (mut award, mut status) = (0.0, 0.0);
+    for (leg, secs) in itinerary.route.iter().zip(flight_secs) {
+        if !partners.contains(&leg.airline.as_str()) {
+            continue;
+        }
+
+        let miles = total_miles * secs as f64 / total_secs as f64;
+        let cabin = match leg.fare_category.as_deref() {
+            Some("W") => 1,
+            Some("C") => 2,
+            Some("F") => 3,
+            _ => 0,
+        };
+        award += miles * program.award[cabin];
+        status += miles * program.status[cabin];
+    }
+
+    Some((award.round() as u64, status.round() as u64))
+}
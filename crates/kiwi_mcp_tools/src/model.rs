@@ -0,0 +1,225 @@
+//! Typed Tequila API responses. The structs are generated from the OpenAPI
+//! spec in `openapi/tequila.json` (see `cargo xtask codegen`); behaviour
+//! lives here.
+
+use chrono::{DateTime, NaiveDate, Timelike};
+use sha2::{Digest, Sha256};
+
+mod generated;
+
+pub use generated::*;
+
+impl Leg {
+    pub fn cabin(&self) -> Option<&'static str> {
+        self.fare_category.as_deref().map(cabin_name)
+    }
+
+    pub fn local_departure_date(&self) -> Option<NaiveDate> {
+        local_date(self.local_departure.as_deref()?)
+    }
+
+    pub fn local_arrival_date(&self) -> Option<NaiveDate> {
+        local_date(self.local_arrival.as_deref()?)
+    }
+
+    /// Local hours of departure and arrival, when known.
+    pub fn local_hours(&self) -> (Option<u32>, Option<u32>) {
+        let hour = |timestamp: &Option<String>| {
+            DateTime::parse_from_rfc3339(timestamp.as_deref()?)
+                .ok()
+                .map(|dt| dt.hour())
+        };
+        (hour(&self.local_departure), hour(&self.local_arrival))
+    }
+
+    /// Departure airport code and terminal when known, e.g. `LHR T5`.
+    pub fn departure_airport(&self) -> String {
+        with_terminal(&self.fly_from, self.terminal_from.as_deref())
+    }
+
+    /// Arrival airport code and terminal when known, e.g. `JFK T4`.
+    pub fn arrival_airport(&self) -> String {
+        with_terminal(&self.fly_to, self.terminal_to.as_deref())
+    }
+
+    /// Whether the API gave either terminal of the leg.
+    pub fn has_terminals(&self) -> bool {
+        self.terminal_from.is_some() || self.terminal_to.is_some()
+    }
+
+    /// Airline code and flight number, e.g. `FR 1234`.
+    pub fn flight(&self) -> String {
+        match self.flight_no {
+            Some(number) => format!("{} {}", self.airline, number),
+            None => self.airline.clone(),
+        }
+    }
+}
+
+/// Time on the ground between two consecutive legs of the same direction.
+#[derive(Debug, Clone)]
+pub struct Layover<'a> {
+    pub arriving: &'a Leg,
+    pub departing: &'a Leg,
+    pub duration: chrono::Duration,
+}
+
+impl Layover<'_> {
+    /// Whether the connection requires getting to a different airport.
+    pub fn changes_airport(&self) -> bool {
+        self.arriving.fly_to != self.departing.fly_from
+    }
+}
+
+impl Itinerary {
+    pub fn local_departure_date(&self) -> Option<NaiveDate> {
+        local_date(self.local_departure.as_deref()?)
+    }
+
+    pub fn local_arrival_date(&self) -> Option<NaiveDate> {
+        local_date(self.local_arrival.as_deref()?)
+    }
+
+    /// Short code the itinerary can be referred to by in follow-up requests,
+    /// e.g. `KW-7F3A91C2`, instead of its long Kiwi ID. 32 bits of a hash,
+    /// so collisions are rare; lookups refuse the ones that happen.
+    pub fn reference(&self) -> String {
+        let digest = Sha256::digest(self.identity().as_bytes());
+        format!(
+            "KW-{:02X}{:02X}{:02X}{:02X}",
+            digest[0], digest[1], digest[2], digest[3]
+        )
+    }
+
+    /// The Kiwi ID, or the flights flown when the API gave none.
+    pub fn identity(&self) -> String {
+        if self.id.is_empty() {
+            self.flights_key()
+        } else {
+            self.id.clone()
+        }
+    }
+
+    /// Identifies the flights flown, so the same itinerary can be recognised
+    /// across searches whose fares (and ids) differ.
+    pub fn flights_key(&self) -> String {
+        self.route
+            .iter()
+            .map(|leg| {
+                format!(
+                    "{}@{}",
+                    leg.flight(),
+                    leg.utc_departure.map(|d| d.timestamp()).unwrap_or_default()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// The outbound leg that departs first and, for round trips, the first
+    /// return leg.
+    pub fn journey_starts(&self) -> impl Iterator<Item = &Leg> {
+        let outbound = self.route.iter().find(|leg| leg.return_leg == 0);
+        let inbound = self.route.iter().find(|leg| leg.return_leg == 1);
+        outbound.into_iter().chain(inbound)
+    }
+
+    /// Local dates of arriving at the destination and of leaving it again,
+    /// for round trips.
+    pub fn stay(&self) -> Option<(NaiveDate, NaiveDate)> {
+        let arrival = self
+            .route
+            .iter()
+            .rev()
+            .find(|leg| leg.return_leg == 0)?
+            .local_arrival_date()?;
+        let departure = self
+            .route
+            .iter()
+            .find(|leg| leg.return_leg == 1)?
+            .local_departure_date()?;
+        Some((arrival, departure))
+    }
+
+    /// Whether the itinerary includes a return journey.
+    pub fn is_round_trip(&self) -> bool {
+        self.route.iter().any(|leg| leg.return_leg == 1)
+    }
+
+    /// Seconds spent flying in one direction (0 outbound, 1 return), or
+    /// `None` when a leg lacks its times.
+    pub fn air_seconds(&self, direction: u8) -> Option<i64> {
+        self.route
+            .iter()
+            .filter(|leg| leg.return_leg == direction)
+            .map(|leg| Some((leg.utc_arrival? - leg.utc_departure?).num_seconds()))
+            .sum()
+    }
+
+    /// Whether each direction is a single flight.
+    pub fn is_direct(&self) -> bool {
+        [0, 1].iter().all(|direction| {
+            self.route
+                .iter()
+                .filter(|leg| leg.return_leg == *direction)
+                .count()
+                <= 1
+        })
+    }
+
+    pub fn layovers(&self) -> Vec<Layover<'_>> {
+        self.route
+            .windows(2)
+            .filter(|legs| legs[0].return_leg == legs[1].return_leg)
+            .filter_map(|legs| {
+                let arrival = legs[0].utc_arrival?;
+                let departure = legs[1].utc_departure?;
+                Some(Layover {
+                    arriving: &legs[0],
+                    departing: &legs[1],
+                    duration: departure - arrival,
+                })
+            })
+            .collect()
+    }
+}
+
+fn local_date(timestamp: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|dt| dt.date_naive())
+}
+
+/// `LHR` and terminal `5` as `LHR T5`. Terminals that already carry a
+/// prefix (`T2`) are kept as they are.
+fn with_terminal(airport: &str, terminal: Option<&str>) -> String {
+    match terminal
+        .map(str::trim)
+        .filter(|terminal| !terminal.is_empty())
+    {
+        Some(terminal) if terminal.starts_with(['T', 't']) => {
+            format!("{} {}", airport, terminal.to_uppercase())
+        }
+        Some(terminal) => format!("{} T{}", airport, terminal),
+        None => airport.to_string(),
+    }
+}
+
+/// Formats an API local timestamp (`2025-06-01T10:30:00.000Z`) for display.
+pub fn format_local_time(timestamp: &str) -> String {
+    match DateTime::parse_from_rfc3339(timestamp) {
+        Ok(dt) => dt.format("%d %b %Y, %H:%M").to_string(),
+        Err(_) => timestamp.to_string(),
+    }
+}
+
+/// Readable name of a cabin class code.
+pub fn cabin_name(code: &str) -> &'static str {
+    match code {
+        "M" => "economy",
+        "W" => "economy premium",
+        "C" => "business",
+        "F" => "first class",
+        _ => "unknown cabin",
+    }
+}
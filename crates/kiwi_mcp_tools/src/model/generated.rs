@@ -0,0 +1,104 @@
+// @generated by `cargo xtask codegen` from openapi/tequila.json; edit the spec, not this file.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Typed view of the `/v2/search` response. Only the fields the tools use are
+/// modelled. Missing fields take their default, so a field the API leaves out
+/// becomes missing data, but a field of an unexpected type still fails to
+/// parse.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchResponse {
+    pub currency: Option<String>,
+    pub data: Vec<Itinerary>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Itinerary {
+    pub id: String,
+    #[serde(rename = "flyFrom")]
+    pub fly_from: String,
+    #[serde(rename = "flyTo")]
+    pub fly_to: String,
+    #[serde(rename = "cityFrom")]
+    pub city_from: String,
+    #[serde(rename = "cityTo")]
+    pub city_to: String,
+    #[serde(rename = "countryTo")]
+    pub country_to: Option<Country>,
+    pub local_departure: Option<String>,
+    pub local_arrival: Option<String>,
+    pub utc_departure: Option<DateTime<Utc>>,
+    pub utc_arrival: Option<DateTime<Utc>>,
+    pub duration: Durations,
+    /// Distance between origin and destination, in kilometres.
+    pub distance: f64,
+    /// Price for the whole party.
+    pub price: f64,
+    /// Price per passenger of each type.
+    pub fare: Option<Fare>,
+    /// Price of the first, second, ... checked bag, keyed `"1"`, `"2"`.
+    pub bags_price: Option<HashMap<String, f64>>,
+    pub airlines: Vec<String>,
+    pub route: Vec<Leg>,
+    pub deep_link: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Country {
+    pub code: String,
+    pub name: String,
+}
+
+/// Price of a single adult, child and infant.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Fare {
+    pub adults: f64,
+    pub children: f64,
+    pub infants: f64,
+}
+
+/// Journey durations, in seconds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Durations {
+    pub departure: i64,
+    #[serde(rename = "return")]
+    pub return_: i64,
+    pub total: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Leg {
+    #[serde(rename = "flyFrom")]
+    pub fly_from: String,
+    #[serde(rename = "flyTo")]
+    pub fly_to: String,
+    #[serde(rename = "cityFrom")]
+    pub city_from: String,
+    #[serde(rename = "cityTo")]
+    pub city_to: String,
+    pub local_departure: Option<String>,
+    pub local_arrival: Option<String>,
+    pub utc_departure: Option<DateTime<Utc>>,
+    pub utc_arrival: Option<DateTime<Utc>>,
+    pub airline: String,
+    pub flight_no: Option<u64>,
+    /// Terminal the leg departs from (e.g. `5`), when known.
+    pub terminal_from: Option<String>,
+    /// Terminal the leg arrives at, when known.
+    pub terminal_to: Option<String>,
+    /// Cabin the leg is booked in: `M`, `W`, `C` or `F`.
+    pub fare_category: Option<String>,
+    /// 0 for outbound legs, 1 for the return journey.
+    #[serde(rename = "return")]
+    pub return_leg: u8,
+    pub bags_recheck_required: bool,
+}
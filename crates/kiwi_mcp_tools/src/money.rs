@@ -0,0 +1,151 @@
+use anyhow::Result;
+
+use crate::error::KiwiToolError;
+
+/// How prices are written: separators, rounding and where the currency
+/// goes, following a locale such as `en-GB` (`€1,234.56`) or `de-DE`
+/// (`1.234,56 €`).
+#[derive(Debug, Clone, Copy)]
+pub struct PriceFormat {
+    thousands: &'static str,
+    decimal: char,
+    currency_first: bool,
+    decimals: usize,
+}
+
+impl Default for PriceFormat {
+    fn default() -> Self {
+        Self {
+            thousands: ",",
+            decimal: '.',
+            currency_first: true,
+            decimals: 2,
+        }
+    }
+}
+
+impl PriceFormat {
+    /// Formatting for `locale`, rounding prices to `decimals` places.
+    pub fn new(locale: &str, decimals: usize) -> Result<Self> {
+        let locale = locale.replace('_', "-").to_ascii_lowercase();
+        let language = locale.split('-').next().unwrap_or_default();
+
+        let (thousands, decimal, currency_first) = match (language, locale.as_str()) {
+            (_, "de-ch" | "fr-ch" | "it-ch") => ("'", '.', true),
+            ("en" | "ga" | "ja" | "zh" | "ko" | "he" | "th", _) => (",", '.', true),
+            ("de" | "it" | "es" | "nl" | "pt" | "da" | "id" | "tr" | "el" | "ro" | "hr", _) => {
+                (".", ',', false)
+            }
+            ("fr" | "cs" | "sk" | "pl" | "fi" | "sv" | "nb" | "no" | "hu" | "ru" | "uk", _) => {
+                ("\u{202f}", ',', false)
+            }
+            _ => {
+                return Err(
+                    KiwiToolError::config(format!("Unsupported locale {:?}", locale)).into(),
+                );
+            }
+        };
+
+        Ok(Self {
+            thousands,
+            decimal,
+            currency_first,
+            decimals,
+        })
+    }
+
+    /// The number alone, e.g. `1,234.56`.
+    pub fn amount(&self, value: f64) -> String {
+        let rounded = format!("{:.*}", self.decimals, value.abs());
+        let (whole, fraction) = match rounded.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (rounded.as_str(), None),
+        };
+
+        let mut result = String::new();
+        if value < 0.0 && rounded.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            result.push('-');
+        }
+        for (i, digit) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                result.push_str(self.thousands);
+            }
+            result.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            result.push(self.decimal);
+            result.push_str(fraction);
+        }
+        result
+    }
+
+    /// The number with an explicit sign, for differences, e.g. `+12.50`.
+    pub fn signed(&self, value: f64) -> String {
+        let amount = self.amount(value);
+        if amount.starts_with('-') {
+            amount
+        } else {
+            format!("+{}", amount)
+        }
+    }
+
+    /// The number with its currency, e.g. `€1,234.56` or `1.234,56 €`.
+    pub fn price(&self, value: f64, currency: &str) -> String {
+        let amount = self.amount(value);
+        match (symbol(currency), self.currency_first) {
+            (Some(symbol), true) => match amount.strip_prefix('-') {
+                Some(amount) => format!("-{}{}", symbol, amount),
+                None => format!("{}{}", symbol, amount),
+            },
+            (Some(symbol), false) => format!("{} {}", amount, symbol),
+            (None, true) => format!("{} {}", currency, amount),
+            (None, false) => format!("{} {}", amount, currency),
+        }
+    }
+}
+
+fn symbol(currency: &str) -> Option<&'static str> {
+    Some(match currency {
+        "EUR" => "€",
+        "GBP" => "£",
+        "USD" => "$",
+        "JPY" => "¥",
+        "INR" => "₹",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prices_follow_the_locale() {
+        let en = PriceFormat::new("en-GB", 2).unwrap();
+        assert_eq!(en.price(1234.5, "EUR"), "€1,234.50");
+        assert_eq!(en.price(-12.5, "GBP"), "-£12.50");
+        assert_eq!(en.price(99.0, "CZK"), "CZK 99.00");
+
+        let de = PriceFormat::new("de_DE", 0).unwrap();
+        assert_eq!(de.price(1234567.4, "EUR"), "1.234.567 €");
+        assert_eq!(de.price(10.0, "PLN"), "10 PLN");
+
+        let ch = PriceFormat::new("de-CH", 2).unwrap();
+        assert_eq!(ch.amount(1234.5), "1'234.50");
+    }
+
+    #[test]
+    fn signs_and_rounding() {
+        let format = PriceFormat::default();
+        assert_eq!(format.signed(12.5), "+12.50");
+        assert_eq!(format.signed(-12.5), "-12.50");
+        // Rounds to zero, so no sign
+        assert_eq!(format.amount(-0.001), "0.00");
+        assert_eq!(format.amount(999.999), "1,000.00");
+    }
+
+    #[test]
+    fn unknown_locales_are_refused() {
+        assert!(PriceFormat::new("xx-YY", 2).is_err());
+    }
+}
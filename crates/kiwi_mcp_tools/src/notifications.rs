@@ -0,0 +1,52 @@
+//! Limits on what the background refresh tells the client: nothing during
+//! the configured quiet hours, and no more than a number of notifications a
+//! day, so a price hovering around a trigger doesn't flood the client.
+//! Days and hours are in the server's local time.
+
+use chrono::{DateTime, Local, NaiveDate, Timelike};
+use parking_lot::{Mutex, RwLock};
+
+use crate::config::NotificationsConfig;
+
+/// Whether a notification may go out now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permit {
+    Send,
+    /// Held until the quiet hours end.
+    Quiet,
+    /// The day's notifications are used up.
+    Capped,
+}
+
+#[derive(Default)]
+pub struct Notifier {
+    config: RwLock<NotificationsConfig>,
+    /// The day counted and the notifications sent on it.
+    sent: Mutex<(Option<NaiveDate>, u32)>,
+}
+
+impl Notifier {
+    pub fn configure(&self, config: NotificationsConfig) {
+        *self.config.write() = config;
+    }
+
+    /// Whether a notification may be sent at `now`, counting it when it
+    /// may.
+    pub fn permit(&self, now: DateTime<Local>) -> Permit {
+        let config = self.config.read();
+        if config.is_quiet(now.hour()) {
+            return Permit::Quiet;
+        }
+
+        let today = now.date_naive();
+        let mut sent = self.sent.lock();
+        if sent.0 != Some(today) {
+            *sent = (Some(today), 0);
+        }
+        if config.max_per_day > 0 && sent.1 >= config.max_per_day {
+            return Permit::Capped;
+        }
+        sent.1 += 1;
+        Permit::Send
+    }
+}
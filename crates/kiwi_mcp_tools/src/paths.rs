@@ -0,0 +1,53 @@
+//! Where the server keeps its files. By default these follow the platform
+//! conventions: the XDG base directories on Linux, `~/Library` on macOS and
+//! the known folders on Windows. A root set with [`set_root`] (the `--home`
+//! flag or `KIWI_MCP_HOME`) puts everything under one directory instead,
+//! for sandboxes and containers.
+
+use std::path::PathBuf;
+
+use parking_lot::RwLock;
+
+/// Directory name used under each platform directory.
+const APP_DIR: &str = "kiwi-mcp";
+
+pub const CONFIG_FILE: &str = "config.toml";
+pub const HISTORY_FILE: &str = "history.jsonl";
+pub const BOOKMARKS_FILE: &str = "bookmarks.json";
+pub const REQUEST_COUNTS_FILE: &str = "request-counts.json";
+pub const SALT_FILE: &str = "encryption-salt";
+
+static ROOT: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Keeps config, data and cache under `root` rather than the platform
+/// directories; `None` restores them.
+pub fn set_root(root: Option<PathBuf>) {
+    *ROOT.write() = root;
+}
+
+/// The default configuration file.
+pub fn config_file() -> Option<PathBuf> {
+    match ROOT.read().as_ref() {
+        Some(root) => Some(root.join(CONFIG_FILE)),
+        None => dirs::config_dir().map(|dir| dir.join(APP_DIR).join(CONFIG_FILE)),
+    }
+}
+
+/// Persisted data such as the search history and saved searches.
+pub fn data_dir() -> Option<PathBuf> {
+    dir("data", dirs::data_dir)
+}
+
+/// The on-disk response cache.
+pub fn cache_dir() -> Option<PathBuf> {
+    dir("cache", dirs::cache_dir)
+}
+
+/// `subdir` of the root when one is set, otherwise the application's
+/// directory under the platform directory `platform` returns.
+fn dir(subdir: &str, platform: fn() -> Option<PathBuf>) -> Option<PathBuf> {
+    match ROOT.read().as_ref() {
+        Some(root) => Some(root.join(subdir)),
+        None => platform().map(|dir| dir.join(APP_DIR)),
+    }
+}
@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    config::SearchDefaults,
+    input::{self, ToolInput},
+    search::{SearchArgs, SearchOptions},
+    trip::{Trip, format_trips},
+};
+
+/// Fetched per direction; every outbound/return pair among them is priced.
+const CANDIDATES: u64 = 5;
+
+#[derive(Deserialize, JsonSchema)]
+struct PlanOpenJawTripArgs {
+    /// IATA code of departure location (e.g., 'LHR', 'NYC', 'UK')
+    fly_from: String,
+    /// IATA code of the outbound destination
+    fly_to: String,
+    /// IATA code of the location the return flight leaves from
+    return_origin: String,
+    /// IATA code of the return flight's destination (defaults to fly_from)
+    return_destination: Option<String>,
+    /// Departure date in format dd/mm/yyyy
+    date_from: String,
+    /// Latest departure date in format dd/mm/yyyy
+    date_to: String,
+    /// Return departure date in format dd/mm/yyyy
+    return_from: String,
+    /// Latest return departure date in format dd/mm/yyyy
+    return_to: String,
+    #[serde(flatten)]
+    search: SearchArgs,
+}
+
+impl ToolInput for PlanOpenJawTripArgs {}
+
+/// Searches an open-jaw trip (out to one city, back from another) as two
+/// one-way searches whose results are paired up.
+pub struct PlanOpenJawTripTool {
+    client: Arc<KiwiClient>,
+    defaults: SearchDefaults,
+}
+
+impl PlanOpenJawTripTool {
+    pub fn new(client: Arc<KiwiClient>, defaults: SearchDefaults) -> Self {
+        Self { client, defaults }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for PlanOpenJawTripTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing PlanOpenJawTripTool");
+        let args: PlanOpenJawTripArgs = input::parse(arguments)?;
+
+        let fly_from = args.fly_from.as_str();
+        let fly_to = args.fly_to.as_str();
+        let return_origin = args.return_origin.as_str();
+        let return_destination = args.return_destination.as_deref().unwrap_or(fly_from);
+        let date_from = args.date_from.as_str();
+        let date_to = args.date_to.as_str();
+        let return_from = args.return_from.as_str();
+        let return_to = args.return_to.as_str();
+
+        let options = SearchOptions::from_args(&args.search, &self.defaults)?;
+        let candidates = SearchOptions {
+            limit: CANDIDATES,
+            ..options.clone()
+        };
+
+        let mut outbound_params = vec![
+            ("fly_from", fly_from.to_string()),
+            ("fly_to", fly_to.to_string()),
+            ("date_from", date_from.to_string()),
+            ("date_to", date_to.to_string()),
+        ];
+        outbound_params.extend(candidates.params());
+
+        let mut return_params = vec![
+            ("fly_from", return_origin.to_string()),
+            ("fly_to", return_destination.to_string()),
+            ("date_from", return_from.to_string()),
+            ("date_to", return_to.to_string()),
+        ];
+        return_params.extend(candidates.params());
+
+        log::info!(
+            "Searching for an open-jaw trip {} → {}, {} → {}",
+            fly_from,
+            fly_to,
+            return_origin,
+            return_destination
+        );
+
+        let (mut outbound, mut inbound) = tokio::try_join!(
+            self.client.search_itineraries(&outbound_params),
+            self.client.search_itineraries(&return_params),
+        )?;
+        options.apply_filters(&mut outbound.data);
+        options.apply_filters(&mut inbound.data);
+
+        if outbound.data.is_empty() {
+            return Ok(vec![ToolContent::Text {
+                text: format!("No outbound flights found from {} to {}.", fly_from, fly_to),
+            }]);
+        }
+        if inbound.data.is_empty() {
+            return Ok(vec![ToolContent::Text {
+                text: format!(
+                    "No return flights found from {} to {}.",
+                    return_origin, return_destination
+                ),
+            }]);
+        }
+
+        // Pair every outbound flight with every return flight leaving after it lands
+        let mut trips: Vec<Trip> = outbound
+            .data
+            .iter()
+            .flat_map(|out| {
+                inbound.data.iter().map(move |back| {
+                    Trip::new()
+                        .then("Outbound", out.clone())
+                        .then("Return", back.clone())
+                })
+            })
+            .filter(Trip::is_in_order)
+            .collect();
+
+        if trips.is_empty() {
+            return Ok(vec![ToolContent::Text {
+                text: String::from(
+                    "No return flight leaves after any of the outbound flights arrive.",
+                ),
+            }]);
+        }
+
+        trips.sort_by(|a, b| a.price().total_cmp(&b.price()));
+        trips.truncate(options.limit as usize);
+
+        let mut result = format!("Found {} open-jaw trips:\n\n", trips.len());
+        result.push_str(&format_trips(&trips, &options, |number, trip| {
+            let (out, back) = (&trip.tickets[0].itinerary, &trip.tickets[1].itinerary);
+            format!(
+                "Trip {}: {} → {} … {} → {}\nTotal price: {} (outbound {} + return {})\n\n",
+                number,
+                out.city_from,
+                out.city_to,
+                back.city_from,
+                back.city_to,
+                options.price(trip.price()),
+                options.price_format.amount(out.price),
+                options.price_format.amount(back.price)
+            )
+        }));
+
+        options.push_notes(&mut result);
+        self.client.push_quota_warning(&mut result);
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "plan_open_jaw_trip".into(),
+            description: Some(
+                "Search for an open-jaw trip: fly out to one destination and return from a different one"
+                    .into(),
+            ),
+            input_schema: input::schema::<PlanOpenJawTripArgs>(),
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use context_server::{Tool, ToolContent, ToolExecutor};
+use futures::future::try_join_all;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    config::SearchDefaults,
+    format::format_flight_list,
+    input::{self, ToolInput},
+    model::SearchResponse,
+    quality::AirlineQuality,
+    search::{MAX_PASSENGERS, SearchArgs, SearchOptions, is_country},
+    self_connect,
+    store::SearchStore,
+};
+
+#[derive(Deserialize, JsonSchema)]
+struct PlanTripArgs {
+    /// IATA code of departure location (e.g., 'LHR', 'NYC', 'UK')
+    fly_from: String,
+    /// IATA code of arrival location
+    fly_to: String,
+    /// Departure date in format dd/mm/yyyy
+    date_from: String,
+    /// Latest departure date in format dd/mm/yyyy
+    date_to: String,
+    /// Return departure date in format dd/mm/yyyy (for round trips)
+    return_from: Option<String>,
+    /// Latest return departure date in format dd/mm/yyyy (for round trips)
+    return_to: Option<String>,
+    /// Show the API request as a curl command, with the API key left out, to reproduce the search elsewhere (default false)
+    #[serde(default)]
+    debug: bool,
+    /// Attach the API response as JSON in a second content block, for debugging (default false)
+    #[serde(default)]
+    include_raw: bool,
+    #[serde(flatten)]
+    search: SearchArgs,
+}
+
+impl ToolInput for PlanTripArgs {}
+
+pub struct PlanTripTool {
+    client: Arc<KiwiClient>,
+    store: Arc<SearchStore>,
+    defaults: SearchDefaults,
+    quality: Option<Arc<dyn AirlineQuality>>,
+}
+
+impl PlanTripTool {
+    pub fn new(client: Arc<KiwiClient>, store: Arc<SearchStore>, defaults: SearchDefaults) -> Self {
+        Self {
+            client,
+            store,
+            defaults,
+            quality: None,
+        }
+    }
+
+    /// Annotates results with ratings from `quality`.
+    pub fn with_airline_quality(mut self, quality: Arc<dyn AirlineQuality>) -> Self {
+        self.quality = Some(quality);
+        self
+    }
+
+    /// Searches a group too large for one booking as several, and keeps the
+    /// flights every batch can book, priced for the whole group.
+    async fn search_split(
+        &self,
+        route_params: &[(&str, String)],
+        options: SearchOptions,
+    ) -> Result<Vec<ToolContent>> {
+        let batches = options.split_group();
+
+        let responses = try_join_all(batches.iter().map(|batch| {
+            let mut params = route_params.to_vec();
+            params.extend(batch.params());
+            async move { self.client.search_itineraries(&params).await }
+        }))
+        .await?;
+
+        let (first, rest) = responses
+            .split_first()
+            .ok_or_else(|| anyhow!("No batches to search"))?;
+        let rest_prices: Vec<HashMap<String, f64>> = rest
+            .iter()
+            .map(|response| {
+                response
+                    .data
+                    .iter()
+                    .map(|itinerary| (itinerary.flights_key(), itinerary.price))
+                    .collect()
+            })
+            .collect();
+
+        let mut combined = Vec::new();
+        let mut unavailable = 0;
+        for itinerary in &first.data {
+            let key = itinerary.flights_key();
+            let prices: Option<Vec<f64>> = rest_prices
+                .iter()
+                .map(|prices| prices.get(&key).copied())
+                .collect();
+
+            match prices {
+                Some(prices) => {
+                    let mut itinerary = itinerary.clone();
+                    itinerary.price += prices.iter().sum::<f64>();
+                    // The link books only the first batch, at its own price
+                    itinerary.deep_link = None;
+                    combined.push(itinerary);
+                }
+                None => unavailable += 1,
+            }
+        }
+        options.apply_filters(&mut combined);
+
+        let sizes: Vec<String> = batches
+            .iter()
+            .map(|batch| (batch.seated() + batch.infants).to_string())
+            .collect();
+        let mut result = format!(
+            "A group of {} is more than one booking can hold, so it was searched as {} bookings of {} passengers. Prices are totals for the whole group.\n\n",
+            options.seated() + options.infants,
+            batches.len(),
+            sizes.join(" + ")
+        );
+        result.push_str(&format_flight_list(&combined, &options));
+
+        if unavailable > 0 {
+            result.push_str(&format!(
+                "\n{} more flights weren't among the results for every batch, so they couldn't be priced for the whole group. They may still have seats.\n",
+                unavailable
+            ));
+        }
+        result.push_str(&format!(
+            "\nNote: booking links are left out, as each would book only one batch. Book the batches ({} passengers) one straight after another, each with its own search; fares can change between bookings and the group may not be seated together.\n",
+            sizes.join(" + ")
+        ));
+        options.push_notes(&mut result);
+        self.client.push_quota_warning(&mut result);
+
+        if !combined.is_empty() {
+            let response = SearchResponse {
+                currency: first.currency.clone(),
+                data: combined,
+            };
+            let search_id = self.store.insert(route_params, &options.curr, response);
+            result.push_str(&search_id_note(&search_id));
+        }
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for PlanTripTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing PlanTripTool");
+        let args: PlanTripArgs = input::parse(arguments)?;
+
+        let fly_from = args.fly_from.as_str();
+        let fly_to = args.fly_to.as_str();
+        let date_from = args.date_from.as_str();
+        let date_to = args.date_to.as_str();
+
+        let return_from = args.return_from.as_deref();
+        let return_to = args.return_to.as_deref();
+        let mut options = SearchOptions::from_args(&args.search, &self.defaults)?;
+
+        // A flat list to a whole country tends to be all one city
+        if is_country(fly_to) && args.search.one_per_city.is_none() {
+            options.one_per_city = true;
+        }
+
+        let mut route_params = vec![
+            ("fly_from", fly_from.to_string()),
+            ("fly_to", fly_to.to_string()),
+            ("date_from", date_from.to_string()),
+            ("date_to", date_to.to_string()),
+        ];
+
+        // Add optional return parameters if provided
+        if let Some(return_from_val) = return_from {
+            route_params.push(("return_from", return_from_val.to_string()));
+        }
+        if let Some(return_to_val) = return_to {
+            route_params.push(("return_to", return_to_val.to_string()));
+        }
+
+        log::info!("Searching for flights from {} to {}", fly_from, fly_to);
+
+        if options.seated() + options.infants > MAX_PASSENGERS {
+            return self.search_split(&route_params, options).await;
+        }
+
+        let mut params = route_params;
+        params.extend(options.params());
+
+        // Checked like search_itineraries, but kept whole for include_raw
+        let response_body = self.client.search_checked(&params).await?;
+
+        let raw = args.include_raw.then(|| prune_raw(&response_body));
+
+        let mut response: SearchResponse = serde_json::from_value(response_body)?;
+        let found = !response.data.is_empty();
+        let hidden = options.apply_filters(&mut response.data);
+
+        // Nothing sells the whole journey, but two tickets via a hub might
+        if !found && return_from.is_none() && !self.defaults.hubs.is_empty() {
+            log::info!("No flights found, trying self-connections via hubs");
+            let connections = self_connect::search(
+                &self.client,
+                &self.defaults.hubs,
+                fly_from,
+                fly_to,
+                date_from,
+                date_to,
+                &options,
+            )
+            .await;
+
+            if !connections.is_empty() {
+                let mut result = String::from("No flights found on a single ticket.\n\n");
+                result.push_str(&self_connect::format_connections(&connections, &options));
+                options.push_notes(&mut result);
+                self.client.push_quota_warning(&mut result);
+                return Ok(vec![ToolContent::Text { text: result }]);
+            }
+        }
+
+        if let Some(quality) = &self.quality {
+            options
+                .rate_airlines(quality.as_ref(), &response.data)
+                .await;
+        }
+
+        // Format the flight results
+        let mut formatted_results = format_flight_list(&response.data, &options);
+        if hidden > 0 {
+            formatted_results.push_str(&format!(
+                "\n{} more flights were hidden by the filters.\n",
+                hidden
+            ));
+        }
+        options.push_notes(&mut formatted_results);
+        self.client.push_quota_warning(&mut formatted_results);
+
+        // Keep the results so follow-up tools and prompts can refer to them
+        if !response.data.is_empty() {
+            let search_id = self.store.insert(&params, &options.curr, response);
+            formatted_results.push_str(&search_id_note(&search_id));
+        }
+
+        if args.debug {
+            formatted_results.push_str(&format!(
+                "\nRequest: {}\n",
+                self.client.search_request(&params)?
+            ));
+        }
+
+        let mut content = vec![ToolContent::Text {
+            text: formatted_results,
+        }];
+        if let Some(raw) = raw {
+            content.push(ToolContent::Text {
+                text: serde_json::to_string_pretty(&raw)?,
+            });
+        }
+
+        Ok(content)
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "plan_trip".into(),
+            description: Some(
+                "Search for flights between destinations with flexible date options".into(),
+            ),
+            input_schema: input::schema::<PlanTripArgs>(),
+        }
+    }
+}
+
+/// The API response without booking tokens, which are long, opaque and of
+/// no use when comparing the response with the formatted results.
+fn prune_raw(response: &Value) -> Value {
+    let mut response = response.clone();
+    if let Some(data) = response.get_mut("data").and_then(Value::as_array_mut) {
+        for itinerary in data.iter_mut().filter_map(Value::as_object_mut) {
+            itinerary.remove("booking_token");
+        }
+    }
+    response
+}
+
+/// Tells the model how to refer back to stored results.
+fn search_id_note(search_id: &str) -> String {
+    format!(
+        "\nSearch ID: {} (refer to these flights by number or by their reference code in follow-up requests)\n",
+        search_id
+    )
+}
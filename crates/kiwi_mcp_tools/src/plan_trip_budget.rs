@@ -0,0 +1,210 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    config::SearchDefaults,
+    error::KiwiToolError,
+    input::{self, ToolInput},
+    model::Itinerary,
+    progress::join_with_progress,
+    search::{SearchArgs, SearchOptions},
+    trip::Trip,
+};
+
+const MAX_LEGS: usize = 8;
+
+/// Results asked for per leg, so filters still leave a fare.
+const LEG_LIMIT: u64 = 5;
+
+#[derive(Deserialize, JsonSchema)]
+struct BudgetLeg {
+    /// IATA code of departure location (e.g., 'LHR', 'NYC')
+    fly_from: String,
+    /// IATA code of arrival location
+    fly_to: String,
+    /// Departure date in format dd/mm/yyyy
+    date_from: String,
+    /// Latest departure date in format dd/mm/yyyy (defaults to date_from)
+    date_to: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct PlanTripBudgetArgs {
+    /// The trip's flights in order, each searched as a one-way flight (at most 8)
+    legs: Vec<BudgetLeg>,
+    #[serde(flatten)]
+    search: SearchArgs,
+}
+
+impl ToolInput for PlanTripBudgetArgs {
+    fn validate(&self) -> Result<()> {
+        if self.legs.is_empty() || self.legs.len() > MAX_LEGS {
+            return Err(KiwiToolError::validation(format!(
+                "legs must list between 1 and {} flights",
+                MAX_LEGS
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Prices every leg of a multi-leg plan and adds them up into a budget
+/// for the party and for each traveller.
+pub struct PlanTripBudgetTool {
+    client: Arc<KiwiClient>,
+    defaults: SearchDefaults,
+}
+
+impl PlanTripBudgetTool {
+    pub fn new(client: Arc<KiwiClient>, defaults: SearchDefaults) -> Self {
+        Self { client, defaults }
+    }
+
+    /// The cheapest flight for `leg` that passes the filters.
+    async fn cheapest(
+        &self,
+        leg: &BudgetLeg,
+        options: &SearchOptions,
+    ) -> Result<Option<Itinerary>> {
+        let mut params = vec![
+            ("fly_from", leg.fly_from.clone()),
+            ("fly_to", leg.fly_to.clone()),
+            ("date_from", leg.date_from.clone()),
+            (
+                "date_to",
+                leg.date_to.clone().unwrap_or_else(|| leg.date_from.clone()),
+            ),
+        ];
+        params.extend(options.params());
+
+        let mut response = self.client.search_itineraries(&params).await?;
+        options.apply_filters(&mut response.data);
+        Ok(response
+            .data
+            .into_iter()
+            .min_by(|a, b| a.price.total_cmp(&b.price)))
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for PlanTripBudgetTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing PlanTripBudgetTool");
+        let args: PlanTripBudgetArgs = input::parse(arguments)?;
+
+        let options = SearchOptions {
+            limit: LEG_LIMIT,
+            ..SearchOptions::from_args(&args.search, &self.defaults)?
+        };
+        let passengers = (options.seated() + options.infants).max(1) as f64;
+
+        log::info!("Pricing a trip budget of {} legs", args.legs.len());
+
+        let mut priced = 0;
+        let searches: Vec<_> = args
+            .legs
+            .iter()
+            .map(|leg| self.cheapest(leg, &options))
+            .collect();
+        let cheapest = join_with_progress(self.client.progress(), searches, |_| {
+            priced += 1;
+            Some(format!("{} legs priced", priced))
+        })
+        .await;
+
+        let mut result = format!(
+            "Budget for {} legs, {} (prices in {}):\n\n",
+            args.legs.len(),
+            if passengers > 1.0 {
+                format!("{} passengers", passengers)
+            } else {
+                "1 passenger".to_string()
+            },
+            options.curr
+        );
+        result.push_str("| Leg | Route | Date | Flights | Party | Per person |\n");
+        result.push_str("|---|---|---|---|---|---|\n");
+
+        let mut trip = Trip::new();
+        let mut missing = Vec::new();
+        for (i, (leg, cheapest)) in args.legs.iter().zip(cheapest).enumerate() {
+            let route = format!("{} → {}", leg.fly_from, leg.fly_to);
+            match cheapest {
+                Ok(Some(itinerary)) => {
+                    let date = itinerary
+                        .local_departure_date()
+                        .map(|date| date.format("%d %b").to_string())
+                        .unwrap_or_default();
+                    let flights: Vec<String> =
+                        itinerary.route.iter().map(|leg| leg.flight()).collect();
+                    result.push_str(&format!(
+                        "| {} | {} | {} | {} [{}] | {} | {} |\n",
+                        i + 1,
+                        route,
+                        date,
+                        flights.join(", "),
+                        itinerary.reference(),
+                        options.price_format.amount(itinerary.price),
+                        options.price_format.amount(itinerary.price / passengers)
+                    ));
+                    trip = trip.then(format!("Leg {}", i + 1), itinerary);
+                }
+                Ok(None) => {
+                    missing.push(i + 1);
+                    result.push_str(&format!(
+                        "| {} | {} | | no flights found | | |\n",
+                        i + 1,
+                        route
+                    ));
+                }
+                Err(err) => {
+                    log::warn!("Failed to price leg {}: {}", i + 1, err);
+                    missing.push(i + 1);
+                    result.push_str(&format!(
+                        "| {} | {} | | search failed: {} | | |\n",
+                        i + 1,
+                        route,
+                        err
+                    ));
+                }
+            }
+        }
+        result.push_str(&format!(
+            "| | **Total** | | | **{}** | **{}** |\n",
+            options.price_format.amount(trip.price()),
+            options.price_format.amount(trip.price() / passengers)
+        ));
+
+        if !missing.is_empty() {
+            let legs: Vec<String> = missing.iter().map(ToString::to_string).collect();
+            result.push_str(&format!(
+                "\nThe total leaves out leg {} with no price; try other dates or airports for it.\n",
+                legs.join(", ")
+            ));
+        }
+        result.push_str("\nEach leg is the cheapest flight found and is booked separately, so a missed connection between legs is not protected.\n");
+        options.push_notes(&mut result);
+        self.client.push_quota_warning(&mut result);
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "plan_trip_budget".into(),
+            description: Some(
+                "Price a whole multi-leg trip plan at once: searches every leg and returns a budget table with each leg's cheapest flight and the total, for the party and per person"
+                    .into(),
+            ),
+            input_schema: input::schema::<PlanTripBudgetArgs>(),
+        }
+    }
+}
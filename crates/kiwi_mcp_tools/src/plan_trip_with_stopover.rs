@@ -0,0 +1,240 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Days, NaiveDate};
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    config::SearchDefaults,
+    error::KiwiToolError,
+    input::{self, ToolInput},
+    model::Itinerary,
+    progress::join_with_progress,
+    search::{SearchArgs, SearchOptions, format_date, parse_date},
+    trip::{Trip, format_trips},
+};
+
+/// Number of first-segment flights an onward connection is searched for.
+const CANDIDATES: usize = 3;
+
+#[derive(Deserialize, JsonSchema)]
+struct PlanTripWithStopoverArgs {
+    /// IATA code of departure location (e.g., 'LHR', 'NYC', 'UK')
+    fly_from: String,
+    /// IATA code of the city to stop over in (e.g., 'LIS', 'IST')
+    stopover: String,
+    /// IATA code of the final destination
+    fly_to: String,
+    /// Departure date in format dd/mm/yyyy
+    date_from: String,
+    /// Latest departure date in format dd/mm/yyyy
+    date_to: String,
+    /// Minimum number of days to spend at the stopover (default 1)
+    #[serde(default = "default_days_min")]
+    stopover_days_min: u64,
+    /// Maximum number of days to spend at the stopover (default 2)
+    #[serde(default = "default_days_max")]
+    stopover_days_max: u64,
+    #[serde(flatten)]
+    search: SearchArgs,
+}
+
+fn default_days_min() -> u64 {
+    1
+}
+
+fn default_days_max() -> u64 {
+    2
+}
+
+impl ToolInput for PlanTripWithStopoverArgs {
+    fn validate(&self) -> Result<()> {
+        if self.stopover_days_min > self.stopover_days_max {
+            return Err(KiwiToolError::validation(
+                "stopover_days_min must not be greater than stopover_days_max",
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Finds itineraries that deliberately stop for a day or two in an
+/// intermediate city, by chaining a search to the stopover with a search
+/// onwards from it.
+pub struct PlanTripWithStopoverTool {
+    client: Arc<KiwiClient>,
+    defaults: SearchDefaults,
+}
+
+impl PlanTripWithStopoverTool {
+    pub fn new(client: Arc<KiwiClient>, defaults: SearchDefaults) -> Self {
+        Self { client, defaults }
+    }
+
+    async fn onward(
+        &self,
+        first: &Itinerary,
+        stopover: &str,
+        fly_to: &str,
+        days: (u64, u64),
+        options: &SearchOptions,
+    ) -> Result<Option<Itinerary>> {
+        let arrival = arrival_date(first)?;
+        let earliest = arrival + Days::new(days.0);
+        let latest = arrival + Days::new(days.1);
+
+        let mut params = vec![
+            ("fly_from", stopover.to_string()),
+            ("fly_to", fly_to.to_string()),
+            ("date_from", format_date(earliest)),
+            ("date_to", format_date(latest)),
+        ];
+        params.extend(
+            SearchOptions {
+                limit: CANDIDATES as u64,
+                ..options.clone()
+            }
+            .params(),
+        );
+
+        let mut response = self.client.search_itineraries(&params).await?;
+        options.apply_filters(&mut response.data);
+        Ok(response
+            .data
+            .into_iter()
+            .min_by(|a, b| a.price.total_cmp(&b.price)))
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for PlanTripWithStopoverTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing PlanTripWithStopoverTool");
+        let args: PlanTripWithStopoverArgs = input::parse(arguments)?;
+
+        let fly_from = args.fly_from.as_str();
+        let fly_to = args.fly_to.as_str();
+        let stopover = args.stopover.as_str();
+        let date_from = parse_date(args.date_from.as_str())?;
+        let date_to = parse_date(args.date_to.as_str())?;
+
+        let days_min = args.stopover_days_min;
+        let days_max = args.stopover_days_max;
+
+        let options = SearchOptions::from_args(&args.search, &self.defaults)?;
+
+        log::info!(
+            "Searching for flights from {} to {} via {}",
+            fly_from,
+            fly_to,
+            stopover
+        );
+
+        let mut params = vec![
+            ("fly_from", fly_from.to_string()),
+            ("fly_to", stopover.to_string()),
+            ("date_from", format_date(date_from)),
+            ("date_to", format_date(date_to)),
+        ];
+        params.extend(
+            SearchOptions {
+                limit: CANDIDATES as u64,
+                ..options.clone()
+            }
+            .params(),
+        );
+
+        let mut first_segments = self.client.search_itineraries(&params).await?.data;
+        options.apply_filters(&mut first_segments);
+        if first_segments.is_empty() {
+            return Ok(vec![ToolContent::Text {
+                text: format!("No flights found from {} to {}.", fly_from, stopover),
+            }]);
+        }
+
+        let onward = join_with_progress(
+            self.client.progress(),
+            first_segments
+                .iter()
+                .map(|first| self.onward(first, stopover, fly_to, (days_min, days_max), &options)),
+            |_| None,
+        )
+        .await;
+
+        let mut trips = Vec::new();
+        for (first, second) in first_segments.iter().zip(onward) {
+            match second {
+                Ok(Some(second)) => trips.push(
+                    Trip::new()
+                        .then("To stopover", first.clone())
+                        .then("Onward", second),
+                ),
+                Ok(None) => {}
+                Err(err) => log::warn!("Onward search from {} failed: {}", stopover, err),
+            }
+        }
+
+        if trips.is_empty() {
+            return Ok(vec![ToolContent::Text {
+                text: format!(
+                    "No onward flights from {} to {} found {}-{} days after arriving.",
+                    stopover, fly_to, days_min, days_max
+                ),
+            }]);
+        }
+
+        trips.sort_by(|a, b| a.price().total_cmp(&b.price()));
+        trips.truncate(options.limit as usize);
+
+        let mut result = format!(
+            "Found {} trips with a stopover in {}:\n\n",
+            trips.len(),
+            trips[0].tickets[0].itinerary.city_to
+        );
+        result.push_str(&format_trips(&trips, &options, |number, trip| {
+            format!(
+                "Trip {}: Total price {}\n\n",
+                number,
+                options.price(trip.price())
+            )
+        }));
+
+        options.push_notes(&mut result);
+        self.client.push_quota_warning(&mut result);
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "plan_trip_with_stopover".into(),
+            description: Some(
+                "Search for one-way trips with a deliberate stopover of a day or more in an intermediate city, to see an extra destination on the way"
+                    .into(),
+            ),
+            input_schema: input::schema::<PlanTripWithStopoverArgs>(),
+        }
+    }
+}
+
+/// Local calendar date the itinerary arrives on.
+fn arrival_date(itinerary: &Itinerary) -> Result<NaiveDate> {
+    if let Some(local) = itinerary.local_arrival.as_deref()
+        && let Ok(arrival) = DateTime::parse_from_rfc3339(local)
+    {
+        return Ok(arrival.date_naive());
+    }
+
+    itinerary
+        .utc_arrival
+        .map(|arrival| arrival.date_naive())
+        .ok_or_else(|| {
+            KiwiToolError::parse(format!("Flight {} has no arrival time", itinerary.id)).into()
+        })
+}
@@ -0,0 +1,278 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    config::SearchDefaults,
+    error::KiwiToolError,
+    input::{self, ToolInput},
+    model::Itinerary,
+    progress::join_with_progress,
+    search::{SearchArgs, SearchOptions, format_date},
+    self_connect::SELF_TRANSFER_MINUTES,
+    trip::{Trip, format_trips},
+};
+
+/// Results asked for per search, so filters still leave a candidate.
+const CANDIDATES: u64 = 3;
+
+/// Hubs searched, at most, while a request budget is tight.
+pub const SPARING_HUBS: usize = 2;
+
+#[derive(Deserialize, JsonSchema)]
+struct PositioningFlightsArgs {
+    /// IATA code of the traveller's home airport or city
+    fly_from: String,
+    /// IATA code of the long-haul destination
+    fly_to: String,
+    /// Long-haul departure date in format dd/mm/yyyy
+    date_from: String,
+    /// Latest long-haul departure date in format dd/mm/yyyy
+    date_to: String,
+    /// IATA codes of hubs to position to (defaults to the configured hubs)
+    hubs: Option<Vec<String>>,
+    #[serde(flatten)]
+    search: SearchArgs,
+}
+
+impl ToolInput for PositioningFlightsArgs {}
+
+/// Compares flying long-haul from the traveller's own airport with taking a
+/// cheap short flight to a hub first and the long-haul from there.
+pub struct PositioningFlightsTool {
+    client: Arc<KiwiClient>,
+    defaults: SearchDefaults,
+}
+
+impl PositioningFlightsTool {
+    pub fn new(client: Arc<KiwiClient>, defaults: SearchDefaults) -> Self {
+        Self { client, defaults }
+    }
+}
+
+/// The cheapest itinerary on `route`, after the search's filters.
+pub async fn cheapest(
+    client: &KiwiClient,
+    route: Vec<(&'static str, String)>,
+    options: &SearchOptions,
+) -> Result<Option<Itinerary>> {
+    let mut params = route;
+    params.extend(options.params());
+
+    let mut response = client.search_itineraries(&params).await?;
+    options.apply_filters(&mut response.data);
+    Ok(response
+        .data
+        .into_iter()
+        .min_by(|a, b| a.price.total_cmp(&b.price)))
+}
+
+/// The cheapest long-haul from `hub`, and the cheapest flight from
+/// `fly_from` that reaches the hub in time for it.
+pub async fn via(
+    client: &KiwiClient,
+    hub: &str,
+    fly_from: &str,
+    fly_to: &str,
+    dates: (&str, &str),
+    options: &SearchOptions,
+) -> Result<Option<Trip>> {
+    let long_haul = cheapest(
+        client,
+        vec![
+            ("fly_from", hub.to_string()),
+            ("fly_to", fly_to.to_string()),
+            ("date_from", dates.0.to_string()),
+            ("date_to", dates.1.to_string()),
+        ],
+        options,
+    )
+    .await?;
+    let Some(long_haul) = long_haul else {
+        return Ok(None);
+    };
+    let (Some(departure), Some(date)) = (long_haul.utc_departure, long_haul.local_departure_date())
+    else {
+        return Ok(None);
+    };
+
+    // Positioning the day before is common for early long-haul flights
+    let mut params = vec![
+        ("fly_from", fly_from.to_string()),
+        ("fly_to", hub.to_string()),
+        ("date_from", format_date(date.pred_opt().unwrap_or(date))),
+        ("date_to", format_date(date)),
+    ];
+    params.extend(options.params());
+    let latest = departure - chrono::Duration::minutes(SELF_TRANSFER_MINUTES);
+    let positioning = client
+        .search_itineraries(&params)
+        .await?
+        .data
+        .into_iter()
+        .filter(|flight| flight.utc_arrival.is_some_and(|arrival| arrival <= latest))
+        .min_by(|a, b| a.price.total_cmp(&b.price));
+
+    Ok(positioning.map(|positioning| {
+        Trip::new()
+            .then("Positioning", positioning)
+            .then("Long-haul", long_haul)
+    }))
+}
+
+#[async_trait]
+impl ToolExecutor for PositioningFlightsTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing PositioningFlightsTool");
+        let args: PositioningFlightsArgs = input::parse(arguments)?;
+
+        let fly_from = args.fly_from.as_str();
+        let fly_to = args.fly_to.as_str();
+        let date_from = args.date_from.as_str();
+        let date_to = args.date_to.as_str();
+        let mut hubs: Vec<String> = args
+            .hubs
+            .unwrap_or_else(|| self.defaults.hubs.clone())
+            .into_iter()
+            .filter(|hub| hub.as_str() != fly_from && hub.as_str() != fly_to)
+            .collect();
+        if hubs.is_empty() {
+            return Err(KiwiToolError::validation("No hubs to position through").into());
+        }
+        let skipped = if self.client.budget().is_tight() {
+            hubs.split_off(hubs.len().min(SPARING_HUBS))
+        } else {
+            Vec::new()
+        };
+        let options = SearchOptions {
+            limit: CANDIDATES,
+            sort: "price".into(),
+            ..SearchOptions::from_args(&args.search, &self.defaults)?
+        };
+
+        log::info!(
+            "Searching for positioning flights from {} to {}",
+            fly_from,
+            fly_to
+        );
+
+        let direct = cheapest(
+            &self.client,
+            vec![
+                ("fly_from", fly_from.to_string()),
+                ("fly_to", fly_to.to_string()),
+                ("date_from", date_from.to_string()),
+                ("date_to", date_to.to_string()),
+            ],
+            &options,
+        );
+        let search_options = &options;
+        let mut best: Option<f64> = None;
+        let positioned = join_with_progress(
+            self.client.progress(),
+            hubs.iter().map(|hub| async move {
+                let found = via(
+                    &self.client,
+                    hub,
+                    fly_from,
+                    fly_to,
+                    (date_from, date_to),
+                    search_options,
+                )
+                .await;
+                if let Err(err) = &found {
+                    log::warn!("Positioning search via {} failed: {}", hub, err);
+                }
+                found.ok().flatten()
+            }),
+            |found: &Option<Trip>| {
+                if let Some(trip) = found {
+                    let total = trip.price();
+                    best = Some(best.map_or(total, |best| best.min(total)));
+                }
+                best.map(|best| format!("cheapest via a hub so far {}", search_options.price(best)))
+            },
+        );
+        let (direct, positioned) = futures::join!(direct, positioned);
+
+        let direct = direct.unwrap_or_else(|err| {
+            log::warn!("Search from {} to {} failed: {}", fly_from, fly_to, err);
+            None
+        });
+        let mut options_found: Vec<Trip> = positioned.into_iter().flatten().collect();
+        options_found.sort_by(|a, b| a.price().total_cmp(&b.price()));
+
+        let mut result = format!(
+            "Positioning options for {} → {} (prices in {}):\n\n",
+            fly_from, fly_to, options.curr
+        );
+        match &direct {
+            Some(direct) => result.push_str(&format!(
+                "From {} directly: {}, {} ({})\n\n",
+                fly_from,
+                options.price_format.amount(direct.price),
+                options.duration(direct.duration.total),
+                direct.airlines.join(", ")
+            )),
+            None => result.push_str(&format!("From {} directly: no flights found\n\n", fly_from)),
+        }
+        if !skipped.is_empty() {
+            result.push_str(&format!(
+                "Skipped {} to save the request budget.\n\n",
+                skipped.join(", ")
+            ));
+        }
+
+        if options_found.is_empty() {
+            result.push_str("No positioning options found through the hubs.\n");
+            return Ok(vec![ToolContent::Text { text: result }]);
+        }
+
+        result.push_str(&format_trips(&options_found, &options, |number, trip| {
+            let total = trip.price();
+            let comparison = match &direct {
+                Some(direct) if total < direct.price => {
+                    format!(
+                        ", saves {}",
+                        options.price_format.amount(direct.price - total)
+                    )
+                }
+                Some(direct) => format!(
+                    ", {} more",
+                    options.price_format.amount(total - direct.price)
+                ),
+                None => String::new(),
+            };
+            format!(
+                "Option {}: via {}, total {}{}\n\n",
+                number,
+                trip.tickets[1].itinerary.city_from,
+                options.price(total),
+                comparison
+            )
+        }));
+
+        result.push_str("\nPositioning flights are separate tickets: if one is late the long-haul airline does not have to rebook you, and bags must be collected and checked in again. Count the cost of a hotel when positioning the day before.\n");
+        options.push_notes(&mut result);
+        self.client.push_quota_warning(&mut result);
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "find_positioning_flights".into(),
+            description: Some(
+                "Compare a long-haul trip from the traveller's airport with a cheap positioning flight to a hub plus the long-haul from there"
+                    .into(),
+            ),
+            input_schema: input::schema::<PositioningFlightsArgs>(),
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::{Local, NaiveDate, Utc};
+use parking_lot::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+
+use crate::{
+    KiwiClient,
+    bookmarks::SearchStatus,
+    config::{NotificationsConfig, PrefetchConfig},
+    money::PriceFormat,
+    notifications::{Notifier, Permit},
+    saved_search_digest::DIGEST_URI,
+    search::parse_date,
+};
+
+/// How far back the search history is looked at for popular routes.
+const HISTORY_WINDOW_HOURS: i64 = 24;
+
+/// Query parameters of a search.
+type Params = Vec<(String, String)>;
+
+/// Refreshes the cache for saved searches and frequently searched routes
+/// while the server is idle, so interactive searches on them are answered
+/// from the cache.
+pub struct Prefetcher {
+    client: Arc<KiwiClient>,
+    config: RwLock<PrefetchConfig>,
+    task: Mutex<Option<JoinHandle<()>>>,
+    /// The last day the digest notification was sent.
+    digest_notified: Mutex<Option<NaiveDate>>,
+    notifier: Notifier,
+    price_format: RwLock<PriceFormat>,
+    /// Whether each saved search was on target when last looked at.
+    on_target: Mutex<HashMap<String, bool>>,
+    /// Trigger notifications waiting for the quiet hours to end, by saved
+    /// search.
+    held: Mutex<BTreeMap<String, String>>,
+}
+
+impl Prefetcher {
+    pub fn new(client: Arc<KiwiClient>, config: PrefetchConfig) -> Arc<Self> {
+        Arc::new(Self {
+            client,
+            config: RwLock::new(config),
+            task: Mutex::new(None),
+            digest_notified: Mutex::new(None),
+            notifier: Notifier::default(),
+            price_format: RwLock::new(PriceFormat::default()),
+            on_target: Mutex::new(HashMap::new()),
+            held: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    pub fn set_config(&self, config: PrefetchConfig) {
+        *self.config.write() = config;
+    }
+
+    /// Sets the limits on notifications, and how prices in them are written.
+    pub fn set_notifications(&self, config: NotificationsConfig, price_format: PriceFormat) {
+        self.notifier.configure(config);
+        *self.price_format.write() = price_format;
+    }
+
+    /// Runs the prefetch loop in the background until [`Prefetcher::stop`].
+    /// Settings are re-read on every round, so reloads apply.
+    pub fn spawn(self: &Arc<Self>) {
+        let prefetcher = self.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                let interval = Duration::from_secs(prefetcher.config.read().interval_secs.max(1));
+                tokio::time::sleep(interval).await;
+                prefetcher.run_once().await;
+            }
+        });
+
+        if let Some(previous) = self.task.lock().replace(task) {
+            previous.abort();
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.task
+            .lock()
+            .as_ref()
+            .is_some_and(|task| !task.is_finished())
+    }
+
+    /// Stops the prefetch loop, cancelling any request in flight.
+    pub fn stop(&self) {
+        if let Some(task) = self.task.lock().take() {
+            task.abort();
+        }
+    }
+
+    async fn run_once(&self) {
+        let config = self.config.read().clone();
+        if !config.enabled || !self.client.cache().is_enabled() {
+            return;
+        }
+
+        for params in self.candidates(&config) {
+            // Stop as soon as someone starts searching
+            if self.client.idle_for() < Duration::from_secs(config.idle_secs) {
+                log::debug!("Prefetch paused: the server is busy");
+                return;
+            }
+
+            let params: Vec<(&str, String)> = params
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.clone()))
+                .collect();
+            match self.client.prefetch(&params).await {
+                Ok(true) => log::debug!("Prefetched {:?}", params),
+                Ok(false) => {}
+                Err(err) => log::warn!("Prefetch failed: {}", err),
+            }
+        }
+
+        if config.notify_triggers {
+            self.watch_triggers();
+        }
+        if config.notify_digest {
+            self.notify_digest();
+        }
+    }
+
+    /// Compares the refreshed saved searches with their triggers, from the
+    /// cache, and tells the client about each one that has just reached
+    /// its trigger. A search is announced again only after it has left and
+    /// reached its trigger again.
+    fn watch_triggers(&self) {
+        let today = Utc::now().date_naive();
+        let price_format = *self.price_format.read();
+        let mut held = self.held.lock();
+        {
+            let mut on_target = self.on_target.lock();
+            for saved in self.client.bookmarks().list_all() {
+                if !saved.has_trigger() || saved.status_on(today) == SearchStatus::Expired {
+                    continue;
+                }
+                let params: Vec<(&str, String)> = saved
+                    .params
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), value.clone()))
+                    .collect();
+                let Some(response) = self.client.cached_search(&params) else {
+                    continue;
+                };
+
+                let cheapest = response
+                    .data
+                    .iter()
+                    .min_by(|a, b| a.price.total_cmp(&b.price));
+                let threshold = saved.threshold(today);
+                let reached = cheapest
+                    .zip(threshold)
+                    .filter(|(itinerary, threshold)| itinerary.price <= *threshold);
+                let was_on_target = on_target
+                    .insert(saved.name.clone(), reached.is_some())
+                    .unwrap_or(false);
+
+                match reached {
+                    Some((itinerary, threshold)) if !was_on_target => {
+                        held.insert(
+                            saved.name.clone(),
+                            format!(
+                                "Saved search {:?} reached its trigger: {} to {}, at or below {}. Recheck it with recheck_saved_search.",
+                                saved.name,
+                                price_format.price(itinerary.price, &saved.currency),
+                                itinerary.city_to,
+                                price_format.price(threshold, &saved.currency)
+                            ),
+                        );
+                    }
+                    Some(_) => {}
+                    None => {
+                        held.remove(&saved.name);
+                    }
+                }
+            }
+        }
+
+        while let Some((name, message)) = held.pop_first() {
+            match self.notifier.permit(Local::now()) {
+                Permit::Send => {
+                    log::info!("{}", message);
+                    self.client.progress().message("notice", &message);
+                }
+                Permit::Quiet => {
+                    held.insert(name, message);
+                    return;
+                }
+                Permit::Capped => {
+                    log::info!(
+                        "Daily notification limit reached; not announcing {} saved searches",
+                        held.len() + 1
+                    );
+                    held.clear();
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Announces the saved searches digest once a day, now that the cache
+    /// can answer the searches it runs.
+    fn notify_digest(&self) {
+        let today = Utc::now().date_naive();
+        let mut notified = self.digest_notified.lock();
+        if *notified == Some(today) || self.client.bookmarks().list_all().is_empty() {
+            return;
+        }
+        match self.notifier.permit(Local::now()) {
+            Permit::Send => {}
+            // Tried again next round
+            Permit::Quiet => return,
+            Permit::Capped => {
+                log::info!("Daily notification limit reached; not announcing the digest");
+                *notified = Some(today);
+                return;
+            }
+        }
+
+        log::info!("Saved searches digest for {} is ready", today);
+        self.client.progress().resource_updated(DIGEST_URI);
+        *notified = Some(today);
+    }
+
+    /// Parameters of the saved searches that haven't expired, then of the
+    /// routes searched at least `min_searches` times lately, most searched
+    /// first. Searches whose dates have passed are left out.
+    fn candidates(&self, config: &PrefetchConfig) -> Vec<Params> {
+        let today = Utc::now().date_naive();
+        let mut candidates: Vec<Params> = self
+            .client
+            .bookmarks()
+            .list_all()
+            .into_iter()
+            .filter(|saved| saved.status_on(today) != SearchStatus::Expired)
+            .map(|saved| saved.params)
+            .collect();
+
+        let since = Utc::now() - chrono::Duration::hours(HISTORY_WINDOW_HOURS);
+        let mut routes: HashMap<(String, String), (usize, Params)> = HashMap::new();
+        for entry in self
+            .client
+            .history()
+            .entries()
+            .into_iter()
+            .filter(|entry| entry.searched_at >= since)
+        {
+            let (from, to) = entry.route();
+            let route = routes
+                .entry((from.to_string(), to.to_string()))
+                .or_default();
+            route.0 += 1;
+            // Entries are in order, so this ends up as the latest search
+            route.1 = entry.params.into_iter().collect();
+        }
+
+        let mut popular: Vec<(usize, Params)> = routes
+            .into_values()
+            .filter(|(count, _)| *count >= config.min_searches)
+            .collect();
+        popular.sort_by_key(|(count, _)| Reverse(*count));
+        candidates.extend(
+            popular
+                .into_iter()
+                .take(config.max_routes)
+                .map(|(_, params)| params),
+        );
+
+        candidates.retain(|params| {
+            params
+                .iter()
+                .find(|(key, _)| key == "date_from")
+                .and_then(|(_, value)| parse_date(value).ok())
+                .is_none_or(|date| date >= today)
+        });
+        candidates
+    }
+}
@@ -0,0 +1,114 @@
+use futures::{StreamExt, stream::FuturesUnordered};
+use parking_lot::RwLock;
+use serde_json::{Value, json};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Passes progress of the tool call in flight to the transport, which can
+/// forward it to the client as `notifications/progress`. Reports are
+/// dropped unless a transport is connected and the client asked for
+/// progress with a `progressToken`.
+#[derive(Default)]
+pub struct ProgressHub {
+    sender: RwLock<Option<UnboundedSender<Value>>>,
+    token: RwLock<Option<Value>>,
+}
+
+impl ProgressHub {
+    /// Sends notifications to `sender` from now on.
+    pub fn connect(&self, sender: UnboundedSender<Value>) {
+        *self.sender.write() = Some(sender);
+    }
+
+    /// Starts reporting under `token`, the request's `_meta.progressToken`.
+    pub fn begin(&self, token: Option<Value>) {
+        *self.token.write() = token;
+    }
+
+    pub fn end(&self) {
+        *self.token.write() = None;
+    }
+
+    /// Tells the client the resource at `uri` has changed, so it can read it
+    /// again. Unlike progress this needs no request in flight.
+    pub fn resource_updated(&self, uri: &str) {
+        let Some(sender) = self.sender.read().clone() else {
+            return;
+        };
+
+        let _ = sender.send(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/resources/updated",
+            "params": { "uri": uri }
+        }));
+    }
+
+    /// Sends `text` to the client as a `notifications/message` at `level`.
+    pub fn message(&self, level: &str, text: &str) {
+        let Some(sender) = self.sender.read().clone() else {
+            return;
+        };
+
+        let _ = sender.send(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/message",
+            "params": {
+                "level": level,
+                "logger": "kiwi-mcp",
+                "data": text
+            }
+        }));
+    }
+
+    pub fn report(&self, progress: usize, total: usize, message: String) {
+        let Some(token) = self.token.read().clone() else {
+            return;
+        };
+        let Some(sender) = self.sender.read().clone() else {
+            return;
+        };
+
+        let _ = sender.send(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/progress",
+            "params": {
+                "progressToken": token,
+                "progress": progress,
+                "total": total,
+                "message": message
+            }
+        }));
+    }
+}
+
+/// Runs `futures` concurrently like `join_all`, reporting each one as it
+/// completes with the message `describe` builds from its output, so
+/// interim findings reach the client before the slowest request is done.
+/// Outputs are returned in the order of `futures`.
+pub async fn join_with_progress<F, T>(
+    hub: &ProgressHub,
+    futures: impl IntoIterator<Item = F>,
+    mut describe: impl FnMut(&T) -> Option<String>,
+) -> Vec<T>
+where
+    F: Future<Output = T>,
+{
+    let mut pending: FuturesUnordered<_> = futures
+        .into_iter()
+        .enumerate()
+        .map(|(i, future)| async move { (i, future.await) })
+        .collect();
+    let total = pending.len();
+
+    let mut outputs: Vec<Option<T>> = (0..total).map(|_| None).collect();
+    let mut done = 0;
+    while let Some((i, output)) = pending.next().await {
+        done += 1;
+        let message = describe(&output)
+            .map(|message| format!("{}/{} searches done; {}", done, total, message))
+            .unwrap_or_else(|| format!("{}/{} searches done", done, total));
+        hub.report(done, total, message);
+        outputs[i] = Some(output);
+    }
+
+    outputs.into_iter().flatten().collect()
+}
@@ -0,0 +1,57 @@
+use std::{collections::HashMap, fmt, fs, path::Path};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+
+/// A source of airline punctuality or review data used to annotate results.
+/// Implement this to plug in another provider.
+#[async_trait]
+pub trait AirlineQuality: Send + Sync {
+    /// Rating for the airline with IATA code `airline`, if the source has one.
+    async fn rating(&self, airline: &str) -> Result<Option<AirlineRating>>;
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AirlineRating {
+    /// Share of flights arriving within 15 minutes of schedule.
+    pub on_time_percent: Option<f64>,
+    /// Review score out of 5.
+    pub score: Option<f64>,
+}
+
+impl fmt::Display for AirlineRating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.on_time_percent, self.score) {
+            (Some(on_time), Some(score)) => write!(f, "{:.0}% on time, {:.1}/5", on_time, score),
+            (Some(on_time), None) => write!(f, "{:.0}% on time", on_time),
+            (None, Some(score)) => write!(f, "{:.1}/5", score),
+            (None, None) => write!(f, "no data"),
+        }
+    }
+}
+
+/// Ratings read from a JSON file mapping airline codes to ratings, e.g.
+/// `{"FR": {"on_time_percent": 84.5, "score": 3.2}}`.
+pub struct FileAirlineQuality {
+    ratings: HashMap<String, AirlineRating>,
+}
+
+impl FileAirlineQuality {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read airline ratings {}", path.display()))?;
+        let ratings = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse airline ratings {}", path.display()))?;
+
+        Ok(Self { ratings })
+    }
+}
+
+#[async_trait]
+impl AirlineQuality for FileAirlineQuality {
+    async fn rating(&self, airline: &str) -> Result<Option<AirlineRating>> {
+        Ok(self.ratings.get(airline).cloned())
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use parking_lot::Mutex;
+use tokio::time::{Instant, sleep, sleep_until};
+
+/// Below this many remaining requests, requests are spread out over what is
+/// left of the quota window instead of being sent as fast as allowed.
+const LOW_QUOTA: u64 = 10;
+
+/// How often a waiting background request checks for a free slot.
+const BACKGROUND_POLL: Duration = Duration::from_millis(50);
+
+/// Which requests go first when they compete for slots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// Made for a tool call someone is waiting on.
+    Interactive,
+    /// Scheduled work such as prefetching, which can wait.
+    Background,
+}
+
+/// Spaces outbound requests evenly so no more than the configured number
+/// are sent per minute. Unlimited until a limit is set.
+///
+/// The quota the API reports in its rate-limit headers is tracked too: once
+/// it runs low, requests are spread over the rest of the window, and once
+/// it is exhausted they wait for it to reset.
+///
+/// Background requests only claim a slot once it is free and no interactive
+/// request is waiting, so scheduled jobs never delay a live search.
+#[derive(Default)]
+pub struct RateLimiter {
+    interval: Mutex<Option<Duration>>,
+    next_slot: Mutex<Option<Instant>>,
+    quota: Mutex<Option<Quota>>,
+    /// Interactive requests waiting for their slot.
+    interactive_waiting: AtomicUsize,
+}
+
+/// Request quota as last reported by the API.
+#[derive(Debug, Clone, Copy)]
+pub struct Quota {
+    pub limit: Option<u64>,
+    pub remaining: u64,
+    /// When the quota window resets, if the API said.
+    pub resets_at: Option<Instant>,
+}
+
+impl Quota {
+    pub fn is_low(&self) -> bool {
+        self.remaining <= LOW_QUOTA
+    }
+}
+
+impl RateLimiter {
+    pub fn set_requests_per_minute(&self, requests_per_minute: Option<u32>) {
+        *self.interval.lock() = requests_per_minute
+            .filter(|rpm| *rpm > 0)
+            .map(|rpm| Duration::from_secs(60) / rpm);
+    }
+
+    /// Waits until the next request slot available at `priority`.
+    pub async fn acquire(&self, priority: Priority) {
+        let interval = self.quota_interval().max(*self.interval.lock());
+        let Some(interval) = interval else {
+            return;
+        };
+
+        if priority == Priority::Background {
+            self.wait_for_idle_slot().await;
+        }
+
+        let _waiting = (priority == Priority::Interactive)
+            .then(|| WaitingGuard::new(&self.interactive_waiting));
+
+        let slot = {
+            let mut next_slot = self.next_slot.lock();
+            let now = Instant::now();
+            let slot = next_slot.map_or(now, |next| next.max(now));
+            *next_slot = Some(slot + interval);
+            slot
+        };
+
+        sleep_until(slot).await;
+    }
+
+    /// Waits until the next slot has come and no interactive request is
+    /// waiting for it.
+    async fn wait_for_idle_slot(&self) {
+        loop {
+            let now = Instant::now();
+            let next_slot = *self.next_slot.lock();
+            let busy = self.interactive_waiting.load(Ordering::SeqCst) > 0;
+
+            match next_slot {
+                Some(next) if next > now => sleep_until(next).await,
+                _ if busy => sleep(BACKGROUND_POLL).await,
+                _ => return,
+            }
+        }
+    }
+
+    /// Spacing that makes the remaining quota last until it resets.
+    fn quota_interval(&self) -> Option<Duration> {
+        let mut quota = self.quota.lock();
+        let current = (*quota)?;
+        let resets_at = current.resets_at?;
+
+        let now = Instant::now();
+        if resets_at <= now {
+            *quota = None;
+            return None;
+        }
+        if !current.is_low() {
+            return None;
+        }
+
+        let left = resets_at - now;
+        Some(match current.remaining {
+            0 => left,
+            remaining => left / remaining as u32,
+        })
+    }
+
+    /// Updates the tracked quota from a response's rate-limit headers,
+    /// looked up by lowercase name with `header`.
+    pub fn record_headers(&self, header: impl Fn(&str) -> Option<String>) {
+        let number = |name: &str| -> Option<u64> { header(name)?.trim().parse().ok() };
+
+        // A 429 may only say when to retry
+        if let Some(retry_after) = number("retry-after") {
+            *self.quota.lock() = Some(Quota {
+                limit: number("x-ratelimit-limit"),
+                remaining: 0,
+                resets_at: Some(Instant::now() + Duration::from_secs(retry_after)),
+            });
+            return;
+        }
+
+        let Some(remaining) = number("x-ratelimit-remaining") else {
+            return;
+        };
+        *self.quota.lock() = Some(Quota {
+            limit: number("x-ratelimit-limit"),
+            remaining,
+            resets_at: number("x-ratelimit-reset").map(|reset| Instant::now() + reset_in(reset)),
+        });
+    }
+
+    pub fn quota(&self) -> Option<Quota> {
+        *self.quota.lock()
+    }
+}
+
+/// Counts an interactive request as waiting until its slot has come, or
+/// the request is dropped while waiting.
+struct WaitingGuard<'a>(&'a AtomicUsize);
+
+impl<'a> WaitingGuard<'a> {
+    fn new(waiting: &'a AtomicUsize) -> Self {
+        waiting.fetch_add(1, Ordering::SeqCst);
+        Self(waiting)
+    }
+}
+
+impl Drop for WaitingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Time until a `X-RateLimit-Reset` value, which APIs give either as
+/// seconds from now or as a Unix timestamp.
+fn reset_in(reset: u64) -> Duration {
+    const TIMESTAMP_THRESHOLD: u64 = 1_000_000_000;
+    if reset < TIMESTAMP_THRESHOLD {
+        return Duration::from_secs(reset);
+    }
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    Duration::from_secs(reset.saturating_sub(now))
+}
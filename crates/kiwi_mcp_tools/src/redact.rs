@@ -0,0 +1,174 @@
+//! Keeps secrets and passenger details out of logs and error messages.
+//!
+//! Secrets are registered once they are known (the API key when the client
+//! is created or the key rotated, the HTTP auth token when the configuration
+//! is loaded) and [`redact`] replaces them wherever text leaves the server.
+
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use context_server::{Tool, ToolContent, ToolExecutor};
+use parking_lot::RwLock;
+use serde_json::Value;
+
+use crate::{diagnostics, error::KiwiToolError};
+
+const REDACTED: &str = "[redacted]";
+
+/// Request headers whose values are shown.
+const SHOWN_HEADERS: [&str; 2] = ["User-Agent", "Accept"];
+
+/// Secrets shorter than this are not registered, as replacing them would
+/// mangle unrelated text.
+const MIN_SECRET_LEN: usize = 6;
+
+static SECRETS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Adds `secret` to the values [`redact`] removes.
+pub fn register_secret(secret: &str) {
+    let secret = secret.trim();
+    if secret.len() < MIN_SECRET_LEN {
+        return;
+    }
+
+    let mut secrets = SECRETS.write();
+    if !secrets.iter().any(|known| known == secret) {
+        secrets.push(secret.to_string());
+    }
+}
+
+/// The value of the request header `name` as it may be shown: only
+/// `User-Agent` and `Accept` are, since the rest may carry credentials,
+/// e.g. for a proxy.
+pub fn header_value<'a>(name: &str, value: &'a str) -> &'a str {
+    if SHOWN_HEADERS
+        .iter()
+        .any(|shown| shown.eq_ignore_ascii_case(name))
+    {
+        value
+    } else {
+        REDACTED
+    }
+}
+
+/// `text` with registered secrets, email addresses and phone numbers
+/// replaced.
+pub fn redact(text: &str) -> String {
+    let mut text = text.to_string();
+    for secret in SECRETS.read().iter() {
+        text = text.replace(secret.as_str(), REDACTED);
+    }
+
+    text.split_inclusive(char::is_whitespace)
+        .map(|word| {
+            let token = word.trim_end();
+            if is_email(token) || is_phone_number(token) {
+                format!("{}{}", REDACTED, &word[token.len()..])
+            } else {
+                word.to_string()
+            }
+        })
+        .collect()
+}
+
+fn is_email(token: &str) -> bool {
+    let token = token.trim_matches(|c: char| !c.is_alphanumeric());
+    token
+        .split_once('@')
+        .is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.'))
+}
+
+/// International numbers such as `+447700900123`.
+fn is_phone_number(token: &str) -> bool {
+    let token = token.trim_end_matches(|c: char| !c.is_alphanumeric());
+    token.strip_prefix('+').is_some_and(|number| {
+        let digits = number.chars().filter(char::is_ascii_digit).count();
+        digits >= 8 && number.chars().all(|c| c.is_ascii_digit() || c == '-')
+    })
+}
+
+/// Wraps a tool so the errors it reports to the client are redacted.
+/// Errors the caller can act on are returned as the tool result, the rest
+/// as errors.
+pub struct RedactErrors {
+    tool: Arc<dyn ToolExecutor>,
+}
+
+impl RedactErrors {
+    pub fn new(tool: Arc<dyn ToolExecutor>) -> Self {
+        Self { tool }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for RedactErrors {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        let err = match self.tool.execute(arguments).await {
+            Ok(content) => return Ok(content),
+            Err(err) => err,
+        };
+
+        let report = diagnostics::report(&err)
+            .map(|path| format!("\n{}", diagnostics::mention(&path)))
+            .unwrap_or_default();
+
+        match KiwiToolError::find(&err).filter(|err| err.is_actionable()) {
+            Some(err) => Ok(err
+                .to_content()
+                .into_iter()
+                .map(|content| match content {
+                    ToolContent::Text { text } => ToolContent::Text {
+                        text: redact(&format!("{}{}", text, report)),
+                    },
+                    other => other,
+                })
+                .collect()),
+            None => Err(anyhow!(redact(&format!("{:#}{}", err, report)))),
+        }
+    }
+
+    fn to_tool(&self) -> Tool {
+        self.tool.to_tool()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registered_secrets_are_redacted() {
+        register_secret("  test-secret-key  ");
+        assert_eq!(
+            redact("Invalid key test-secret-key in request"),
+            "Invalid key [redacted] in request"
+        );
+    }
+
+    #[test]
+    fn short_secrets_are_not_registered() {
+        register_secret("abc");
+        assert_eq!(redact("abcdef"), "abcdef");
+    }
+
+    #[test]
+    fn emails_and_phone_numbers_are_redacted() {
+        assert_eq!(
+            redact("Contact jane.doe@example.com, or +447700900123.\nThanks"),
+            "Contact [redacted] or [redacted]\nThanks"
+        );
+        assert_eq!(redact("Flight FR1234 at 10:30"), "Flight FR1234 at 10:30");
+        assert_eq!(redact("+12 bags"), "+12 bags");
+    }
+
+    #[test]
+    fn only_harmless_header_values_are_shown() {
+        assert_eq!(header_value("user-agent", "kiwi-mcp/0.1"), "kiwi-mcp/0.1");
+        assert_eq!(
+            header_value("Accept", "application/json"),
+            "application/json"
+        );
+        assert_eq!(header_value("Proxy-Authorization", "Basic abc"), REDACTED);
+    }
+}
@@ -0,0 +1,145 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use context_server::{Resource, ResourceContent, ResourceExecutor};
+use serde_json::{Value, json};
+
+use crate::{KiwiClient, error::KiwiToolError, model::Itinerary, store::SearchStore};
+
+const URI_PREFIX: &str = "kiwi://searches/";
+const REFERENCE_URI_PREFIX: &str = "kiwi://itineraries/";
+const URI_SUFFIX: &str = "/route.geojson";
+
+/// How a route resource URI names its itinerary.
+enum RouteUri<'a> {
+    /// `kiwi://searches/{id}/{index}/route.geojson`
+    Search(&'a str, usize),
+    /// `kiwi://itineraries/{reference}/route.geojson`
+    Reference(&'a str),
+}
+
+/// Serves the legs of a stored itinerary as a GeoJSON `FeatureCollection`,
+/// one `LineString` per leg, for clients that can draw maps.
+pub struct RouteGeoJsonResource {
+    client: Arc<KiwiClient>,
+    store: Arc<SearchStore>,
+}
+
+impl RouteGeoJsonResource {
+    pub fn new(client: Arc<KiwiClient>, store: Arc<SearchStore>) -> Self {
+        Self { client, store }
+    }
+
+    async fn feature_collection(&self, itinerary: &Itinerary) -> Result<Value> {
+        let mut coordinates = HashMap::new();
+        for leg in &itinerary.route {
+            for code in [&leg.fly_from, &leg.fly_to] {
+                if !coordinates.contains_key(code) {
+                    let position = self.client.airport_coordinates(code).await?;
+                    if position.is_none() {
+                        log::warn!("No coordinates found for airport {}", code);
+                    }
+                    coordinates.insert(code.clone(), position);
+                }
+            }
+        }
+
+        let position = |code: &String| {
+            coordinates
+                .get(code)
+                .copied()
+                .flatten()
+                .map(|(lon, lat)| json!([lon, lat]))
+        };
+
+        let features: Vec<Value> = itinerary
+            .route
+            .iter()
+            .enumerate()
+            .map(|(i, leg)| {
+                let geometry = match (position(&leg.fly_from), position(&leg.fly_to)) {
+                    (Some(from), Some(to)) => json!({
+                        "type": "LineString",
+                        "coordinates": [from, to],
+                    }),
+                    _ => Value::Null,
+                };
+
+                json!({
+                    "type": "Feature",
+                    "geometry": geometry,
+                    "properties": {
+                        "leg": i + 1,
+                        "flight": leg.flight(),
+                        "from": leg.fly_from,
+                        "from_city": leg.city_from,
+                        "to": leg.fly_to,
+                        "to_city": leg.city_to,
+                        "departure": leg.local_departure,
+                        "arrival": leg.local_arrival,
+                        "return": leg.return_leg == 1,
+                    },
+                })
+            })
+            .collect();
+
+        Ok(json!({
+            "type": "FeatureCollection",
+            "features": features,
+        }))
+    }
+}
+
+fn parse_uri(uri: &str) -> Option<RouteUri<'_>> {
+    let path = uri.strip_suffix(URI_SUFFIX)?;
+    if let Some(reference) = path.strip_prefix(REFERENCE_URI_PREFIX) {
+        return (!reference.is_empty() && !reference.contains('/'))
+            .then_some(RouteUri::Reference(reference));
+    }
+
+    let (id, index) = path.strip_prefix(URI_PREFIX)?.split_once('/')?;
+    Some(RouteUri::Search(id, index.parse().ok()?))
+}
+
+#[async_trait]
+impl ResourceExecutor for RouteGeoJsonResource {
+    fn to_resource(&self) -> Resource {
+        Resource {
+            uri: format!("{}{{id}}/{{index}}{}", URI_PREFIX, URI_SUFFIX),
+            name: "Itinerary route".into(),
+            description: Some(format!(
+                "GeoJSON of a stored itinerary's legs, by search ID and 1-based result number (or as {}{{reference}}{})",
+                REFERENCE_URI_PREFIX, URI_SUFFIX
+            )),
+            mime_type: Some("application/geo+json".into()),
+        }
+    }
+
+    fn handles(&self, uri: &str) -> bool {
+        parse_uri(uri).is_some()
+    }
+
+    async fn read(&self, uri: &str) -> Result<Vec<ResourceContent>> {
+        let (search, index) = match parse_uri(uri) {
+            Some(RouteUri::Search(id, index)) => (self.store.get(id)?, index),
+            Some(RouteUri::Reference(reference)) => self.store.find_reference(reference)?,
+            None => {
+                return Err(KiwiToolError::validation(format!(
+                    "Invalid route resource URI: {}",
+                    uri
+                ))
+                .into());
+            }
+        };
+        let itinerary = search.itinerary(index)?;
+        let geojson = self.feature_collection(itinerary).await?;
+
+        Ok(vec![ResourceContent {
+            uri: uri.to_string(),
+            mime_type: Some("application/geo+json".into()),
+            text: Some(serde_json::to_string_pretty(&geojson)?),
+            blob: None,
+        }])
+    }
+}
@@ -0,0 +1,201 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{NaiveDate, Utc};
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    config::SearchDefaults,
+    error::KiwiToolError,
+    fare_history::{FareStats, MIN_SAMPLES, RouteQuery},
+    input::{self, ToolInput},
+    search::{SearchArgs, SearchOptions, format_date, month_days, parse_month},
+};
+
+/// Results asked for when searching the month, so filters still leave a
+/// fare.
+const MONTH_LIMIT: u64 = 5;
+
+#[derive(Deserialize, JsonSchema)]
+struct RouteStatsArgs {
+    /// IATA code of departure location (e.g., 'LHR', 'NYC')
+    fly_from: String,
+    /// IATA code of arrival location
+    fly_to: String,
+    /// Departure month in mm/yyyy format
+    month: String,
+    /// Nights at the destination, to look at round trips (one-way when omitted)
+    nights: Option<u64>,
+    /// Price to judge against the history; when omitted the month is searched for the current cheapest fare
+    current_price: Option<f64>,
+    #[serde(flatten)]
+    search: SearchArgs,
+}
+
+impl ToolInput for RouteStatsArgs {
+    fn validate(&self) -> Result<()> {
+        parse_month(&self.month)?;
+        if self.current_price.is_some_and(|price| price <= 0.0) {
+            return Err(KiwiToolError::validation("current_price must be positive").into());
+        }
+        Ok(())
+    }
+}
+
+/// Summarizes the fares past searches found for a route and departure
+/// month, and judges the current price against them.
+pub struct RouteStatsTool {
+    client: Arc<KiwiClient>,
+    defaults: SearchDefaults,
+}
+
+impl RouteStatsTool {
+    pub fn new(client: Arc<KiwiClient>, defaults: SearchDefaults) -> Self {
+        Self { client, defaults }
+    }
+
+    /// The cheapest fare departing in the rest of `month`, or `None` when
+    /// the month is over or has no flights.
+    async fn current_price(
+        &self,
+        args: &RouteStatsArgs,
+        month: NaiveDate,
+        options: &SearchOptions,
+    ) -> Result<Option<f64>> {
+        let Some((from, last)) = month_days(month, Utc::now().date_naive()) else {
+            return Ok(None);
+        };
+
+        let mut params = vec![
+            ("fly_from", args.fly_from.clone()),
+            ("fly_to", args.fly_to.clone()),
+            ("date_from", format_date(from)),
+            ("date_to", format_date(last)),
+        ];
+        if let Some(nights) = args.nights {
+            params.push(("flight_type", "round".to_string()));
+            params.push(("nights_in_dst_from", nights.to_string()));
+            params.push(("nights_in_dst_to", nights.to_string()));
+        }
+        params.extend(options.params());
+
+        let mut response = self.client.search_itineraries(&params).await?;
+        options.apply_filters(&mut response.data);
+        Ok(response
+            .data
+            .iter()
+            .map(|itinerary| itinerary.price)
+            .min_by(f64::total_cmp))
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for RouteStatsTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing RouteStatsTool");
+        let args: RouteStatsArgs = input::parse(arguments)?;
+
+        let month = parse_month(&args.month)?;
+        let options = SearchOptions {
+            limit: MONTH_LIMIT,
+            sort: "price".into(),
+            ..SearchOptions::from_args(&args.search, &self.defaults)?
+        };
+
+        // Read before searching, so the search doesn't judge itself
+        let fares = RouteQuery {
+            fly_from: &args.fly_from,
+            fly_to: &args.fly_to,
+            nights: args.nights,
+            options: &options,
+            month: Some(month),
+        }
+        .fares(&self.client.history().entries());
+        let stats = FareStats::new(&fares);
+
+        let current = match args.current_price {
+            Some(price) => Some(price),
+            None => self.current_price(&args, month, &options).await?,
+        };
+
+        let mut result = format!(
+            "Fares for {} → {}{} departing in {} (prices in {}):\n\n",
+            args.fly_from,
+            args.fly_to,
+            match args.nights {
+                Some(nights) => format!(", {} nights", nights),
+                None => ", one-way".to_string(),
+            },
+            month.format("%B %Y"),
+            options.curr
+        );
+
+        match (&stats, fares.first(), fares.last()) {
+            (Some(stats), Some(first), Some(last)) => {
+                result.push_str(&format!(
+                    "From {} days of searches between {} and {}:\n",
+                    stats.samples,
+                    first.searched_on.format("%d %b %Y"),
+                    last.searched_on.format("%d %b %Y")
+                ));
+                result.push_str(&format!(
+                    "Low: {}, typical: {}, high: {} (the middle half between {} and {})\n",
+                    options.price(stats.low),
+                    options.price(stats.median),
+                    options.price(stats.high),
+                    options.price(stats.lower_quartile),
+                    options.price(stats.upper_quartile)
+                ));
+            }
+            _ => result.push_str(&format!(
+                "Not enough history: this route and month were searched on {} days, and at least {} are needed. Searches made from now on are counted.\n",
+                fares.len(),
+                MIN_SAMPLES
+            )),
+        }
+
+        match (current, &stats) {
+            (Some(price), Some(stats)) => result.push_str(&format!(
+                "\n{}: {} is {}.\n",
+                if args.current_price.is_some() {
+                    "Given price"
+                } else {
+                    "Cheapest now"
+                },
+                options.price(price),
+                stats.standing(price).describe()
+            )),
+            (Some(price), None) => {
+                result.push_str(&format!("\nCheapest now: {}.\n", options.price(price)))
+            }
+            (None, _) if args.current_price.is_none() => {
+                result.push_str("\nNo fares are on sale for the rest of the month.\n")
+            }
+            (None, _) => {}
+        }
+
+        result.push_str(
+            "\nStatistics use the cheapest fare of each day's searches through this server for a single departure date, with the same cabin, passengers, stay and filters, so they depend on how often the route was searched that way.\n",
+        );
+        options.push_notes(&mut result);
+        self.client.push_quota_warning(&mut result);
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "route_stats".into(),
+            description: Some(
+                "Report the low, typical and high fares past searches found for a route and departure month, and whether the current price is a good deal by comparison"
+                    .into(),
+            ),
+            input_schema: input::schema::<RouteStatsArgs>(),
+        }
+    }
+}
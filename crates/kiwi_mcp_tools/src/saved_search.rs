@@ -0,0 +1,486 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{NaiveDate, Utc};
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    bookmarks::{SavedSearch, SearchStatus, Trigger},
+    diff::ResultDiff,
+    error::KiwiToolError,
+    input::{self, ToolInput},
+    money::PriceFormat,
+    session,
+    store::SearchStore,
+};
+
+#[derive(Deserialize, JsonSchema)]
+struct SaveSearchArgs {
+    /// Search ID shown with the results
+    search_id: String,
+    /// Name to save the search under (replaces an existing one)
+    name: String,
+    /// Price at or below which the search counts as triggered, in the search's currency
+    target_price: Option<f64>,
+    /// Trigger instead once the price has dropped by this percentage since the search was saved, e.g. 15
+    drop_percent: Option<f64>,
+    /// Trigger instead whenever the price is the lowest recorded for this search (default false)
+    #[serde(default)]
+    all_time_low: bool,
+    /// Destinations to follow together instead of the search's own, e.g. ["BCN", "LIS", "OPO"]; the target applies to each
+    destinations: Option<Vec<String>>,
+}
+
+/// Most destinations one saved search can follow.
+const MAX_DESTINATIONS: usize = 10;
+
+impl ToolInput for SaveSearchArgs {
+    fn validate(&self) -> Result<()> {
+        if self.target_price.is_some_and(|price| price <= 0.0) {
+            return Err(KiwiToolError::validation("target_price must be positive").into());
+        }
+        if self
+            .drop_percent
+            .is_some_and(|percent| percent <= 0.0 || percent >= 100.0)
+        {
+            return Err(KiwiToolError::validation("drop_percent must be between 0 and 100").into());
+        }
+        let triggers = [
+            self.target_price.is_some(),
+            self.drop_percent.is_some(),
+            self.all_time_low,
+        ];
+        if triggers.into_iter().filter(|&set| set).count() > 1 {
+            return Err(KiwiToolError::validation(
+                "Use only one of target_price, drop_percent and all_time_low",
+            )
+            .into());
+        }
+        if let Some(destinations) = &self.destinations {
+            if destinations.len() < 2 || destinations.len() > MAX_DESTINATIONS {
+                return Err(KiwiToolError::validation(format!(
+                    "destinations must list between 2 and {} locations",
+                    MAX_DESTINATIONS
+                ))
+                .into());
+            }
+            if let Some(invalid) = destinations
+                .iter()
+                .find(|code| code.is_empty() || !code.chars().all(|c| c.is_ascii_alphanumeric()))
+            {
+                return Err(KiwiToolError::validation(format!(
+                    "Invalid destination {:?}: use airport, city or country codes like LIS",
+                    invalid
+                ))
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Bookmarks a stored search under a name so it can be rechecked later.
+pub struct SaveSearchTool {
+    client: Arc<KiwiClient>,
+    store: Arc<SearchStore>,
+    price_format: PriceFormat,
+}
+
+impl SaveSearchTool {
+    pub fn new(
+        client: Arc<KiwiClient>,
+        store: Arc<SearchStore>,
+        price_format: PriceFormat,
+    ) -> Self {
+        Self {
+            client,
+            store,
+            price_format,
+        }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for SaveSearchTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing SaveSearchTool");
+        let args: SaveSearchArgs = input::parse(arguments)?;
+
+        let search_id = args.search_id.as_str();
+        let name = args.name.as_str();
+
+        let search = self.store.get(search_id)?;
+        let now = Utc::now();
+
+        let mut saved = SavedSearch {
+            name: name.to_string(),
+            saved_at: now,
+            checked_at: now,
+            params: search.params,
+            currency: search.currency,
+            itineraries: search.itineraries,
+            prices: Vec::new(),
+            target_price: args.target_price,
+            trigger: match (args.drop_percent, args.all_time_low) {
+                (Some(percent), _) => Trigger::Drop { percent },
+                (None, true) => Trigger::AllTimeLow,
+                (None, false) => Trigger::Below,
+            },
+            status: SearchStatus::Active,
+            checks_on_target: 0,
+            session: session::current(),
+        };
+        let bookmarks = self.client.bookmarks();
+
+        let mut result = format!(
+            "Saved search {} as {:?}. Use recheck_saved_search to see how its prices change.\n",
+            search_id, name
+        );
+        match &args.destinations {
+            // The stored results cover other destinations, so the first
+            // recheck sets the baseline
+            Some(destinations) => {
+                let fly_to = destinations.join(",").to_uppercase();
+                saved
+                    .params
+                    .retain(|(key, _)| key != "fly_to" && key != "one_for_city");
+                saved.params.push(("fly_to".to_string(), fly_to.clone()));
+                saved
+                    .params
+                    .push(("one_for_city".to_string(), "1".to_string()));
+                saved.itineraries.clear();
+                result.push_str(&format!(
+                    "It follows {} together, with the cheapest flight to each; recheck it to price them.\n",
+                    fly_to
+                ));
+            }
+            None => {
+                saved.observe(now.date_naive(), saved.cheapest(), bookmarks.expire_after());
+            }
+        }
+        if saved.has_trigger() && !saved.prices.is_empty() {
+            result.push_str(&describe_status(
+                &saved,
+                now.date_naive(),
+                bookmarks.expire_after(),
+                &self.price_format,
+            ));
+        }
+        bookmarks.save(saved)?;
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "save_search".into(),
+            description: Some(
+                "Bookmark the results of an earlier search under a name, to recheck later. Optionally follow several destinations together, and trigger below a target price, after a percentage drop or at the lowest price recorded".into(),
+            ),
+            input_schema: input::schema::<SaveSearchArgs>(),
+        }
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct RecheckSavedSearchArgs {
+    /// Name the search was saved under
+    name: String,
+}
+
+impl ToolInput for RecheckSavedSearchArgs {}
+
+/// Runs a saved search again and reports how its results changed.
+pub struct RecheckSavedSearchTool {
+    client: Arc<KiwiClient>,
+    price_format: PriceFormat,
+}
+
+impl RecheckSavedSearchTool {
+    pub fn new(client: Arc<KiwiClient>, price_format: PriceFormat) -> Self {
+        Self {
+            client,
+            price_format,
+        }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for RecheckSavedSearchTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing RecheckSavedSearchTool");
+        let args: RecheckSavedSearchArgs = input::parse(arguments)?;
+
+        let name = args.name.as_str();
+        let mut saved = self.client.bookmarks().get(name)?;
+
+        let params: Vec<(&str, String)> = saved
+            .params
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.clone()))
+            .collect();
+
+        log::info!("Rechecking saved search {:?}", name);
+
+        let response = self.client.search_itineraries(&params).await?;
+
+        let mut result = format!(
+            "Rechecked {:?} ({} → {}), last checked {}:\n\n",
+            name,
+            saved.param("fly_from").unwrap_or("?"),
+            saved.param("fly_to").unwrap_or("?"),
+            saved.checked_at.format("%d %b %Y, %H:%M UTC")
+        );
+        result.push_str(
+            &ResultDiff::new(&saved.itineraries, &response.data)
+                .format(&saved.currency, &self.price_format),
+        );
+
+        // The fresh results become the baseline for the next recheck
+        let bookmarks = self.client.bookmarks();
+        let today = Utc::now().date_naive();
+        saved.checked_at = Utc::now();
+        saved.itineraries = response.data;
+        saved.observe(today, saved.cheapest(), bookmarks.expire_after());
+        if saved.has_several_destinations() {
+            result.push_str("\nCheapest per destination:\n");
+            for (destination, price) in saved.cheapest_by_destination() {
+                result.push_str(&format!(
+                    "  {}: {}\n",
+                    destination,
+                    self.price_format.price(price, &saved.currency)
+                ));
+            }
+        }
+        result.push_str(&format!(
+            "\n{}",
+            describe_status(&saved, today, bookmarks.expire_after(), &self.price_format)
+        ));
+        bookmarks.save(saved)?;
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "recheck_saved_search".into(),
+            description: Some(
+                "Run a saved search again and report which options got cheaper, pricier, appeared or disappeared since it was last checked"
+                    .into(),
+            ),
+            input_schema: input::schema::<RecheckSavedSearchArgs>(),
+        }
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ListSavedSearchesArgs {
+    /// Only searches in this state
+    #[schemars(extend("enum" = ["active", "triggered", "expired"]))]
+    status: Option<String>,
+}
+
+impl ToolInput for ListSavedSearchesArgs {
+    fn validate(&self) -> Result<()> {
+        if let Some(status) = &self.status
+            && !["active", "triggered", "expired"].contains(&status.as_str())
+        {
+            return Err(KiwiToolError::validation(format!(
+                "Unknown status {:?}; use active, triggered or expired",
+                status
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Lists the saved searches with their targets and where each is in its
+/// life: active, triggered or expired.
+pub struct ListSavedSearchesTool {
+    client: Arc<KiwiClient>,
+    price_format: PriceFormat,
+}
+
+impl ListSavedSearchesTool {
+    pub fn new(client: Arc<KiwiClient>, price_format: PriceFormat) -> Self {
+        Self {
+            client,
+            price_format,
+        }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for ListSavedSearchesTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing ListSavedSearchesTool");
+        let args: ListSavedSearchesArgs = input::parse(arguments)?;
+
+        let today = Utc::now().date_naive();
+        let bookmarks = self.client.bookmarks();
+        let searches: Vec<SavedSearch> = bookmarks
+            .list()
+            .into_iter()
+            .filter(|saved| {
+                args.status
+                    .as_deref()
+                    .is_none_or(|status| saved.status_on(today).name() == status)
+            })
+            .collect();
+
+        if searches.is_empty() {
+            return Ok(vec![ToolContent::Text {
+                text: match &args.status {
+                    Some(status) => format!("No {} saved searches.", status),
+                    None => "No saved searches yet. Save one with save_search.".to_string(),
+                },
+            }]);
+        }
+
+        let mut result = format!("{} saved searches:\n\n", searches.len());
+        for saved in &searches {
+            result.push_str(&format!(
+                "{} ({} → {}, departing {}), last checked {}\n",
+                saved.name,
+                saved.param("fly_from").unwrap_or("?"),
+                saved.param("fly_to").unwrap_or("?"),
+                saved.param("date_from").unwrap_or("?"),
+                saved.checked_at.format("%d %b %Y")
+            ));
+            if let Some(cheapest) = saved.prices.last() {
+                result.push_str(&format!(
+                    "  Cheapest when last checked: {}\n",
+                    self.price_format.price(cheapest.price, &saved.currency)
+                ));
+            }
+            if saved.has_several_destinations() && !saved.itineraries.is_empty() {
+                let destinations: Vec<String> = saved
+                    .cheapest_by_destination()
+                    .into_iter()
+                    .map(|(destination, price)| {
+                        format!(
+                            "{} {}",
+                            destination,
+                            self.price_format.price(price, &saved.currency)
+                        )
+                    })
+                    .collect();
+                result.push_str(&format!("  Per destination: {}\n", destinations.join(", ")));
+            }
+            result.push_str(&format!(
+                "  {}",
+                describe_status(saved, today, bookmarks.expire_after(), &self.price_format)
+            ));
+        }
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "list_saved_searches".into(),
+            description: Some(
+                "List saved searches with their target prices and status: active, triggered (price at or below target) or expired (dates passed, or on target long enough)"
+                    .into(),
+            ),
+            input_schema: input::schema::<ListSavedSearchesArgs>(),
+        }
+    }
+}
+
+/// A line on where `saved` is in its life and why.
+fn describe_status(
+    saved: &SavedSearch,
+    today: NaiveDate,
+    expire_after: u32,
+    price_format: &PriceFormat,
+) -> String {
+    let condition = condition(saved, today, price_format);
+    let cheapest = saved
+        .prices
+        .last()
+        .map(|point| price_format.price(point.price, &saved.currency));
+
+    match (saved.status_on(today), condition) {
+        (SearchStatus::Expired, _) if saved.status != SearchStatus::Expired => {
+            "Expired: its departure dates have passed.\n".to_string()
+        }
+        (SearchStatus::Expired, Some(condition)) => format!(
+            "Expired: the price was {} for {} checks in a row.\n",
+            condition, saved.checks_on_target
+        ),
+        (SearchStatus::Expired, None) => "Expired.\n".to_string(),
+        (SearchStatus::Triggered, Some(condition)) => format!(
+            "Triggered: {} {} ({} in a row{}).\n",
+            on_target(saved, today, price_format)
+                .unwrap_or_else(|| format!("{} is", cheapest.unwrap_or_default())),
+            condition,
+            match saved.checks_on_target {
+                1 => "1 check".to_string(),
+                checks => format!("{} checks", checks),
+            },
+            if expire_after > 0 {
+                format!("; expires after {}", expire_after)
+            } else {
+                String::new()
+            }
+        ),
+        (_, Some(condition)) => format!("Active: waiting for the price to be {}.\n", condition),
+        (_, None) => "Active, with no target price.\n".to_string(),
+    }
+}
+
+/// What the price has to be to trigger `saved`, e.g. `at or below the
+/// target of €120`.
+fn condition(saved: &SavedSearch, today: NaiveDate, price_format: &PriceFormat) -> Option<String> {
+    let price = |price: f64| price_format.price(price, &saved.currency);
+    let threshold = saved.threshold(today);
+    match saved.trigger {
+        Trigger::Below => saved
+            .target_price
+            .map(|target| format!("at or below the target of {}", price(target))),
+        Trigger::Drop { percent } => Some(match (saved.prices.first(), threshold) {
+            (Some(first), Some(threshold)) => format!(
+                "{}% or more below the first price of {} ({} or less)",
+                percent,
+                price(first.price),
+                price(threshold)
+            ),
+            _ => format!("{}% or more below the first price", percent),
+        }),
+        Trigger::AllTimeLow => Some(match threshold {
+            Some(lowest) => format!("at or below the lowest price recorded, {}", price(lowest)),
+            None => "at or below the lowest price recorded".to_string(),
+        }),
+    }
+}
+
+/// The destinations of a multi-destination search that are on target, e.g.
+/// `Lisbon (LIS) at €98 and Porto (OPO) at €110 are`.
+fn on_target(saved: &SavedSearch, today: NaiveDate, price_format: &PriceFormat) -> Option<String> {
+    if !saved.has_several_destinations() {
+        return None;
+    }
+    let destinations: Vec<String> = saved
+        .destinations_on_target(today)
+        .into_iter()
+        .map(|(destination, price)| {
+            format!(
+                "{} at {}",
+                destination,
+                price_format.price(price, &saved.currency)
+            )
+        })
+        .collect();
+    match destinations.as_slice() {
+        [] => None,
+        [destination] => Some(format!("{} is", destination)),
+        [rest @ .., last] => Some(format!("{} and {} are", rest.join(", "), last)),
+    }
+}
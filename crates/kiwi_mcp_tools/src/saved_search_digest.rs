@@ -0,0 +1,232 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{NaiveDate, Utc};
+use context_server::{Resource, ResourceContent, ResourceExecutor};
+use futures::future::join_all;
+use parking_lot::Mutex;
+
+use crate::{
+    KiwiClient,
+    bookmarks::{PricePoint, SavedSearch, SearchStatus, Trigger},
+    money::PriceFormat,
+    session,
+};
+
+pub const DIGEST_URI: &str = "kiwi://saved-searches/digest";
+
+/// A day's digest and the saved searches it covers.
+type Digest = (NaiveDate, Vec<String>, String);
+
+/// What a saved search costs today.
+enum Current {
+    /// The cheapest price and the city it flies to.
+    Price(f64, String),
+    NoFlights,
+    Expired,
+    Failed,
+}
+
+/// A daily overview of every saved search: the cheapest price today and how
+/// it moved since the previous day it was checked, in one table instead of
+/// a recheck per search. Built once a day, or again when the saved searches
+/// change, and recorded in each search's price history. Each session gets
+/// the digest of its own saved searches.
+pub struct SavedSearchDigestResource {
+    client: Arc<KiwiClient>,
+    price_format: PriceFormat,
+    /// The day's digest and the saved searches it covers, by session.
+    latest: Mutex<HashMap<Option<String>, Digest>>,
+}
+
+impl SavedSearchDigestResource {
+    pub fn new(client: Arc<KiwiClient>, price_format: PriceFormat) -> Self {
+        Self {
+            client,
+            price_format,
+            latest: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn current(&self, saved: &SavedSearch, today: NaiveDate) -> Current {
+        if saved.status_on(today) == SearchStatus::Expired {
+            return Current::Expired;
+        }
+
+        let params: Vec<(&str, String)> = saved
+            .params
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.clone()))
+            .collect();
+        match self.client.search_itineraries(&params).await {
+            Ok(response) => response
+                .data
+                .iter()
+                .min_by(|a, b| a.price.total_cmp(&b.price))
+                .map_or(Current::NoFlights, |itinerary| {
+                    Current::Price(itinerary.price, itinerary.city_to.clone())
+                }),
+            Err(err) => {
+                log::warn!("Digest search for {:?} failed: {}", saved.name, err);
+                Current::Failed
+            }
+        }
+    }
+
+    async fn build(&self, saved: Vec<SavedSearch>, today: NaiveDate) -> Result<String> {
+        let mut result = format!("# Saved searches, {}\n\n", today.format("%d %b %Y"));
+        if saved.is_empty() {
+            result.push_str(
+                "No saved searches yet. Save one with save_search to follow its price here.\n",
+            );
+            return Ok(result);
+        }
+
+        let current = join_all(saved.iter().map(|saved| self.current(saved, today))).await;
+
+        let (mut cheaper, mut pricier) = (0, 0);
+        let mut rows = Vec::new();
+        for (saved, current) in saved.iter().zip(&current) {
+            let previous = saved.price_before(today);
+            let (price, change) = match current {
+                Current::Price(price, city) => {
+                    match previous {
+                        Some(previous) if *price < previous.price => cheaper += 1,
+                        Some(previous) if *price > previous.price => pricier += 1,
+                        _ => {}
+                    }
+                    let mut cheapest = self.price_format.price(*price, &saved.currency);
+                    if saved.has_several_destinations() {
+                        cheapest.push_str(&format!(" to {}", city));
+                    }
+                    (cheapest, self.change(*price, previous))
+                }
+                Current::NoFlights => ("no flights".to_string(), String::new()),
+                Current::Expired => ("expired".to_string(), String::new()),
+                Current::Failed => ("search failed".to_string(), String::new()),
+            };
+            let status = match current {
+                Current::Price(price, _) => self
+                    .client
+                    .bookmarks()
+                    .observe(&saved.name, today, Some(*price))?
+                    .map(|saved| saved.status_on(today))
+                    .unwrap_or_default(),
+                _ => saved.status_on(today),
+            };
+            rows.push(format!(
+                "| {} | {} → {} | {} | {} | {} | {} |\n",
+                saved.name,
+                saved.param("fly_from").unwrap_or("?"),
+                saved.param("fly_to").unwrap_or("?"),
+                dates(saved),
+                price,
+                change,
+                self.target(saved, status)
+            ));
+        }
+
+        result.push_str(&format!(
+            "{} saved searches: {} cheaper and {} pricier than when last checked.\n\n",
+            saved.len(),
+            cheaper,
+            pricier
+        ));
+        result.push_str("| Saved search | Route | Departing | Cheapest now | Change | Target |\n");
+        result.push_str("|---|---|---|---|---|---|\n");
+        for row in rows {
+            result.push_str(&row);
+        }
+        result.push_str(
+            "\nUse recheck_saved_search for the options behind a price and how they changed.\n",
+        );
+        self.client.push_quota_warning(&mut result);
+
+        Ok(result)
+    }
+
+    fn change(&self, price: f64, previous: Option<PricePoint>) -> String {
+        let Some(previous) = previous else {
+            return "first check".to_string();
+        };
+        let since = previous.date.format("%d %b");
+        if price == previous.price {
+            format!("no change since {}", since)
+        } else {
+            format!(
+                "{} since {}",
+                self.price_format.signed(price - previous.price),
+                since
+            )
+        }
+    }
+
+    /// The trigger and the search's status, e.g. `€120 (triggered)`.
+    fn target(&self, saved: &SavedSearch, status: SearchStatus) -> String {
+        let trigger = match saved.trigger {
+            Trigger::Below => match saved.target_price {
+                Some(target) => self.price_format.price(target, &saved.currency),
+                None => return String::new(),
+            },
+            Trigger::Drop { percent } => format!("−{}%", percent),
+            Trigger::AllTimeLow => "lowest".to_string(),
+        };
+        format!("{} ({})", trigger, status.name())
+    }
+}
+
+#[async_trait]
+impl ResourceExecutor for SavedSearchDigestResource {
+    fn to_resource(&self) -> Resource {
+        Resource {
+            uri: DIGEST_URI.into(),
+            name: "Saved searches digest".into(),
+            description: Some(
+                "Today's cheapest price for every saved search, with the change since it was last checked"
+                    .into(),
+            ),
+            mime_type: Some("text/markdown".into()),
+        }
+    }
+
+    async fn read(&self, uri: &str) -> Result<Vec<ResourceContent>> {
+        let today = Utc::now().date_naive();
+        let saved = self.client.bookmarks().list();
+        let names: Vec<String> = saved.iter().map(|saved| saved.name.clone()).collect();
+        let session = session::current();
+
+        let cached = self
+            .latest
+            .lock()
+            .get(&session)
+            .filter(|(date, covers, _)| *date == today && *covers == names)
+            .map(|(_, _, digest)| digest.clone());
+        let digest = match cached {
+            Some(digest) => digest,
+            None => {
+                let digest = self.build(saved, today).await?;
+                let mut latest = self.latest.lock();
+                latest.retain(|session, _| session::is_kept(session.as_deref()));
+                latest.insert(session, (today, names, digest.clone()));
+                digest
+            }
+        };
+
+        Ok(vec![ResourceContent {
+            uri: uri.to_string(),
+            mime_type: Some("text/markdown".into()),
+            text: Some(digest),
+            blob: None,
+        }])
+    }
+}
+
+/// The saved search's departure window, e.g. `01/07/2026–15/07/2026`.
+fn dates(saved: &SavedSearch) -> String {
+    match (saved.param("date_from"), saved.param("date_to")) {
+        (Some(from), Some(to)) if from != to => format!("{}–{}", from, to),
+        (Some(from), _) => from.to_string(),
+        _ => "?".to_string(),
+    }
+}
@@ -0,0 +1,450 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Days, NaiveDate, Utc};
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    config::SearchDefaults,
+    error::KiwiToolError,
+    input::{self, ToolInput},
+    locations,
+    model::Itinerary,
+    progress::join_with_progress,
+    search::{SearchArgs, SearchOptions, format_date, parse_date},
+};
+
+/// Days either side of the requested date searched.
+const SHIFT_DAYS: u64 = 2;
+
+/// How far from the requested airports others count as alternatives.
+const RADIUS_KM: u64 = 100;
+
+/// Alternative airports searched on each side, closest first.
+const MAX_ALTERNATES: usize = 4;
+
+/// Results asked for per search, so filters still leave a candidate.
+const CANDIDATES: u64 = 5;
+
+#[derive(Deserialize, JsonSchema)]
+struct SavingsFinderArgs {
+    /// IATA code of departure location (e.g., 'LHR', 'NYC')
+    fly_from: String,
+    /// IATA code of arrival location
+    fly_to: String,
+    /// Departure date in dd/mm/yyyy format
+    date: String,
+    /// Nights at the destination, for a round trip (one-way when omitted)
+    nights: Option<u64>,
+    #[serde(flatten)]
+    search: SearchArgs,
+}
+
+impl ToolInput for SavingsFinderArgs {
+    fn validate(&self) -> Result<()> {
+        let date = parse_date(&self.date)?;
+        if date < Utc::now().date_naive() {
+            return Err(KiwiToolError::validation("date must not be in the past").into());
+        }
+        Ok(())
+    }
+}
+
+/// One way of loosening the search: the dates, the airports or both.
+struct Variant {
+    label: &'static str,
+    shift_dates: bool,
+    other_airports: bool,
+}
+
+const VARIANTS: &[Variant] = &[
+    Variant {
+        label: "As asked",
+        shift_dates: false,
+        other_airports: false,
+    },
+    Variant {
+        label: "Other dates, same airports",
+        shift_dates: true,
+        other_airports: false,
+    },
+    Variant {
+        label: "Nearby airports, same date",
+        shift_dates: false,
+        other_airports: true,
+    },
+    Variant {
+        label: "Other dates and nearby airports",
+        shift_dates: true,
+        other_airports: true,
+    },
+];
+
+/// Airports near one end of the trip, with their distance from the
+/// requested one in kilometres.
+type Alternates = Vec<(String, f64)>;
+
+/// Loosens a search by a couple of days and to nearby airports, and reports
+/// the cheapest combination with what it costs in dates, distance and
+/// travel time.
+pub struct SavingsFinderTool {
+    client: Arc<KiwiClient>,
+    defaults: SearchDefaults,
+}
+
+impl SavingsFinderTool {
+    pub fn new(client: Arc<KiwiClient>, defaults: SearchDefaults) -> Self {
+        Self { client, defaults }
+    }
+
+    /// Airports within [`RADIUS_KM`] of `code` that it doesn't already
+    /// cover, closest first. Lookup failures are only logged.
+    async fn alternates(&self, code: &str) -> Alternates {
+        let position = match self.client.locations_query(code, 5).await {
+            Ok(response) => locations::parse(&response)
+                .into_iter()
+                .find(|location| location.code.eq_ignore_ascii_case(code))
+                .and_then(|location| location.position),
+            Err(err) => {
+                log::warn!("Failed to look up location {}: {}", code, err);
+                None
+            }
+        };
+        let Some(position) = position else {
+            return Vec::new();
+        };
+
+        let airports = match self
+            .client
+            .nearby_airports(position, RADIUS_KM, MAX_ALTERNATES as u64 + 5)
+            .await
+        {
+            Ok(airports) => airports,
+            Err(err) => {
+                log::warn!("Failed to look up airports near {}: {}", code, err);
+                return Vec::new();
+            }
+        };
+
+        let mut alternates: Alternates = airports
+            .into_iter()
+            // A city code already covers its own airports
+            .filter(|airport| {
+                !airport.code.eq_ignore_ascii_case(code)
+                    && !airport.city_code.eq_ignore_ascii_case(code)
+            })
+            .filter_map(|airport| {
+                let distance = locations::distance_km(position, airport.position?);
+                (distance <= RADIUS_KM as f64).then_some((airport.code, distance))
+            })
+            .collect();
+        alternates.sort_by(|a, b| a.1.total_cmp(&b.1));
+        alternates.truncate(MAX_ALTERNATES);
+        alternates
+    }
+
+    async fn cheapest(
+        &self,
+        route: Vec<(&'static str, String)>,
+        options: &SearchOptions,
+    ) -> Result<Option<Itinerary>> {
+        let mut params = route;
+        params.extend(options.params());
+
+        let mut response = self.client.search_itineraries(&params).await?;
+        options.apply_filters(&mut response.data);
+        Ok(response
+            .data
+            .into_iter()
+            .min_by(|a, b| a.price.total_cmp(&b.price)))
+    }
+}
+
+/// `code` followed by its alternates, as a comma-separated location list.
+fn with_alternates(code: &str, alternates: &Alternates) -> String {
+    std::iter::once(code)
+        .chain(alternates.iter().map(|(code, _)| code.as_str()))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// What `found` gives up against the search as asked: a different date, a
+/// different airport and a longer journey.
+fn trade_offs(
+    found: &Itinerary,
+    date: NaiveDate,
+    asked: Option<&Itinerary>,
+    (from, to): (&Alternates, &Alternates),
+    args: &SavingsFinderArgs,
+    options: &SearchOptions,
+) -> Vec<String> {
+    let mut trade_offs = Vec::new();
+
+    if let Some(departs) = found.local_departure_date() {
+        let days = (departs - date).num_days();
+        if days != 0 {
+            trade_offs.push(format!(
+                "leaves {}, {} day{} {}",
+                departs.format("%a %d %b"),
+                days.abs(),
+                if days.abs() == 1 { "" } else { "s" },
+                if days < 0 { "earlier" } else { "later" }
+            ));
+        }
+    }
+
+    let distance = |alternates: &Alternates, code: &str| {
+        alternates
+            .iter()
+            .find(|(alternate, _)| alternate == code)
+            .map(|(_, distance)| *distance)
+    };
+    if let Some(km) = distance(from, &found.fly_from) {
+        trade_offs.push(format!(
+            "departs from {} ({}), about {:.0} km from {}",
+            found.fly_from, found.city_from, km, args.fly_from
+        ));
+    }
+    if let Some(km) = distance(to, &found.fly_to) {
+        trade_offs.push(format!(
+            "lands at {} ({}), about {:.0} km from {}",
+            found.fly_to, found.city_to, km, args.fly_to
+        ));
+    }
+
+    if let Some(asked) = asked {
+        let longer = found.duration.total - asked.duration.total;
+        if longer > 0 {
+            trade_offs.push(format!(
+                "{} in transit, {} longer",
+                options.duration(found.duration.total),
+                options.duration(longer)
+            ));
+        }
+    }
+    trade_offs
+}
+
+#[async_trait]
+impl ToolExecutor for SavingsFinderTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing SavingsFinderTool");
+        let args: SavingsFinderArgs = input::parse(arguments)?;
+
+        let date = parse_date(&args.date)?;
+        let today = Utc::now().date_naive();
+        let options = SearchOptions {
+            limit: CANDIDATES,
+            sort: "price".into(),
+            ..SearchOptions::from_args(&args.search, &self.defaults)?
+        };
+
+        let (from, to) = futures::join!(
+            self.alternates(&args.fly_from),
+            self.alternates(&args.fly_to)
+        );
+        let earliest = date
+            .checked_sub_days(Days::new(SHIFT_DAYS))
+            .unwrap_or(date)
+            .max(today);
+        let latest = date.checked_add_days(Days::new(SHIFT_DAYS)).unwrap_or(date);
+
+        log::info!(
+            "Searching for savings from {} to {} around {}, with {} and {} nearby airports",
+            args.fly_from,
+            args.fly_to,
+            args.date,
+            from.len(),
+            to.len()
+        );
+
+        // Without nearby airports, the variants searching them add nothing
+        let variants: Vec<&Variant> = VARIANTS
+            .iter()
+            .filter(|variant| !variant.other_airports || !from.is_empty() || !to.is_empty())
+            .collect();
+        let searches: Vec<_> = variants
+            .iter()
+            .map(|variant| {
+                let (fly_from, fly_to) = if variant.other_airports {
+                    (
+                        with_alternates(&args.fly_from, &from),
+                        with_alternates(&args.fly_to, &to),
+                    )
+                } else {
+                    (args.fly_from.clone(), args.fly_to.clone())
+                };
+                let (first, last) = if variant.shift_dates {
+                    (earliest, latest)
+                } else {
+                    (date, date)
+                };
+                let mut route = vec![
+                    ("fly_from", fly_from),
+                    ("fly_to", fly_to),
+                    ("date_from", format_date(first)),
+                    ("date_to", format_date(last)),
+                ];
+                if let Some(nights) = args.nights {
+                    route.push(("flight_type", "round".to_string()));
+                    route.push(("nights_in_dst_from", nights.to_string()));
+                    route.push(("nights_in_dst_to", nights.to_string()));
+                }
+                self.cheapest(route, &options)
+            })
+            .collect();
+        let mut best: Option<f64> = None;
+        let found = join_with_progress(self.client.progress(), searches, |found| {
+            if let Ok(Some(itinerary)) = found {
+                best = Some(best.map_or(itinerary.price, |best| best.min(itinerary.price)));
+            }
+            best.map(|best| format!("cheapest so far {}", options.price(best)))
+        })
+        .await;
+
+        let found: Vec<(&Variant, Itinerary)> = variants
+            .into_iter()
+            .zip(found)
+            .filter_map(|(variant, found)| match found {
+                Ok(found) => Some((variant, found?)),
+                Err(err) => {
+                    log::warn!("Savings search {:?} failed: {}", variant.label, err);
+                    None
+                }
+            })
+            .collect();
+        let asked = found
+            .iter()
+            .find(|(variant, _)| !variant.shift_dates && !variant.other_airports)
+            .map(|(_, itinerary)| itinerary);
+
+        let mut result = format!(
+            "Savings for {} → {} on {}{} (prices in {}):\n\n",
+            args.fly_from,
+            args.fly_to,
+            date.format("%d %b %Y"),
+            args.nights
+                .map(|nights| format!(", {} nights", nights))
+                .unwrap_or_default(),
+            options.curr
+        );
+        let nearby = |alternates: &Alternates| {
+            alternates
+                .iter()
+                .map(|(code, km)| format!("{} ({:.0} km)", code, km))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        result.push_str(&format!(
+            "Searched departures from {} to {}",
+            earliest.format("%d %b"),
+            latest.format("%d %b")
+        ));
+        if !from.is_empty() {
+            result.push_str(&format!(", from {} too", nearby(&from)));
+        }
+        if !to.is_empty() {
+            result.push_str(&format!(", to {} too", nearby(&to)));
+        }
+        if from.is_empty() && to.is_empty() {
+            result.push_str(&format!(
+                ". No other airports found within {} km",
+                RADIUS_KM
+            ));
+        }
+        result.push_str(".\n\n");
+
+        match asked {
+            Some(asked) => result.push_str(&format!(
+                "As asked: {}, {} in transit ({})\n\n",
+                options.price(asked.price),
+                options.duration(asked.duration.total),
+                asked.airlines.join(", ")
+            )),
+            None => result.push_str("As asked: no flights found\n\n"),
+        }
+
+        let Some((_, cheapest)) = found
+            .iter()
+            .min_by(|(_, a), (_, b)| a.price.total_cmp(&b.price))
+        else {
+            result.push_str("No flights found with other dates or airports either.\n");
+            self.client.push_quota_warning(&mut result);
+            return Ok(vec![ToolContent::Text { text: result }]);
+        };
+
+        let alternates = (&from, &to);
+        if asked.is_some_and(|asked| asked.price <= cheapest.price) {
+            result.push_str(
+                "Nothing nearby is cheaper: the search as asked already has the lowest fare.\n",
+            );
+        } else {
+            result.push_str(&format!(
+                "Cheapest found: {}{} ({})\n",
+                options.price(cheapest.price),
+                asked
+                    .map(|asked| format!(
+                        ", saves {}",
+                        options.price_format.amount(asked.price - cheapest.price)
+                    ))
+                    .unwrap_or_default(),
+                cheapest.airlines.join(", ")
+            ));
+            for trade_off in trade_offs(cheapest, date, asked, alternates, &args, &options) {
+                result.push_str(&format!("- {}\n", trade_off));
+            }
+            if let Some(link) = &cheapest.deep_link {
+                result.push_str(&format!("Book: {}\n", link));
+            }
+
+            result.push_str("\nBy what was changed:\n");
+            for (variant, itinerary) in &found {
+                if !variant.shift_dates && !variant.other_airports {
+                    continue;
+                }
+                let trade_offs = trade_offs(itinerary, date, asked, alternates, &args, &options);
+                result.push_str(&format!(
+                    "- {}: {}{}{}\n",
+                    variant.label,
+                    options.price(itinerary.price),
+                    asked
+                        .filter(|asked| itinerary.price < asked.price)
+                        .map(|asked| format!(
+                            ", saves {}",
+                            options.price_format.amount(asked.price - itinerary.price)
+                        ))
+                        .unwrap_or_default(),
+                    if trade_offs.is_empty() {
+                        String::new()
+                    } else {
+                        format!("; {}", trade_offs.join("; "))
+                    }
+                ));
+            }
+        }
+
+        result.push_str(
+            "\nDistances are in a straight line from the airport asked for; allow for the journey to a different airport, and check the return lands where you can get home from.\n",
+        );
+        options.push_notes(&mut result);
+        self.client.push_quota_warning(&mut result);
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "savings_finder".into(),
+            description: Some(
+                "Find a cheaper version of a search by moving the date up to two days either way and trying airports within 100 km of both ends, spelling out the trade-offs: the date change, the distance to the other airport and any extra travel time"
+                    .into(),
+            ),
+            input_schema: input::schema::<SavingsFinderArgs>(),
+        }
+    }
+}
@@ -0,0 +1,778 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{Days, Months, NaiveDate};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    alliances,
+    config::{InfantPolicy, SearchDefaults},
+    error::KiwiToolError,
+    format::{format_duration, format_duration_humanized},
+    formatter::{self, ResultFormatter},
+    holidays,
+    mileage::{self, Program},
+    model::{Itinerary, Layover, Leg},
+    money::PriceFormat,
+    quality::{AirlineQuality, AirlineRating},
+    transfers::{self, DEFAULT_MIN_CONNECTION_MINUTES},
+};
+
+/// Date format the Tequila API expects, e.g. `24/12/2025`.
+pub const DATE_FORMAT: &str = "%d/%m/%Y";
+
+pub fn parse_date(value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, DATE_FORMAT).map_err(|_| {
+        KiwiToolError::validation(format!("Invalid date {:?}, expected dd/mm/yyyy", value)).into()
+    })
+}
+
+/// The first day of `month`, given as mm/yyyy.
+pub fn parse_month(month: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("01/{}", month), DATE_FORMAT).map_err(|_| {
+        KiwiToolError::validation(format!("Invalid month {:?}, expected mm/yyyy", month)).into()
+    })
+}
+
+/// The first and last day of the month starting on `month` that are still
+/// ahead of `today`, or `None` when the month is over.
+pub fn month_days(month: NaiveDate, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    let last = month.checked_add_months(Months::new(1))? - Days::new(1);
+    let first = month.max(today + Days::new(1));
+    (first <= last).then_some((first, last))
+}
+
+pub fn format_date(date: NaiveDate) -> String {
+    date.format(DATE_FORMAT).to_string()
+}
+
+/// Whether a `fly_to` location is a country (`IT` or `country:IT`) rather
+/// than a city or airport.
+pub fn is_country(location: &str) -> bool {
+    let code = location.strip_prefix("country:").unwrap_or(location);
+    code.len() == 2 && code.chars().all(|c| c.is_ascii_uppercase())
+}
+
+/// Search options every flight search tool accepts, flattened into its
+/// input. Those left out take the configured defaults.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+pub struct SearchArgs {
+    /// Number of adult passengers
+    pub adults: Option<u64>,
+    /// Number of child passengers
+    pub children: Option<u64>,
+    /// Age of each child passenger (2-17); sets children when it is omitted
+    #[serde(default)]
+    pub children_ages: Vec<u64>,
+    /// Number of infant passengers
+    pub infants: Option<u64>,
+    /// Cabin class: M (economy), W (economy premium), C (business), F (first class)
+    #[schemars(extend("enum" = ["M", "W", "C", "F"]))]
+    pub selected_cabins: Option<String>,
+    /// Other cabin classes legs may be booked in, comma separated (e.g., 'M' to allow economy short legs on a business search)
+    pub mix_with_cabins: Option<String>,
+    /// Currency for prices (e.g., EUR, USD, GBP)
+    pub curr: Option<String>,
+    /// Maximum number of stopovers
+    pub max_stopovers: Option<u64>,
+    /// Hide itineraries with a connection shorter than this many minutes
+    pub min_connection_minutes: Option<u64>,
+    /// Hide itineraries with a flight departing or arriving overnight
+    #[serde(default)]
+    pub avoid_red_eyes: bool,
+    /// Only depart (and return) on working days: Monday to Friday, excluding holiday_country's public holidays
+    #[serde(default)]
+    pub only_working_days: bool,
+    /// Only depart (and return) on Saturdays or Sundays
+    #[serde(default)]
+    pub only_weekends: bool,
+    /// Country whose public holidays the holiday filters use (GB, IE, US, DE, FR, IT, ES, NL, CZ)
+    pub holiday_country: Option<String>,
+    /// Don't depart or return on a public holiday in holiday_country
+    #[serde(default)]
+    pub avoid_holidays: bool,
+    /// Only round trips whose stay covers a long weekend (a public holiday on the Friday or Monday) in holiday_country
+    #[serde(default)]
+    pub long_weekend: bool,
+    /// Only fly with airlines of this alliance
+    #[schemars(extend("enum" = ["star_alliance", "oneworld", "skyteam"]))]
+    pub alliance: Option<String>,
+    /// Show estimated miles earned with this program
+    #[schemars(extend("enum" = ["miles_and_more", "mileageplus", "aadvantage", "executive_club", "flying_blue", "skymiles"]))]
+    pub frequent_flyer_program: Option<String>,
+    /// Only the cheapest flight to each destination city, with a summary per country. The default when fly_to is a country
+    pub one_per_city: Option<bool>,
+    /// Passenger nationality as an ISO country code (e.g., 'IN'), to flag transit and entry visa requirements
+    pub nationality: Option<String>,
+    /// Also show each itinerary's price per adult and the average per person, besides the total for the party
+    #[serde(default)]
+    pub per_person_prices: bool,
+    /// Search groups of more than 9 passengers (infants included) as several bookings on the same flights
+    #[serde(default)]
+    pub split_large_groups: bool,
+    /// Return at most this many itineraries per airline, so one carrier can't crowd out the rest
+    pub max_per_airline: Option<u64>,
+    /// Present results in sections with the cheapest price of each
+    #[schemars(extend("enum" = ["airline", "date", "stops", "destination"]))]
+    pub group_by: Option<String>,
+    /// Show a flight found on several dates as a separate result for each date, instead of once with the dates it also flies (default false)
+    #[serde(default)]
+    pub show_every_date: bool,
+    /// How durations are written: compact ('7h 30m', the default) or humanized ('about 7½ hours', '2 days 3 hours')
+    #[schemars(extend("enum" = ["compact", "humanized"]))]
+    pub duration_style: Option<String>,
+    /// How trips of several tickets are written: detailed text, markdown, json or compact (one line per ticket)
+    #[schemars(extend("enum" = ["text", "markdown", "json", "compact"]))]
+    pub output_format: Option<String>,
+    /// Sort results by (price, duration, date, quality)
+    #[schemars(extend("enum" = ["price", "duration", "date", "quality"]))]
+    pub sort: Option<String>,
+    /// Maximum number of results to return
+    pub limit: Option<u64>,
+}
+
+/// Search parameters shared by every flight search tool, with the configured
+/// defaults filled in.
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub adults: u64,
+    pub children: u64,
+    pub infants: u64,
+    pub selected_cabins: String,
+    /// Cabins legs may also be booked in, e.g. `M` so short legs of a
+    /// business search can be economy.
+    pub mix_with_cabins: Option<String>,
+    pub curr: String,
+    pub max_stopovers: u64,
+    pub sort: String,
+    pub limit: u64,
+    /// Exact ages of the children, when the caller gave them. The API only
+    /// prices by age band, so these are used to validate and warn.
+    pub children_ages: Vec<u64>,
+    /// Itineraries with a shorter connection, in minutes, are dropped.
+    pub min_connection_minutes: Option<u64>,
+    pub tight_connection_minutes: u64,
+    pub tight_recheck_minutes: u64,
+    /// Drop itineraries with a leg departing or arriving at night.
+    pub avoid_red_eyes: bool,
+    pub night_hours: (u32, u32),
+    pub only_working_days: bool,
+    pub only_weekends: bool,
+    /// Country whose public holidays the holiday filters use.
+    pub holiday_country: Option<String>,
+    pub avoid_holidays: bool,
+    /// Keep only round trips whose stay covers a long weekend.
+    pub long_weekend: bool,
+    /// Only fly with members of this alliance.
+    pub alliance: Option<&'static [&'static str]>,
+    /// Show estimated miles earned with this program.
+    pub frequent_flyer_program: Option<&'static Program>,
+    /// Present the results in sections.
+    pub group_by: Option<GroupBy>,
+    /// Show the same flights on several dates as one result.
+    pub collapse_repeats: bool,
+    pub duration_style: DurationStyle,
+    /// Writes trips of several tickets.
+    pub formatter: &'static dyn ResultFormatter,
+    /// Keep at most this many itineraries per carrier.
+    pub max_per_airline: Option<u64>,
+    /// Only the cheapest itinerary to each destination city, summarised by
+    /// country.
+    pub one_per_city: bool,
+    /// Ratings of the airlines in the results, filled in before formatting
+    /// when an airline quality source is configured.
+    pub airline_ratings: HashMap<String, AirlineRating>,
+    /// Passenger nationality, for travel document hints.
+    pub nationality: Option<String>,
+    pub infant_policies: Vec<InfantPolicy>,
+    pub price_format: PriceFormat,
+    /// Also show the price per adult and per person.
+    pub per_person_prices: bool,
+}
+
+/// How results can be split into sections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Airline,
+    Date,
+    Stops,
+    Destination,
+}
+
+impl GroupBy {
+    fn parse(value: &str) -> Result<Self> {
+        match value {
+            "airline" => Ok(Self::Airline),
+            "date" => Ok(Self::Date),
+            "stops" => Ok(Self::Stops),
+            "destination" => Ok(Self::Destination),
+            _ => Err(KiwiToolError::validation(format!(
+                "Unknown group_by {:?}; use airline, date, stops or destination",
+                value
+            ))
+            .into()),
+        }
+    }
+
+    /// Section heading the itinerary belongs under.
+    pub fn key(self, itinerary: &Itinerary) -> String {
+        match self {
+            Self::Airline => itinerary.airlines.join(", "),
+            Self::Date => itinerary
+                .local_departure_date()
+                .map(|date| date.format("%a %d %b %Y").to_string())
+                .unwrap_or_else(|| "Unknown date".to_string()),
+            Self::Stops => match itinerary
+                .route
+                .iter()
+                .filter(|leg| leg.return_leg == 0)
+                .count()
+                .saturating_sub(1)
+            {
+                0 => "Direct".to_string(),
+                1 => "1 stopover".to_string(),
+                n => format!("{} stopovers", n),
+            },
+            Self::Destination => format!("{} ({})", itinerary.city_to, itinerary.fly_to),
+        }
+    }
+}
+
+/// How durations are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DurationStyle {
+    /// `7h 30m`
+    #[default]
+    Compact,
+    /// `about 7½ hours`
+    Humanized,
+}
+
+impl DurationStyle {
+    fn parse(value: &str) -> Result<Self> {
+        match value {
+            "compact" => Ok(Self::Compact),
+            "humanized" => Ok(Self::Humanized),
+            _ => Err(KiwiToolError::validation(format!(
+                "Unknown duration_style {:?}; use compact or humanized",
+                value
+            ))
+            .into()),
+        }
+    }
+}
+
+/// With a per-airline cap, this many times `limit` results are fetched so
+/// enough remain once the cap is applied.
+const MAX_PER_AIRLINE_OVERFETCH: u64 = 4;
+
+/// Most passengers (adults, children and infants) one search can price.
+pub const MAX_PASSENGERS: u64 = 9;
+
+/// Checks the passenger mix against the API's limits, so an invalid one gets
+/// a clear message instead of an opaque API error.
+pub fn validate_passengers(adults: u64, children: u64, infants: u64, split: bool) -> Result<()> {
+    if adults + children == 0 {
+        return Err(
+            KiwiToolError::validation("At least one adult or child passenger is required").into(),
+        );
+    }
+
+    if adults + children + infants > MAX_PASSENGERS && !split {
+        return Err(KiwiToolError::validation(format!(
+            "{} adults, children and infants requested; a search can include at most {} (set split_large_groups to search in batches)",
+            adults + children + infants, MAX_PASSENGERS
+        ))
+        .into());
+    }
+
+    if infants > adults {
+        return Err(KiwiToolError::validation(format!(
+            "{} infants but {} adults: each infant must travel on an adult's lap",
+            infants, adults
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Youngest age the API treats as a child rather than an infant.
+const CHILD_MIN_AGE: u64 = 2;
+/// Oldest age the API prices as a child; many airlines charge older
+/// children adult fares.
+const CHILD_MAX_AGE: u64 = 11;
+
+impl SearchOptions {
+    pub fn from_args(args: &SearchArgs, defaults: &SearchDefaults) -> Result<Self> {
+        let children_ages = args.children_ages.clone();
+
+        // The ages imply the number of children when it isn't given
+        let children = match args.children {
+            Some(children) => children,
+            None if !children_ages.is_empty() => children_ages.len() as u64,
+            None => defaults.children,
+        };
+
+        if !children_ages.is_empty() && children_ages.len() as u64 != children {
+            return Err(KiwiToolError::validation(format!(
+                "children_ages lists {} ages but children is {}",
+                children_ages.len(),
+                children
+            ))
+            .into());
+        }
+
+        if let Some(age) = children_ages.iter().find(|age| **age < CHILD_MIN_AGE) {
+            return Err(KiwiToolError::validation(format!(
+                "A child aged {} counts as an infant; pass them in infants instead",
+                age
+            ))
+            .into());
+        }
+
+        if let Some(age) = children_ages.iter().find(|age| **age >= 18) {
+            return Err(KiwiToolError::validation(format!(
+                "A passenger aged {} counts as an adult; pass them in adults instead",
+                age
+            ))
+            .into());
+        }
+
+        let only_working_days = args.only_working_days;
+        let only_weekends = args.only_weekends;
+        let avoid_holidays = args.avoid_holidays;
+        let long_weekend = args.long_weekend;
+        let holiday_country = args.holiday_country.as_deref().map(str::to_uppercase);
+
+        if only_working_days && only_weekends {
+            return Err(KiwiToolError::validation(
+                "only_working_days and only_weekends can't both be set",
+            )
+            .into());
+        }
+
+        match &holiday_country {
+            Some(country) if !holidays::is_supported(country) => {
+                return Err(KiwiToolError::validation(format!(
+                    "No holiday calendar for {}; available: {}",
+                    country,
+                    holidays::countries().collect::<Vec<_>>().join(", ")
+                ))
+                .into());
+            }
+            None if avoid_holidays || long_weekend => {
+                return Err(KiwiToolError::validation(
+                    "avoid_holidays and long_weekend need holiday_country",
+                )
+                .into());
+            }
+            _ => {}
+        }
+
+        let alliance = match args.alliance.as_deref() {
+            Some(name) => Some(alliances::members(name).ok_or_else(|| {
+                KiwiToolError::validation(format!(
+                    "Unknown alliance {:?}; use star_alliance, oneworld or skyteam",
+                    name
+                ))
+            })?),
+            None => None,
+        };
+
+        let frequent_flyer_program = match args.frequent_flyer_program.as_deref() {
+            Some(id) => Some(mileage::find(id).ok_or_else(|| {
+                KiwiToolError::validation(format!(
+                    "Unknown frequent flyer program {:?}; available: {}",
+                    id,
+                    mileage::program_ids().collect::<Vec<_>>().join(", ")
+                ))
+            })?),
+            None => None,
+        };
+
+        let group_by = args.group_by.as_deref().map(GroupBy::parse).transpose()?;
+        let duration_style = args
+            .duration_style
+            .as_deref()
+            .map(DurationStyle::parse)
+            .transpose()?
+            .unwrap_or_default();
+        let output_format = args
+            .output_format
+            .as_deref()
+            .unwrap_or(&defaults.output_format);
+        let formatter = formatter::find(output_format).ok_or_else(|| {
+            KiwiToolError::validation(format!(
+                "Unknown output_format {:?}; use {}",
+                output_format,
+                formatter::names().collect::<Vec<_>>().join(", ")
+            ))
+        })?;
+
+        let adults = args.adults.unwrap_or(defaults.adults);
+        let infants = args.infants.unwrap_or(defaults.infants);
+        validate_passengers(adults, children, infants, args.split_large_groups)?;
+
+        Ok(Self {
+            adults,
+            children,
+            infants,
+            selected_cabins: args
+                .selected_cabins
+                .clone()
+                .unwrap_or_else(|| defaults.selected_cabins.clone()),
+            mix_with_cabins: args.mix_with_cabins.clone(),
+            curr: args.curr.clone().unwrap_or_else(|| defaults.curr.clone()),
+            max_stopovers: args.max_stopovers.unwrap_or(defaults.max_stopovers),
+            sort: args.sort.clone().unwrap_or_else(|| defaults.sort.clone()),
+            limit: args.limit.unwrap_or(defaults.limit),
+            children_ages,
+            min_connection_minutes: args.min_connection_minutes,
+            tight_connection_minutes: defaults.tight_connection_minutes,
+            tight_recheck_minutes: defaults.tight_recheck_minutes,
+            avoid_red_eyes: args.avoid_red_eyes,
+            night_hours: (defaults.night_start_hour, defaults.night_end_hour),
+            only_working_days,
+            only_weekends,
+            holiday_country,
+            avoid_holidays,
+            long_weekend,
+            alliance,
+            frequent_flyer_program,
+            group_by,
+            collapse_repeats: !args.show_every_date,
+            duration_style,
+            formatter,
+            max_per_airline: args.max_per_airline.filter(|max| *max > 0),
+            one_per_city: args.one_per_city.unwrap_or(false),
+            airline_ratings: HashMap::new(),
+            nationality: args
+                .nationality
+                .clone()
+                .or_else(|| defaults.nationality.clone()),
+            infant_policies: defaults.infant_policies.clone(),
+            price_format: defaults.price_format()?,
+            per_person_prices: args.per_person_prices,
+        })
+    }
+
+    /// Looks up ratings for every airline in `itineraries`. A failing source
+    /// only loses the annotations.
+    pub async fn rate_airlines(&mut self, quality: &dyn AirlineQuality, itineraries: &[Itinerary]) {
+        let mut airlines: Vec<&str> = itineraries
+            .iter()
+            .flat_map(|itinerary| itinerary.airlines.iter().map(String::as_str))
+            .collect();
+        airlines.sort_unstable();
+        airlines.dedup();
+
+        for airline in airlines {
+            match quality.rating(airline).await {
+                Ok(Some(rating)) => {
+                    self.airline_ratings.insert(airline.to_string(), rating);
+                }
+                Ok(None) => {}
+                Err(err) => log::warn!("Failed to rate airline {}: {}", airline, err),
+            }
+        }
+    }
+
+    /// Formats a price in the search currency, e.g. `€1,234.56`.
+    pub fn price(&self, value: f64) -> String {
+        self.price_format.price(value, &self.curr)
+    }
+
+    /// Formats a duration given in seconds in the requested style.
+    pub fn duration(&self, seconds: i64) -> String {
+        match self.duration_style {
+            DurationStyle::Compact => format_duration(seconds),
+            DurationStyle::Humanized => format_duration_humanized(seconds),
+        }
+    }
+
+    /// Adults and children, who each need a seat.
+    pub fn seated(&self) -> u64 {
+        self.adults + self.children
+    }
+
+    /// Splits the group into as few bookings as the passenger cap allows,
+    /// infants included, as evenly as possible, keeping each infant with an
+    /// adult. Each batch asks for more results, since only flights found for
+    /// every batch can be used.
+    pub fn split_group(&self) -> Vec<SearchOptions> {
+        let passengers = self.seated() + self.infants;
+        let mut batches = passengers.div_ceil(MAX_PASSENGERS).max(1);
+
+        loop {
+            // Extra adults and infants go to the first batches, so each
+            // infant has an adult, and extra children to the last ones
+            let share = |total: u64, batch: u64, from_end: bool| {
+                let remainder = total % batches;
+                let extra = if from_end {
+                    batch >= batches - remainder
+                } else {
+                    batch < remainder
+                };
+                total / batches + extra as u64
+            };
+
+            let split: Vec<SearchOptions> = (0..batches)
+                .map(|batch| SearchOptions {
+                    adults: share(self.adults, batch, false),
+                    children: share(self.children, batch, true),
+                    infants: share(self.infants, batch, false),
+                    children_ages: Vec::new(),
+                    limit: self.limit * 3,
+                    ..self.clone()
+                })
+                .collect();
+            // Both remainders landing on the first batch can push it over
+            // the cap; one more batch always fixes that
+            if split
+                .iter()
+                .all(|batch| batch.seated() + batch.infants <= MAX_PASSENGERS)
+                || batches >= passengers
+            {
+                return split;
+            }
+            batches += 1;
+        }
+    }
+
+    /// How many results to ask the API for. More than `limit` when the
+    /// per-airline cap will discard some of them.
+    fn api_limit(&self) -> u64 {
+        match self.max_per_airline {
+            Some(_) => self.limit * MAX_PER_AIRLINE_OVERFETCH,
+            None => self.limit,
+        }
+    }
+
+    /// Drops itineraries the API returned but that fail the filters it has no
+    /// parameter for, and returns how many were dropped.
+    pub fn apply_filters(&self, itineraries: &mut Vec<Itinerary>) -> usize {
+        let before = itineraries.len();
+
+        if let Some(min) = self.min_connection_minutes {
+            itineraries.retain(|itinerary| {
+                itinerary
+                    .layovers()
+                    .iter()
+                    .all(|layover| layover.duration.num_minutes() >= min as i64)
+            });
+        }
+
+        if self.avoid_red_eyes {
+            itineraries.retain(|itinerary| !itinerary.route.iter().any(|leg| self.is_red_eye(leg)));
+        }
+
+        itineraries.retain(|itinerary| self.matches_dates(itinerary));
+
+        if let Some(max) = self.max_per_airline {
+            let mut counts: HashMap<String, u64> = HashMap::new();
+            itineraries.retain(|itinerary| {
+                let count = counts.entry(itinerary.airlines.join(",")).or_default();
+                *count += 1;
+                *count <= max
+            });
+        }
+
+        let removed = before - itineraries.len();
+
+        // Drop the extra results fetched to make up for the per-airline cap
+        itineraries.truncate(self.limit as usize);
+
+        removed
+    }
+
+    fn matches_dates(&self, itinerary: &Itinerary) -> bool {
+        let country = self.holiday_country.as_deref();
+        let is_holiday = |date| country.is_some_and(|country| holidays::is_holiday(country, date));
+
+        let dates_ok = itinerary
+            .journey_starts()
+            .filter_map(|leg| leg.local_departure_date())
+            .all(|date| {
+                let weekend = holidays::is_weekend(date);
+                !(self.only_working_days && (weekend || is_holiday(date))
+                    || self.only_weekends && !weekend
+                    || self.avoid_holidays && is_holiday(date))
+            });
+
+        let long_weekend_ok = match (self.long_weekend, country) {
+            (true, Some(country)) => itinerary.stay().is_some_and(|(arrival, departure)| {
+                holidays::covers_long_weekend(country, arrival, departure)
+            }),
+            _ => true,
+        };
+
+        dates_ok && long_weekend_ok
+    }
+
+    /// Whether the leg departs or arrives during the configured night hours.
+    pub fn is_red_eye(&self, leg: &Leg) -> bool {
+        let (start, end) = self.night_hours;
+        let at_night = |hour: u32| {
+            if start <= end {
+                (start..end).contains(&hour)
+            } else {
+                hour >= start || hour < end
+            }
+        };
+
+        let (departure, arrival) = leg.local_hours();
+        departure.is_some_and(at_night) || arrival.is_some_and(at_night)
+    }
+
+    /// Shortest connection that is comfortable at this layover, in minutes.
+    /// Changing airports or rechecking bags needs longer.
+    pub fn comfortable_connection_minutes(&self, layover: &Layover) -> u64 {
+        if layover.changes_airport() {
+            transfers::find(&layover.arriving.fly_to, &layover.departing.fly_from)
+                .map_or(DEFAULT_MIN_CONNECTION_MINUTES, |t| t.min_connection_minutes)
+                as u64
+        } else if layover.arriving.bags_recheck_required {
+            self.tight_recheck_minutes
+        } else {
+            self.tight_connection_minutes
+        }
+    }
+
+    /// Appends warnings about options the API can't fully honour.
+    pub fn push_notes(&self, result: &mut String) {
+        let teens: Vec<String> = self
+            .children_ages
+            .iter()
+            .filter(|age| **age > CHILD_MAX_AGE)
+            .map(|age| age.to_string())
+            .collect();
+
+        if !teens.is_empty() {
+            result.push_str(&format!(
+                "\nNote: children aged {} are searched at child fares, but many airlines charge passengers over {} adult fares. Check the final price when booking.\n",
+                teens.join(", "),
+                CHILD_MAX_AGE
+            ));
+        }
+
+        if self.infants > 0 {
+            result.push_str(
+                "\nNote: infants under 2 travel on an adult's lap, one per adult, usually for a fee or a share of the adult fare. An infant needing their own seat must be booked as a child. Check each airline's infant policy before booking.\n",
+            );
+        }
+    }
+
+    /// Policies to flag for the itinerary's airlines, when infants travel.
+    pub fn infant_policies_for<'a>(&'a self, itinerary: &Itinerary) -> Vec<&'a InfantPolicy> {
+        if self.infants == 0 {
+            return Vec::new();
+        }
+
+        self.infant_policies
+            .iter()
+            .filter(|policy| itinerary.airlines.contains(&policy.airline))
+            .collect()
+    }
+
+    pub fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![
+            ("adults", self.adults.to_string()),
+            ("children", self.children.to_string()),
+            ("infants", self.infants.to_string()),
+            ("selected_cabins", self.selected_cabins.clone()),
+            ("curr", self.curr.clone()),
+            ("max_stopovers", self.max_stopovers.to_string()),
+            ("sort", self.sort.clone()),
+            ("limit", self.api_limit().to_string()),
+        ];
+
+        if let Some(mix_with_cabins) = &self.mix_with_cabins {
+            params.push(("mix_with_cabins", mix_with_cabins.clone()));
+        }
+
+        if self.one_per_city {
+            params.push(("one_for_city", "1".to_string()));
+        }
+
+        if let Some(members) = self.alliance {
+            params.push(("select_airlines", members.join(",")));
+            params.push(("select_airlines_exclude", "false".to_string()));
+        }
+
+        // Days of the week, 0 being Sunday
+        let fly_days = if self.only_working_days {
+            Some("1,2,3,4,5")
+        } else if self.only_weekends {
+            Some("0,6")
+        } else {
+            None
+        };
+        if let Some(fly_days) = fly_days {
+            params.push(("fly_days", fly_days.to_string()));
+            params.push(("ret_fly_days", fly_days.to_string()));
+        }
+
+        params
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(adults: u64, children: u64, infants: u64) -> SearchOptions {
+        let args = SearchArgs {
+            adults: Some(adults),
+            children: Some(children),
+            infants: Some(infants),
+            split_large_groups: true,
+            ..SearchArgs::default()
+        };
+        SearchOptions::from_args(&args, &SearchDefaults::default()).unwrap()
+    }
+
+    fn passengers(batches: &[SearchOptions]) -> Vec<(u64, u64, u64)> {
+        batches
+            .iter()
+            .map(|batch| (batch.adults, batch.children, batch.infants))
+            .collect()
+    }
+
+    #[test]
+    fn small_groups_are_not_split() {
+        let options = group(2, 1, 1);
+        let batches = options.split_group();
+
+        assert_eq!(passengers(&batches), [(2, 1, 1)]);
+        assert_eq!(batches[0].limit, options.limit * 3);
+    }
+
+    #[test]
+    fn large_groups_split_evenly() {
+        assert_eq!(
+            passengers(&group(12, 0, 0).split_group()),
+            [(6, 0, 0), (6, 0, 0)]
+        );
+        assert_eq!(
+            passengers(&group(7, 5, 0).split_group()),
+            [(4, 2, 0), (3, 3, 0)]
+        );
+    }
+
+    #[test]
+    fn every_batch_fits_and_keeps_infants_with_adults() {
+        for (adults, children, infants) in [(5, 0, 5), (9, 9, 1), (10, 1, 8), (19, 0, 0)] {
+            let batches = group(adults, children, infants).split_group();
+
+            for batch in &batches {
+                assert!(batch.seated() + batch.infants <= MAX_PASSENGERS);
+                assert!(batch.infants <= batch.adults);
+            }
+            assert_eq!(batches.iter().map(|b| b.adults).sum::<u64>(), adults);
+            assert_eq!(batches.iter().map(|b| b.children).sum::<u64>(), children);
+            assert_eq!(batches.iter().map(|b| b.infants).sum::<u64>(), infants);
+        }
+    }
+}
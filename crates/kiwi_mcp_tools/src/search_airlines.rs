@@ -0,0 +1,110 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    airlines::{self, Airline},
+    alliances,
+    error::KiwiToolError,
+    input::{self, ToolInput},
+};
+
+#[derive(Deserialize, JsonSchema)]
+struct SearchAirlinesArgs {
+    /// IATA code (e.g., 'FR') or part of the name (e.g., 'lufthansa'); all airlines when omitted
+    query: Option<String>,
+    /// Only airlines of this alliance
+    #[schemars(extend("enum" = ["star_alliance", "oneworld", "skyteam"]))]
+    alliance: Option<String>,
+    /// Only low-cost carriers (true) or only full-service airlines (false)
+    low_cost: Option<bool>,
+}
+
+impl ToolInput for SearchAirlinesArgs {
+    fn validate(&self) -> Result<()> {
+        if let Some(alliance) = &self.alliance
+            && alliances::members(alliance).is_none()
+        {
+            return Err(KiwiToolError::validation(format!(
+                "Unknown alliance {:?}; use star_alliance, oneworld or skyteam",
+                alliance
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Looks carriers up in the built-in airline directory, so the model can
+/// find the codes and alliances the search tools' airline options take.
+#[derive(Default)]
+pub struct SearchAirlinesTool;
+
+#[async_trait]
+impl ToolExecutor for SearchAirlinesTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing SearchAirlinesTool");
+        let args: SearchAirlinesArgs = input::parse(arguments)?;
+
+        let query = args
+            .query
+            .as_deref()
+            .map(str::trim)
+            .filter(|query| !query.is_empty());
+        let alliance = args.alliance.as_deref().and_then(alliances::members);
+
+        let matches: Vec<&Airline> = airlines::all()
+            .iter()
+            .filter(|airline| query.is_none_or(|query| matches(airline, query)))
+            .filter(|airline| alliance.is_none_or(|members| members.contains(&airline.code)))
+            .filter(|airline| {
+                args.low_cost
+                    .is_none_or(|low_cost| airline.low_cost == low_cost)
+            })
+            .collect();
+
+        if matches.is_empty() {
+            return Ok(vec![ToolContent::Text {
+                text: "No airlines in the directory match. It only lists common carriers; results can still include others by their IATA code.".into(),
+            }]);
+        }
+
+        let mut result = format!("Found {} airlines:\n\n", matches.len());
+        for airline in matches {
+            let mut kind = if airline.low_cost {
+                "low-cost".to_string()
+            } else {
+                "full service".to_string()
+            };
+            if let Some(alliance) = airline.alliance() {
+                kind.push_str(&format!(", {}", alliance));
+            }
+            result.push_str(&format!("{}  {} ({})\n", airline.code, airline.name, kind));
+        }
+        result.push_str(
+            "\nSearch tools take an alliance to fly only with its members, and results can be grouped by airline.\n",
+        );
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "search_airlines".into(),
+            description: Some(
+                "Look up airlines by name or IATA code: code, full name, low-cost or full service, and alliance. Only a built-in list of about 90 major carriers is covered, not the full Kiwi carriers directory, so many codes in search results (smaller and regional airlines) won't be found"
+                    .into(),
+            ),
+            input_schema: input::schema::<SearchAirlinesArgs>(),
+        }
+    }
+}
+
+/// Whether `query` is the airline's code or part of its name.
+fn matches(airline: &Airline, query: &str) -> bool {
+    airline.code.eq_ignore_ascii_case(query)
+        || airline.name.to_lowercase().contains(&query.to_lowercase())
+}
@@ -0,0 +1,219 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use context_server::{
+    Resource, ResourceContent, ResourceExecutor, Tool, ToolContent, ToolExecutor,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    history::HistoryEntry,
+    input::{self, ToolInput},
+    money::PriceFormat,
+};
+
+const HISTORY_URI: &str = "kiwi://history";
+
+#[derive(Deserialize, JsonSchema)]
+struct SearchHistoryArgs {
+    /// Only include searches from this location code
+    fly_from: Option<String>,
+    /// Only include searches to this location code
+    fly_to: Option<String>,
+    /// How many days back to look (default: 7)
+    #[serde(default = "default_days")]
+    days: i64,
+    /// Maximum number of individual searches to list (default: 20)
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+fn default_days() -> i64 {
+    7
+}
+
+fn default_limit() -> usize {
+    20
+}
+
+impl ToolInput for SearchHistoryArgs {}
+
+/// Lists past searches, filtered by route and age, with a per-route summary.
+pub struct SearchHistoryTool {
+    client: Arc<KiwiClient>,
+    price_format: PriceFormat,
+}
+
+impl SearchHistoryTool {
+    pub fn new(client: Arc<KiwiClient>, price_format: PriceFormat) -> Self {
+        Self {
+            client,
+            price_format,
+        }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for SearchHistoryTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing SearchHistoryTool");
+        let args: SearchHistoryArgs = input::parse(arguments)?;
+
+        let fly_from = args.fly_from.as_deref();
+        let fly_to = args.fly_to.as_deref();
+        let days = args.days;
+        let limit = args.limit;
+
+        let since = Utc::now() - Duration::days(days);
+        let matches = |param: Option<&str>, filter: Option<&str>| {
+            filter
+                .is_none_or(|filter| param.is_some_and(|param| param.eq_ignore_ascii_case(filter)))
+        };
+
+        let entries: Vec<HistoryEntry> = self
+            .client
+            .history()
+            .session_entries()
+            .into_iter()
+            .filter(|entry| entry.searched_at >= since)
+            .filter(|entry| matches(entry.param("fly_from"), fly_from))
+            .filter(|entry| matches(entry.param("fly_to"), fly_to))
+            .collect();
+
+        if entries.is_empty() {
+            return Ok(vec![ToolContent::Text {
+                text: format!("No searches found in the last {} days.", days),
+            }]);
+        }
+
+        Ok(vec![ToolContent::Text {
+            text: format_history(&entries, days, limit, &self.price_format),
+        }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "search_history".into(),
+            description: Some(
+                "List past flight searches and the routes being researched, with the cheapest price each search found"
+                    .into(),
+            ),
+            input_schema: input::schema::<SearchHistoryArgs>(),
+        }
+    }
+}
+
+fn format_history(
+    entries: &[HistoryEntry],
+    days: i64,
+    limit: usize,
+    price_format: &PriceFormat,
+) -> String {
+    // Summarise per route: number of searches and the cheapest one
+    let mut routes: BTreeMap<(&str, &str), Vec<&HistoryEntry>> = BTreeMap::new();
+    for entry in entries {
+        routes.entry(entry.route()).or_default().push(entry);
+    }
+
+    let mut result = format!(
+        "{} searches over {} routes in the last {} days:\n\n",
+        entries.len(),
+        routes.len(),
+        days
+    );
+
+    result.push_str("Routes:\n");
+    for ((from, to), searches) in &routes {
+        let best = searches
+            .iter()
+            .filter_map(|entry| entry.cheapest_price.map(|price| (price, entry)))
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(price, entry)| {
+                format!(
+                    ", best {}",
+                    price_format.price(price, entry.currency.as_deref().unwrap_or(""))
+                )
+            })
+            .unwrap_or_default();
+        result.push_str(&format!(
+            "  {} → {}: {} searches{}\n",
+            from,
+            to,
+            searches.len(),
+            best
+        ));
+    }
+
+    result.push_str("\nMost recent searches:\n");
+    for entry in entries.iter().rev().take(limit) {
+        let (from, to) = entry.route();
+        let dates = match (entry.param("date_from"), entry.param("date_to")) {
+            (Some(date_from), Some(date_to)) if date_from != date_to => {
+                format!("{}–{}", date_from, date_to)
+            }
+            (Some(date), _) | (None, Some(date)) => date.to_string(),
+            (None, None) => "any date".to_string(),
+        };
+        let cheapest = match entry.cheapest_price {
+            Some(price) => format!(
+                "cheapest {}",
+                price_format.price(price, entry.currency.as_deref().unwrap_or(""))
+            ),
+            None => "no results".to_string(),
+        };
+
+        result.push_str(&format!(
+            "  {} — {} → {}, {}, {} ({} results)\n",
+            entry.searched_at.format("%d %b %Y, %H:%M UTC"),
+            from,
+            to,
+            dates,
+            cheapest,
+            entry.results
+        ));
+    }
+
+    result
+}
+
+/// Exposes the full search history as JSON at `kiwi://history`.
+pub struct HistoryResource {
+    client: Arc<KiwiClient>,
+}
+
+impl HistoryResource {
+    pub fn new(client: Arc<KiwiClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl ResourceExecutor for HistoryResource {
+    fn to_resource(&self) -> Resource {
+        Resource {
+            uri: HISTORY_URI.into(),
+            name: "Search history".into(),
+            description: Some(
+                "Every flight search made through this server, with its parameters and cheapest price"
+                    .into(),
+            ),
+            mime_type: Some("application/json".into()),
+        }
+    }
+
+    async fn read(&self, uri: &str) -> Result<Vec<ResourceContent>> {
+        let entries = self.client.history().session_entries();
+
+        Ok(vec![ResourceContent {
+            uri: uri.to_string(),
+            mime_type: Some("application/json".into()),
+            text: Some(serde_json::to_string_pretty(&entries)?),
+            blob: None,
+        }])
+    }
+}
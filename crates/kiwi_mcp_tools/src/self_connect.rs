@@ -0,0 +1,143 @@
+use anyhow::Result;
+use chrono::{DateTime, Days};
+
+use crate::{
+    KiwiClient,
+    positioning_flights::SPARING_HUBS,
+    progress::join_with_progress,
+    search::{SearchOptions, format_date},
+    trip::{Trip, format_trips},
+};
+
+/// Time allowed between separate tickets at the hub, to collect bags, check
+/// in again and absorb a modest delay.
+pub const SELF_TRANSFER_MINUTES: i64 = 180;
+
+/// Results asked for per search, so filters still leave a candidate.
+const CANDIDATES: u64 = 3;
+
+/// Searches for self-connections, two separately booked flights connecting
+/// at a hub, from `fly_from` to `fly_to` through each
+/// of `hubs`, returning the cheapest per hub, cheapest first. While the
+/// request budget is tight only the first [`SPARING_HUBS`] are tried.
+pub async fn search(
+    client: &KiwiClient,
+    hubs: &[String],
+    fly_from: &str,
+    fly_to: &str,
+    date_from: &str,
+    date_to: &str,
+    options: &SearchOptions,
+) -> Vec<Trip> {
+    let sparing = client.budget().is_tight();
+    let hubs = hubs
+        .iter()
+        .filter(|hub| hub.as_str() != fly_from && hub.as_str() != fly_to)
+        .take(if sparing { SPARING_HUBS } else { usize::MAX });
+
+    let mut best: Option<f64> = None;
+    let found = join_with_progress(
+        client.progress(),
+        hubs.map(|hub| async move {
+            let connection = via(client, hub, fly_from, fly_to, date_from, date_to, options).await;
+            if let Err(err) = &connection {
+                log::warn!("Self-connect search via {} failed: {}", hub, err);
+            }
+            connection.ok().flatten()
+        }),
+        |connection: &Option<Trip>| {
+            if let Some(connection) = connection {
+                let price = connection.price();
+                best = Some(best.map_or(price, |best| best.min(price)));
+            }
+            best.map(|best| format!("cheapest self-connection so far {}", options.price(best)))
+        },
+    )
+    .await;
+
+    let mut connections: Vec<Trip> = found.into_iter().flatten().collect();
+    connections.sort_by(|a, b| a.price().total_cmp(&b.price()));
+    connections
+}
+
+async fn via(
+    client: &KiwiClient,
+    hub: &str,
+    fly_from: &str,
+    fly_to: &str,
+    date_from: &str,
+    date_to: &str,
+    options: &SearchOptions,
+) -> Result<Option<Trip>> {
+    let options = SearchOptions {
+        limit: CANDIDATES,
+        sort: "price".into(),
+        ..options.clone()
+    };
+
+    let mut params = vec![
+        ("fly_from", fly_from.to_string()),
+        ("fly_to", hub.to_string()),
+        ("date_from", date_from.to_string()),
+        ("date_to", date_to.to_string()),
+    ];
+    params.extend(options.params());
+    let mut first = client.search_itineraries(&params).await?.data;
+    options.apply_filters(&mut first);
+    let Some(first) = first.into_iter().min_by(|a, b| a.price.total_cmp(&b.price)) else {
+        return Ok(None);
+    };
+    let Some(arrival) = first.utc_arrival else {
+        return Ok(None);
+    };
+
+    let arrival_date = first
+        .local_arrival
+        .as_deref()
+        .and_then(|local| DateTime::parse_from_rfc3339(local).ok())
+        .map(|local| local.date_naive())
+        .unwrap_or_else(|| arrival.date_naive());
+    let mut params = vec![
+        ("fly_from", hub.to_string()),
+        ("fly_to", fly_to.to_string()),
+        ("date_from", format_date(arrival_date)),
+        ("date_to", format_date(arrival_date + Days::new(1))),
+    ];
+    params.extend(options.params());
+    let earliest = arrival + chrono::Duration::minutes(SELF_TRANSFER_MINUTES);
+    let second = client
+        .search_itineraries(&params)
+        .await?
+        .data
+        .into_iter()
+        .filter(|second| {
+            second
+                .utc_departure
+                .is_some_and(|departure| departure >= earliest)
+        })
+        .min_by(|a, b| a.price.total_cmp(&b.price));
+
+    Ok(second.map(|second| Trip::new().then("Ticket 1", first).then("Ticket 2", second)))
+}
+
+/// Presents self-connections, clearly marked as separate tickets.
+pub fn format_connections(connections: &[Trip], options: &SearchOptions) -> String {
+    let mut result = format!(
+        "Found {} self-connect options through hubs. Each is two separate tickets:\n\n",
+        connections.len()
+    );
+    result.push_str(&format_trips(connections, options, |number, connection| {
+        format!(
+            "Option {}: via {}, total price {}\n\n",
+            number,
+            connection.tickets[0].itinerary.city_to,
+            options.price(connection.price())
+        )
+    }));
+
+    result.push_str(&format!(
+        "\nSelf-connecting is at your own risk: if the first flight is late, the second airline does not have to rebook you; bags must be collected and checked in again; and you may need to enter the hub country. Options allow at least {} between tickets.\n",
+        options.duration(SELF_TRANSFER_MINUTES * 60)
+    ));
+    result
+}
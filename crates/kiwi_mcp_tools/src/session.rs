@@ -0,0 +1,104 @@
+//! Which client a tool call is made for, so that concurrent clients of a
+//! multi-client transport don't see each other's results.
+//!
+//! Transports run each request inside [`scope`]; stores tag what they keep
+//! with [`current`] and only hand it back within the same session. Calls
+//! made outside any scope, such as over stdio, share the unscoped session.
+//!
+//! Sessions end when the client closes them, after a day unused, or when
+//! more than [`MAX_SESSIONS`] are open and they are the least recently
+//! used; stores drop what they keep for sessions that ended.
+
+use std::{
+    collections::HashMap,
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+use aes_gcm::aead::{OsRng, rand_core::RngCore};
+use parking_lot::Mutex;
+
+/// Sessions open at once, at most.
+const MAX_SESSIONS: usize = 1000;
+
+/// Sessions unused for this long end.
+const SESSION_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Live session IDs handed out by [`new_id`], with when each was last used,
+/// so made-up and ended ones can be refused.
+static ISSUED: LazyLock<Mutex<HashMap<String, Instant>>> = LazyLock::new(Mutex::default);
+
+tokio::task_local! {
+    static SESSION: String;
+}
+
+/// Runs `future` as part of `session`, or unscoped when `None`.
+pub async fn scope<F: Future>(session: Option<String>, future: F) -> F::Output {
+    match session {
+        Some(session) => SESSION.scope(session, future).await,
+        None => future.await,
+    }
+}
+
+/// The session of the request being handled, if it has one.
+pub fn current() -> Option<String> {
+    SESSION.try_with(Clone::clone).ok()
+}
+
+/// A new session ID from the OS random source, remembered as issued. Ends
+/// the least recently used session when [`MAX_SESSIONS`] are open.
+pub fn new_id() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    let id: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+    let mut issued = ISSUED.lock();
+    expire(&mut issued);
+    if issued.len() >= MAX_SESSIONS
+        && let Some(oldest) = issued
+            .iter()
+            .min_by_key(|(_, used)| **used)
+            .map(|(id, _)| id.clone())
+    {
+        issued.remove(&oldest);
+    }
+    issued.insert(id.clone(), Instant::now());
+    id
+}
+
+/// Whether `id` is a live session handed out by [`new_id`], marking it as
+/// used when it is.
+pub fn touch(id: &str) -> bool {
+    let mut issued = ISSUED.lock();
+    expire(&mut issued);
+    match issued.get_mut(id) {
+        Some(used) => {
+            *used = Instant::now();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Whether `id` is a live session, without marking it as used.
+pub fn is_live(id: &str) -> bool {
+    ISSUED
+        .lock()
+        .get(id)
+        .is_some_and(|used| used.elapsed() < SESSION_TTL)
+}
+
+/// Ends the session `id`. Returns whether it was live.
+pub fn end(id: &str) -> bool {
+    ISSUED.lock().remove(id).is_some()
+}
+
+/// Whether data kept for `session` should still be: always for the
+/// unscoped session, and while a scoped one is live.
+pub fn is_kept(session: Option<&str>) -> bool {
+    session.is_none_or(is_live)
+}
+
+fn expire(issued: &mut HashMap<String, Instant>) {
+    issued.retain(|_, used| used.elapsed() < SESSION_TTL);
+}
@@ -0,0 +1,373 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use context_server::{Tool, ToolContent, ToolExecutor};
+use futures::future::join_all;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    config::SearchDefaults,
+    error::KiwiToolError,
+    input::{self, ToolInput},
+    locations,
+    positioning_flights::{SPARING_HUBS, cheapest, via},
+    progress::join_with_progress,
+    search::{SearchArgs, SearchOptions},
+    transfers,
+    trip::{Trip, format_trips},
+};
+
+/// Results asked for per search, so filters still leave a candidate.
+const CANDIDATES: u64 = 3;
+
+/// Gateways tried, at most, per call.
+const MAX_GATEWAYS: usize = 5;
+
+/// Time between tickets under which a late short hop is likely to cost the
+/// long-haul.
+const TIGHT_MINUTES: i64 = 240;
+
+#[derive(Deserialize, JsonSchema)]
+struct SplitLongHaulArgs {
+    /// IATA code of the traveller's home airport or city
+    fly_from: String,
+    /// IATA code of the destination on another continent
+    fly_to: String,
+    /// Long-haul departure date in format dd/mm/yyyy
+    date_from: String,
+    /// Latest long-haul departure date in format dd/mm/yyyy
+    date_to: String,
+    /// IATA codes of gateways to fly long-haul from (defaults to the configured gateways on the traveller's continent)
+    gateways: Option<Vec<String>>,
+    #[serde(flatten)]
+    search: SearchArgs,
+}
+
+impl ToolInput for SplitLongHaulArgs {}
+
+/// Compares an intercontinental trip booked as one ticket with a cheap hop
+/// to a long-haul gateway on the same continent plus a separate long-haul
+/// from there, and notes what each split risks.
+pub struct SplitLongHaulTool {
+    client: Arc<KiwiClient>,
+    defaults: SearchDefaults,
+}
+
+impl SplitLongHaulTool {
+    pub fn new(client: Arc<KiwiClient>, defaults: SearchDefaults) -> Self {
+        Self { client, defaults }
+    }
+
+    /// The `/locations/query` response for `code`. Lookup failures are
+    /// only logged.
+    async fn locate(&self, code: &str) -> Option<Value> {
+        self.client
+            .locations_query(code, 5)
+            .await
+            .inspect_err(|err| log::warn!("Failed to look up location {}: {}", code, err))
+            .ok()
+    }
+
+    /// The continent of `code`, when the locations API knows it.
+    async fn continent(&self, code: &str) -> Option<String> {
+        locations::continent(&self.locate(code).await?, code)
+    }
+}
+
+/// What can go wrong with the split `trip`: the time between the tickets,
+/// a night or a change of airport on the way, and entering the gateway's
+/// country to check in again unless the hop stays in `home_country`.
+fn risks(trip: &Trip, options: &SearchOptions, home_country: Option<&str>) -> Vec<String> {
+    let hop = &trip.tickets[0].itinerary;
+    let long_haul = &trip.tickets[1].itinerary;
+    let mut risks = Vec::new();
+
+    if hop.local_arrival_date() != long_haul.local_departure_date() {
+        risks.push(format!(
+            "the short hop lands the day before: a night in {}, so budget for a hotel",
+            hop.city_to
+        ));
+    } else if let Some(between) = trip.time_between(0) {
+        if between.num_minutes() < TIGHT_MINUTES {
+            risks.push(format!(
+                "only {} between tickets: a late short hop can cost the long-haul, and neither airline has to rebook you",
+                options.duration(between.num_seconds())
+            ));
+        } else {
+            risks.push(format!(
+                "{} between tickets",
+                options.duration(between.num_seconds())
+            ));
+        }
+    }
+
+    if hop.fly_to != long_haul.fly_from {
+        risks.push(match transfers::find(&hop.fly_to, &long_haul.fly_from) {
+            Some(transfer) => format!(
+                "change airports from {} to {}, about {} km",
+                hop.fly_to, long_haul.fly_from, transfer.distance_km
+            ),
+            None => format!(
+                "change airports from {} to {}",
+                hop.fly_to, long_haul.fly_from
+            ),
+        });
+    }
+
+    if let Some(country) = &hop.country_to
+        && home_country.is_none_or(|home| !home.eq_ignore_ascii_case(&country.code))
+    {
+        risks.push(format!(
+            "checking in again means entering {}: check its entry rules for your passport",
+            country.name
+        ));
+    }
+    risks
+}
+
+#[async_trait]
+impl ToolExecutor for SplitLongHaulTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing SplitLongHaulTool");
+        let args: SplitLongHaulArgs = input::parse(arguments)?;
+
+        let fly_from = args.fly_from.as_str();
+        let fly_to = args.fly_to.as_str();
+        let date_from = args.date_from.as_str();
+        let date_to = args.date_to.as_str();
+        let options = SearchOptions {
+            limit: CANDIDATES,
+            sort: "price".into(),
+            ..SearchOptions::from_args(&args.search, &self.defaults)?
+        };
+
+        let (home, destination) = futures::join!(self.locate(fly_from), self.continent(fly_to));
+        let origin = home
+            .as_ref()
+            .and_then(|home| locations::continent(home, fly_from));
+        let home_country = home
+            .as_ref()
+            .and_then(|home| locations::country(home, fly_from));
+        if let (Some(origin), Some(destination)) = (&origin, &destination)
+            && origin == destination
+        {
+            return Ok(vec![ToolContent::Text {
+                text: format!(
+                    "{} and {} are both in {}. Splitting through a long-haul gateway pays off on intercontinental trips; for this one try find_positioning_flights or a plain search.",
+                    fly_from, fly_to, origin
+                ),
+            }]);
+        }
+
+        let tried = if self.client.budget().is_tight() {
+            SPARING_HUBS
+        } else {
+            MAX_GATEWAYS
+        };
+        let gateways = match (args.gateways, &origin) {
+            (Some(gateways), _) => gateways,
+            (None, None) => {
+                return Err(KiwiToolError::validation(format!(
+                    "Couldn't tell which continent {} is on, so the gateways near it are unknown; pass some in gateways",
+                    fly_from
+                ))
+                .into());
+            }
+            (None, Some(_)) => {
+                // Only a gateway on the traveller's continent makes the hop
+                // there a short one. Looked up a few at a time, stopping
+                // once there are enough
+                let mut nearby = Vec::new();
+                for chunk in self.defaults.gateways.chunks(MAX_GATEWAYS) {
+                    if nearby.len() >= tried {
+                        break;
+                    }
+                    let continents =
+                        join_all(chunk.iter().map(|gateway| self.continent(gateway))).await;
+                    nearby.extend(
+                        chunk
+                            .iter()
+                            .zip(continents)
+                            .filter(|(gateway, continent)| {
+                                *continent == origin
+                                    && gateway.as_str() != fly_from
+                                    && gateway.as_str() != fly_to
+                            })
+                            .map(|(gateway, _)| gateway.clone()),
+                    );
+                }
+                nearby
+            }
+        };
+        let mut gateways: Vec<String> = gateways
+            .into_iter()
+            .filter(|gateway| gateway.as_str() != fly_from && gateway.as_str() != fly_to)
+            .collect();
+        if gateways.is_empty() {
+            return Err(KiwiToolError::validation(format!(
+                "No gateways to split through{}; pass some in gateways or add them to the configured gateways",
+                origin
+                    .as_ref()
+                    .map(|origin| format!(" in {}", origin))
+                    .unwrap_or_default()
+            ))
+            .into());
+        }
+
+        let skipped = gateways.split_off(gateways.len().min(tried));
+
+        log::info!(
+            "Searching for split tickets from {} to {} through {}",
+            fly_from,
+            fly_to,
+            gateways.join(", ")
+        );
+
+        let direct = cheapest(
+            &self.client,
+            vec![
+                ("fly_from", fly_from.to_string()),
+                ("fly_to", fly_to.to_string()),
+                ("date_from", date_from.to_string()),
+                ("date_to", date_to.to_string()),
+            ],
+            &options,
+        );
+        let search_options = &options;
+        let mut best: Option<f64> = None;
+        let split = join_with_progress(
+            self.client.progress(),
+            gateways.iter().map(|gateway| async move {
+                let found = via(
+                    &self.client,
+                    gateway,
+                    fly_from,
+                    fly_to,
+                    (date_from, date_to),
+                    search_options,
+                )
+                .await;
+                if let Err(err) = &found {
+                    log::warn!("Split search via {} failed: {}", gateway, err);
+                }
+                found.ok().flatten()
+            }),
+            |found: &Option<Trip>| {
+                if let Some(trip) = found {
+                    let total = trip.price();
+                    best = Some(best.map_or(total, |best| best.min(total)));
+                }
+                best.map(|best| format!("cheapest split so far {}", search_options.price(best)))
+            },
+        );
+        let (direct, split) = futures::join!(direct, split);
+
+        let direct = direct.unwrap_or_else(|err| {
+            log::warn!("Search from {} to {} failed: {}", fly_from, fly_to, err);
+            None
+        });
+        let mut trips: Vec<Trip> = split
+            .into_iter()
+            .flatten()
+            .map(|mut trip| {
+                trip.tickets[0].label = "Short hop".to_string();
+                trip
+            })
+            .collect();
+        trips.sort_by(|a, b| a.price().total_cmp(&b.price()));
+
+        let mut result = format!(
+            "Split tickets for {} → {}{} (prices in {}):\n\n",
+            fly_from,
+            fly_to,
+            match (&origin, &destination) {
+                (Some(origin), Some(destination)) => format!(", {} to {}", origin, destination),
+                _ => String::new(),
+            },
+            options.curr
+        );
+        match &direct {
+            Some(direct) => result.push_str(&format!(
+                "One ticket from {}: {}, {} ({})\n\n",
+                fly_from,
+                options.price_format.amount(direct.price),
+                options.duration(direct.duration.total),
+                direct.airlines.join(", ")
+            )),
+            None => result.push_str(&format!(
+                "One ticket from {}: no flights found\n\n",
+                fly_from
+            )),
+        }
+        if !skipped.is_empty() {
+            result.push_str(&format!(
+                "Skipped {} to keep the number of searches down; pass them in gateways to try them.\n\n",
+                skipped.join(", ")
+            ));
+        }
+
+        if trips.is_empty() {
+            result.push_str(&format!(
+                "No split options found through {}.\n",
+                gateways.join(", ")
+            ));
+            self.client.push_quota_warning(&mut result);
+            return Ok(vec![ToolContent::Text { text: result }]);
+        }
+        if let Some(direct) = &direct
+            && trips[0].price() >= direct.price
+        {
+            result.push_str(
+                "No split is cheaper than one ticket, which also protects the whole journey.\n\n",
+            );
+        }
+
+        result.push_str(&format_trips(&trips, &options, |number, trip| {
+            let total = trip.price();
+            let comparison = match &direct {
+                Some(direct) if total < direct.price => format!(
+                    ", saves {}",
+                    options.price_format.amount(direct.price - total)
+                ),
+                Some(direct) => format!(
+                    ", {} more",
+                    options.price_format.amount(total - direct.price)
+                ),
+                None => String::new(),
+            };
+            let mut heading = format!(
+                "Option {}: via {}, total {}{}\n",
+                number,
+                trip.tickets[1].itinerary.city_from,
+                options.price(total),
+                comparison
+            );
+            for risk in risks(trip, &options, home_country.as_deref()) {
+                heading.push_str(&format!("Risk: {}\n", risk));
+            }
+            heading.push('\n');
+            heading
+        }));
+
+        result.push_str("\nEvery split is two tickets: if the short hop is late or cancelled the long-haul airline does not have to rebook you, a change to one ticket doesn't carry over to the other, and bags must be collected and checked in again. Weigh the saving against the cost of missing the long-haul.\n");
+        options.push_notes(&mut result);
+        self.client.push_quota_warning(&mut result);
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "split_long_haul".into(),
+            description: Some(
+                "For intercontinental trips, compare one ticket with a cheap hop to a long-haul gateway on the traveller's continent plus a separate long-haul from there, listing the risks of each split"
+                    .into(),
+            ),
+            input_schema: input::schema::<SplitLongHaulArgs>(),
+        }
+    }
+}
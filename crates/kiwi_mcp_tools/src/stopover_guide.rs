@@ -0,0 +1,165 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use context_server::{
+    Prompt, PromptArgument, PromptContent, PromptExecutor, PromptMessage, PromptRole,
+};
+
+use crate::{
+    model::{Layover, format_local_time},
+    store::SearchStore,
+};
+
+/// Builds a prompt asking for advice on how to spend a layover from a
+/// previously returned itinerary.
+pub struct StopoverGuidePrompt {
+    store: Arc<SearchStore>,
+}
+
+impl StopoverGuidePrompt {
+    pub fn new(store: Arc<SearchStore>) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl PromptExecutor for StopoverGuidePrompt {
+    fn name(&self) -> &str {
+        "stopover_guide"
+    }
+
+    async fn compute(
+        &self,
+        arguments: Option<HashMap<String, String>>,
+    ) -> Result<Vec<PromptMessage>> {
+        let args = arguments.ok_or_else(|| anyhow!("Missing arguments"))?;
+
+        let argument = |name| {
+            args.get(name)
+                .map(|value: &String| value.trim())
+                .filter(|value| !value.is_empty())
+        };
+        let flight = argument("flight")
+            .map(|f| {
+                f.parse::<usize>()
+                    .map_err(|_| anyhow!("Invalid flight argument"))
+            })
+            .transpose()?;
+        let city = argument("city");
+
+        let (search, flight) =
+            self.store
+                .resolve(argument("reference"), argument("search_id"), flight)?;
+        let itinerary = search.itinerary(flight)?;
+        let layovers = itinerary.layovers();
+
+        let layover = match city {
+            Some(city) => layovers
+                .iter()
+                .find(|layover| {
+                    layover.arriving.city_to.eq_ignore_ascii_case(city)
+                        || layover.arriving.fly_to.eq_ignore_ascii_case(city)
+                })
+                .ok_or_else(|| anyhow!("Flight {} has no layover in {}", flight, city))?,
+            None => layovers
+                .iter()
+                .max_by_key(|layover| layover.duration)
+                .ok_or_else(|| anyhow!("Flight {} is a direct flight", flight))?,
+        };
+
+        Ok(vec![PromptMessage {
+            role: PromptRole::User,
+            content: PromptContent::Text {
+                text: describe_layover(layover),
+            },
+        }])
+    }
+
+    fn to_prompt(&self) -> Prompt {
+        Prompt {
+            name: "stopover_guide".into(),
+            description: Some(
+                "Suggest what is feasible during a layover of a flight from earlier search results"
+                    .into(),
+            ),
+            arguments: Some(vec![
+                PromptArgument {
+                    name: "reference".into(),
+                    description: Some(
+                        "Reference code shown with the flight, e.g. KW-7F3A91C2 (or give search_id and flight)"
+                            .into(),
+                    ),
+                    required: Some(false),
+                },
+                PromptArgument {
+                    name: "search_id".into(),
+                    description: Some("Search ID returned with the flight results".into()),
+                    required: Some(false),
+                },
+                PromptArgument {
+                    name: "flight".into(),
+                    description: Some("Number of the flight within those results".into()),
+                    required: Some(false),
+                },
+                PromptArgument {
+                    name: "city".into(),
+                    description: Some(
+                        "Layover city or airport code (defaults to the longest layover)".into(),
+                    ),
+                    required: Some(false),
+                },
+            ]),
+        }
+    }
+}
+
+fn describe_layover(layover: &Layover) -> String {
+    let arriving = layover.arriving;
+    let departing = layover.departing;
+    let hours = layover.duration.num_minutes() / 60;
+    let minutes = layover.duration.num_minutes() % 60;
+
+    let mut text = format!(
+        "I have a {}h {}m layover in {} ({}). I arrive on {} from {} at {} local time and leave on {} to {} at {} local time.\n",
+        hours,
+        minutes,
+        arriving.city_to,
+        arriving.fly_to,
+        arriving.flight(),
+        arriving.city_from,
+        arriving
+            .local_arrival
+            .as_deref()
+            .map(format_local_time)
+            .unwrap_or_else(|| "an unknown time".to_string()),
+        departing.flight(),
+        departing.city_to,
+        departing
+            .local_departure
+            .as_deref()
+            .map(format_local_time)
+            .unwrap_or_else(|| "an unknown time".to_string()),
+    );
+
+    if layover.changes_airport() {
+        text.push_str(&format!(
+            "The connection requires changing airports, from {} to {}.\n",
+            arriving.fly_to, departing.fly_from
+        ));
+    }
+    if arriving.bags_recheck_required {
+        text.push_str(
+            "This is a self-transfer: checked bags must be collected and checked in again.\n",
+        );
+    }
+
+    text.push_str(
+        "\nIs it feasible to leave the airport during this layover? Take into account immigration and transit visa rules, \
+         getting to and from the city centre, security screening on the way back and a safe buffer before the onward departure. \
+         If there is enough time, suggest what I could realistically see or do, with a rough schedule. \
+         If not, suggest how to make the most of the time at the airport.",
+    );
+
+    text
+}
@@ -0,0 +1,175 @@
+//! Where persisted data is read from and written to: the search history,
+//! saved searches and the on-disk response cache.
+//!
+//! Stores go through the [`Storage`] installed with [`set_storage`]. Native
+//! builds default to [`FileStorage`]; WebAssembly builds default to
+//! [`MemoryStorage`], as edge runtimes have no writable filesystem, and
+//! hosts with one (or with a key-value store) can install their own.
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Result;
+use parking_lot::{Mutex, RwLock};
+
+static STORAGE: RwLock<Option<Arc<dyn Storage>>> = RwLock::new(None);
+
+/// Files addressed by path.
+pub trait Storage: Send + Sync {
+    /// The contents of `path`, or `None` when there is no such file.
+    fn read(&self, path: &Path) -> Result<Option<Vec<u8>>>;
+
+    /// Replaces the contents of `path`. Readers never see a partial write.
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<()>;
+
+    /// Adds `contents` to the end of `path`, creating it if needed.
+    fn append(&self, path: &Path, contents: &[u8]) -> Result<()>;
+
+    /// Deletes `path`. A missing file is not an error.
+    fn remove(&self, path: &Path) -> Result<()>;
+
+    /// The files directly under `dir`.
+    fn list(&self, dir: &Path) -> Result<Vec<PathBuf>>;
+}
+
+/// Stores read and write through `storage` from now on.
+pub fn set_storage(storage: Arc<dyn Storage>) {
+    *STORAGE.write() = Some(storage);
+}
+
+/// The installed storage, or the platform default.
+pub fn current() -> Arc<dyn Storage> {
+    if let Some(storage) = STORAGE.read().as_ref() {
+        return storage.clone();
+    }
+
+    STORAGE.write().get_or_insert_with(default_storage).clone()
+}
+
+/// The contents of `path` as text, or `None` when there is no such file.
+pub fn read_to_string(path: &Path) -> Result<Option<String>> {
+    current()
+        .read(path)?
+        .map(|contents| Ok(String::from_utf8(contents)?))
+        .transpose()
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn default_storage() -> Arc<dyn Storage> {
+    Arc::new(FileStorage)
+}
+
+#[cfg(target_family = "wasm")]
+fn default_storage() -> Arc<dyn Storage> {
+    Arc::new(MemoryStorage::default())
+}
+
+/// The local filesystem.
+pub struct FileStorage;
+
+impl Storage for FileStorage {
+    fn read(&self, path: &Path) -> Result<Option<Vec<u8>>> {
+        match fs::read(path) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<()> {
+        create_parent(path)?;
+
+        // Write to a temporary file first and move it into place
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, contents)?;
+        fs::rename(&tmp, path)?;
+
+        Ok(())
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> Result<()> {
+        create_parent(path)?;
+
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(contents)?;
+
+        Ok(())
+    }
+
+    fn remove(&self, path: &Path) -> Result<()> {
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn list(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect())
+    }
+}
+
+fn create_parent(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(())
+}
+
+/// Files kept in memory, lost when the process exits.
+#[derive(Default)]
+pub struct MemoryStorage {
+    files: Mutex<BTreeMap<PathBuf, Vec<u8>>>,
+}
+
+impl Storage for MemoryStorage {
+    fn read(&self, path: &Path) -> Result<Option<Vec<u8>>> {
+        Ok(self.files.lock().get(path).cloned())
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<()> {
+        self.files
+            .lock()
+            .insert(path.to_path_buf(), contents.to_vec());
+        Ok(())
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> Result<()> {
+        self.files
+            .lock()
+            .entry(path.to_path_buf())
+            .or_default()
+            .extend_from_slice(contents);
+        Ok(())
+    }
+
+    fn remove(&self, path: &Path) -> Result<()> {
+        self.files.lock().remove(path);
+        Ok(())
+    }
+
+    fn list(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        Ok(self
+            .files
+            .lock()
+            .keys()
+            .filter(|path| path.parent() == Some(dir))
+            .cloned()
+            .collect())
+    }
+}
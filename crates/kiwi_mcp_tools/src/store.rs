@@ -0,0 +1,181 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+
+use crate::{
+    error::KiwiToolError,
+    model::{Itinerary, SearchResponse},
+    session,
+};
+
+/// How many result sets are kept per session for follow-up tools and
+/// prompts.
+const CAPACITY: usize = 50;
+
+/// A search whose results can be referred back to by ID.
+#[derive(Debug, Clone)]
+pub struct StoredSearch {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub params: Vec<(String, String)>,
+    pub currency: String,
+    pub itineraries: Vec<Itinerary>,
+    /// Only requests from this session can retrieve the results.
+    session: Option<String>,
+}
+
+impl StoredSearch {
+    /// Looks up an itinerary by its 1-based position in the results.
+    pub fn itinerary(&self, index: usize) -> Result<&Itinerary> {
+        index
+            .checked_sub(1)
+            .and_then(|i| self.itineraries.get(i))
+            .ok_or_else(|| {
+                KiwiToolError::validation(format!(
+                    "Search {} has {} results; there is no result {}",
+                    self.id,
+                    self.itineraries.len(),
+                    index
+                ))
+                .into()
+            })
+    }
+}
+
+/// Recent search results, keyed by a short ID shown to the model.
+#[derive(Default)]
+pub struct SearchStore {
+    next_id: AtomicU64,
+    searches: RwLock<VecDeque<StoredSearch>>,
+}
+
+impl SearchStore {
+    /// Stores the results and returns the ID they can be retrieved with.
+    pub fn insert(
+        &self,
+        params: &[(&str, String)],
+        currency: &str,
+        response: SearchResponse,
+    ) -> String {
+        let id = (self.next_id.fetch_add(1, Ordering::Relaxed) + 1).to_string();
+        let session = session::current();
+
+        let mut searches = self.searches.write();
+        searches.retain(|search| session::is_kept(search.session.as_deref()));
+        // Only this session's own oldest results make room
+        let stored = searches
+            .iter()
+            .filter(|search| search.session == session)
+            .count();
+        if stored >= CAPACITY
+            && let Some(oldest) = searches.iter().position(|search| search.session == session)
+        {
+            searches.remove(oldest);
+        }
+        searches.push_back(StoredSearch {
+            id: id.clone(),
+            created_at: Utc::now(),
+            params: params
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect(),
+            currency: response.currency.unwrap_or_else(|| currency.to_string()),
+            itineraries: response.data,
+            session,
+        });
+
+        id
+    }
+
+    /// Forgets every stored search. Returns how many there were.
+    pub fn clear(&self) -> usize {
+        std::mem::take(&mut *self.searches.write()).len()
+    }
+
+    /// Finds an itinerary by the reference code shown with it, in the most
+    /// recent search of the current session that returned it. Returns the
+    /// search and the itinerary's 1-based position in it. A code shared by
+    /// different itineraries is refused rather than guessed.
+    pub fn find_reference(&self, reference: &str) -> Result<(StoredSearch, usize)> {
+        let reference = reference.trim().to_ascii_uppercase();
+        let reference = match reference.strip_prefix("KW-") {
+            Some(_) => reference,
+            None => format!("KW-{}", reference),
+        };
+
+        let session = session::current();
+        let searches = self.searches.read();
+        let mut found: Option<(&StoredSearch, usize)> = None;
+        let mut identities = HashSet::new();
+        for search in searches
+            .iter()
+            .rev()
+            .filter(|search| search.session == session)
+        {
+            for (index, itinerary) in search.itineraries.iter().enumerate() {
+                if itinerary.reference() == reference {
+                    identities.insert(itinerary.identity());
+                    found.get_or_insert((search, index + 1));
+                }
+            }
+        }
+
+        if identities.len() > 1 {
+            return Err(KiwiToolError::validation(format!(
+                "Reference '{}' matches {} different itineraries; give the search ID and flight number instead",
+                reference,
+                identities.len()
+            ))
+            .into());
+        }
+        found
+            .map(|(search, index)| (search.clone(), index))
+            .ok_or_else(|| {
+                KiwiToolError::validation(format!(
+                    "Unknown itinerary reference '{}'; it may have expired, run the search again",
+                    reference
+                ))
+                .into()
+            })
+    }
+
+    /// The search and 1-based position of an itinerary given by its
+    /// reference code, or else by search ID and result number.
+    pub fn resolve(
+        &self,
+        reference: Option<&str>,
+        search_id: Option<&str>,
+        index: Option<usize>,
+    ) -> Result<(StoredSearch, usize)> {
+        match (reference, search_id, index) {
+            (Some(reference), _, _) => self.find_reference(reference),
+            (None, Some(search_id), Some(index)) => Ok((self.get(search_id)?, index)),
+            _ => Err(KiwiToolError::validation(
+                "Give the itinerary's reference code, or a search ID and flight number",
+            )
+            .into()),
+        }
+    }
+
+    /// Looks up results stored in the current session.
+    pub fn get(&self, id: &str) -> Result<StoredSearch> {
+        let session = session::current();
+        self.searches
+            .read()
+            .iter()
+            .find(|search| search.id == id && search.session == session)
+            .cloned()
+            .ok_or_else(|| {
+                KiwiToolError::validation(format!(
+                    "Unknown search ID '{}'; it may have expired, run the search again",
+                    id
+                ))
+                .into()
+            })
+    }
+}
@@ -0,0 +1,79 @@
+//! Ground transfers between airports serving the same city, for connections
+//! that arrive at one airport and leave from another.
+
+/// A transfer between two airports, valid in either direction.
+#[derive(Debug, Clone, Copy)]
+pub struct AirportTransfer {
+    pub airports: (&'static str, &'static str),
+    /// Road distance, in kilometres.
+    pub distance_km: u32,
+    /// Recommended minimum time between landing and the next departure.
+    pub min_connection_minutes: u32,
+}
+
+/// Used for airport changes that aren't in the table.
+pub const DEFAULT_MIN_CONNECTION_MINUTES: u32 = 180;
+
+const TRANSFERS: &[AirportTransfer] = &[
+    transfer("CDG", "ORY", 35, 180),
+    transfer("CDG", "BVA", 85, 270),
+    transfer("ORY", "BVA", 100, 270),
+    transfer("LHR", "LGW", 70, 240),
+    transfer("LHR", "STN", 80, 240),
+    transfer("LHR", "LTN", 60, 240),
+    transfer("LHR", "LCY", 40, 180),
+    transfer("LGW", "STN", 110, 270),
+    transfer("LGW", "LTN", 100, 270),
+    transfer("STN", "LTN", 60, 240),
+    transfer("FCO", "CIA", 30, 180),
+    transfer("MXP", "LIN", 50, 210),
+    transfer("MXP", "BGY", 70, 240),
+    transfer("LIN", "BGY", 50, 210),
+    transfer("IST", "SAW", 75, 270),
+    transfer("SVO", "DME", 80, 270),
+    transfer("SVO", "VKO", 60, 240),
+    transfer("DME", "VKO", 45, 210),
+    transfer("ARN", "BMA", 40, 180),
+    transfer("OSL", "TRF", 110, 270),
+    transfer("JFK", "LGA", 18, 180),
+    transfer("JFK", "EWR", 55, 240),
+    transfer("LGA", "EWR", 40, 210),
+    transfer("ORD", "MDW", 35, 180),
+    transfer("IAD", "DCA", 45, 180),
+    transfer("IAD", "BWI", 85, 240),
+    transfer("SFO", "OAK", 35, 180),
+    transfer("SFO", "SJC", 55, 210),
+    transfer("LAX", "BUR", 45, 180),
+    transfer("LAX", "LGB", 35, 180),
+    transfer("GRU", "CGH", 35, 210),
+    transfer("GIG", "SDU", 20, 150),
+    transfer("EZE", "AEP", 35, 180),
+    transfer("NRT", "HND", 75, 240),
+    transfer("KIX", "ITM", 50, 210),
+    transfer("ICN", "GMP", 45, 180),
+    transfer("PVG", "SHA", 50, 210),
+    transfer("PEK", "PKX", 70, 240),
+    transfer("DXB", "DWC", 60, 210),
+    transfer("BKK", "DMK", 45, 180),
+    transfer("KUL", "SZB", 60, 210),
+];
+
+const fn transfer(
+    from: &'static str,
+    to: &'static str,
+    distance_km: u32,
+    min_connection_minutes: u32,
+) -> AirportTransfer {
+    AirportTransfer {
+        airports: (from, to),
+        distance_km,
+        min_connection_minutes,
+    }
+}
+
+/// Looks up the transfer between two airports, in either direction.
+pub fn find(from: &str, to: &str) -> Option<&'static AirportTransfer> {
+    TRANSFERS
+        .iter()
+        .find(|t| t.airports == (from, to) || t.airports == (to, from))
+}
@@ -0,0 +1,86 @@
+//! Trips made of separately booked itineraries: self-connections,
+//! stopovers, positioning flights, open jaws and multi-leg plans. The tools
+//! that put them together build a [`Trip`], so totals, the time between
+//! tickets and the presentation (see [`crate::formatter`]) are the same
+//! everywhere.
+
+use chrono::Duration;
+
+use crate::{model::Itinerary, search::SearchOptions};
+
+/// One itinerary of a trip, booked on its own, and how it is labelled.
+#[derive(Debug, Clone)]
+pub struct Ticket {
+    pub label: String,
+    pub itinerary: Itinerary,
+}
+
+/// Separately booked itineraries, in the order they are flown.
+#[derive(Debug, Clone, Default)]
+pub struct Trip {
+    pub tickets: Vec<Ticket>,
+}
+
+impl Trip {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the next ticket of the trip.
+    pub fn then(mut self, label: impl Into<String>, itinerary: Itinerary) -> Self {
+        self.tickets.push(Ticket {
+            label: label.into(),
+            itinerary,
+        });
+        self
+    }
+
+    /// The price of every ticket together.
+    pub fn price(&self) -> f64 {
+        self.itineraries().map(|itinerary| itinerary.price).sum()
+    }
+
+    pub fn itineraries(&self) -> impl Iterator<Item = &Itinerary> {
+        self.tickets.iter().map(|ticket| &ticket.itinerary)
+    }
+
+    /// Whether each ticket departs after the one before it lands. Tickets
+    /// without times are assumed to fit.
+    pub fn is_in_order(&self) -> bool {
+        self.tickets.windows(2).all(|pair| {
+            match (
+                pair[0].itinerary.utc_arrival,
+                pair[1].itinerary.utc_departure,
+            ) {
+                (Some(arrival), Some(departure)) => departure > arrival,
+                _ => true,
+            }
+        })
+    }
+
+    /// The time between `ticket` (0-based) landing and the next one
+    /// leaving, when both leave from the same city and the times are known.
+    pub fn time_between(&self, ticket: usize) -> Option<Duration> {
+        let arriving = &self.tickets.get(ticket)?.itinerary;
+        let departing = &self.tickets.get(ticket + 1)?.itinerary;
+        if arriving.city_to != departing.city_from {
+            return None;
+        }
+        Some(departing.utc_departure? - arriving.utc_arrival?)
+    }
+}
+
+/// Trips one after the other in the search's output format, each under the
+/// heading `heading` gives it from its 1-based number.
+pub fn format_trips(
+    trips: &[Trip],
+    options: &SearchOptions,
+    heading: impl Fn(usize, &Trip) -> String,
+) -> String {
+    let headed: Vec<(String, &Trip)> = trips
+        .iter()
+        .enumerate()
+        .map(|(i, trip)| (heading(i + 1, trip).trim_end().to_string(), trip))
+        .collect();
+    options.formatter.format_trips(&headed, options)
+}
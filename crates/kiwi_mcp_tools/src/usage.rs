@@ -0,0 +1,161 @@
+//! In-process usage counters: calls, errors and time per tool, and the Kiwi
+//! API requests each tool made. Requests are attributed to the tool call
+//! they were made in; the background refresh makes the rest. Tool usage is
+//! counted per session, so HTTP clients each see their own.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use context_server::{Tool, ToolContent, ToolExecutor};
+use parking_lot::Mutex;
+use serde_json::Value;
+
+use crate::{KiwiClient, dry_run, session};
+
+tokio::task_local! {
+    /// The tool whose call is running on this task.
+    static CURRENT_TOOL: String;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ToolUsage {
+    pub calls: u64,
+    pub errors: u64,
+    pub total_time: Duration,
+    /// Requests sent to the Kiwi API.
+    pub api_requests: u64,
+    /// Requests answered from the response cache instead.
+    pub cache_hits: u64,
+}
+
+impl ToolUsage {
+    pub fn average_time(&self) -> Option<Duration> {
+        (self.calls > 0).then(|| self.total_time / self.calls as u32)
+    }
+}
+
+pub struct Usage {
+    /// Usage per tool, by session.
+    tools: Mutex<HashMap<Option<String>, BTreeMap<String, ToolUsage>>>,
+    /// Requests made outside any tool call.
+    background: Mutex<ToolUsage>,
+    since: Mutex<Instant>,
+}
+
+impl Default for Usage {
+    fn default() -> Self {
+        Self {
+            tools: Mutex::new(HashMap::new()),
+            background: Mutex::new(ToolUsage::default()),
+            since: Mutex::new(Instant::now()),
+        }
+    }
+}
+
+impl Usage {
+    /// Runs `call` as a call of `tool`, counting it and the requests it
+    /// makes.
+    pub async fn measure<T>(&self, tool: &str, call: impl Future<Output = Result<T>>) -> Result<T> {
+        let started = Instant::now();
+        let outcome = CURRENT_TOOL.scope(tool.to_string(), call).await;
+
+        let mut tools = self.tools.lock();
+        tools.retain(|session, _| session::is_kept(session.as_deref()));
+        let usage = tools
+            .entry(session::current())
+            .or_default()
+            .entry(tool.to_string())
+            .or_default();
+        usage.calls += 1;
+        usage.errors += u64::from(outcome.is_err());
+        usage.total_time += started.elapsed();
+
+        outcome
+    }
+
+    /// Counts a Kiwi API request, or a cache hit in its place, against the
+    /// tool call in progress.
+    pub fn record_request(&self, cached: bool) {
+        let count = |usage: &mut ToolUsage| {
+            if cached {
+                usage.cache_hits += 1;
+            } else {
+                usage.api_requests += 1;
+            }
+        };
+
+        let counted = CURRENT_TOOL.try_with(|tool| {
+            count(
+                self.tools
+                    .lock()
+                    .entry(session::current())
+                    .or_default()
+                    .entry(tool.clone())
+                    .or_default(),
+            );
+        });
+        if counted.is_err() {
+            count(&mut self.background.lock());
+        }
+    }
+
+    /// Usage per tool in the current session, and of requests made outside
+    /// tool calls.
+    pub fn snapshot(&self) -> (BTreeMap<String, ToolUsage>, ToolUsage) {
+        let tools = self
+            .tools
+            .lock()
+            .get(&session::current())
+            .cloned()
+            .unwrap_or_default();
+        (tools, *self.background.lock())
+    }
+
+    /// When counting started: at startup or the last [`Usage::reset`].
+    pub fn since(&self) -> Instant {
+        *self.since.lock()
+    }
+
+    pub fn reset(&self) {
+        self.tools.lock().clear();
+        *self.background.lock() = ToolUsage::default();
+        *self.since.lock() = Instant::now();
+    }
+}
+
+/// Wraps a tool so its calls are counted in the client's [`Usage`].
+pub struct Metered {
+    tool: Arc<dyn ToolExecutor>,
+    client: Arc<KiwiClient>,
+}
+
+impl Metered {
+    pub fn new(tool: Arc<dyn ToolExecutor>, client: Arc<KiwiClient>) -> Self {
+        Self { tool, client }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for Metered {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        // Nothing is sent on a dry run, so there is nothing to count
+        if dry_run::is_requested(arguments.as_ref()) {
+            return self.tool.execute(arguments).await;
+        }
+
+        self.client
+            .usage()
+            .measure(&self.tool.to_tool().name, self.tool.execute(arguments))
+            .await
+    }
+
+    fn to_tool(&self) -> Tool {
+        self.tool.to_tool()
+    }
+}
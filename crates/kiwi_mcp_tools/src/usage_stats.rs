@@ -0,0 +1,145 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use context_server::{Tool, ToolContent, ToolExecutor};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    KiwiClient,
+    format::format_duration_humanized,
+    input::{self, ToolInput},
+    usage::ToolUsage,
+};
+
+#[derive(Deserialize, JsonSchema)]
+struct UsageStatsArgs {
+    /// Start counting again from zero after this report (default false)
+    #[serde(default)]
+    reset: bool,
+}
+
+impl ToolInput for UsageStatsArgs {}
+
+/// Reports how often each tool was called, how long calls took and how many
+/// Kiwi API requests they cost, so users can see what their conversations
+/// spend of the API quota.
+pub struct UsageStatsTool {
+    client: Arc<KiwiClient>,
+}
+
+impl UsageStatsTool {
+    pub fn new(client: Arc<KiwiClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for UsageStatsTool {
+    async fn execute(&self, arguments: Option<Value>) -> Result<Vec<ToolContent>> {
+        log::debug!("Executing UsageStatsTool");
+        let args: UsageStatsArgs = input::parse(arguments)?;
+
+        let usage = self.client.usage();
+        let (tools, background) = usage.snapshot();
+        let since = format_duration_humanized(usage.since().elapsed().as_secs() as i64);
+
+        let mut result = format!("Usage over the last {}:\n\n", since);
+        if tools.is_empty() {
+            result.push_str("No tools have been called yet.\n");
+        }
+        for (name, usage) in &tools {
+            result.push_str(&format!(
+                "{}: {}, {}\n",
+                name,
+                calls(usage),
+                requests(usage)
+            ));
+        }
+
+        if background.api_requests + background.cache_hits > 0 {
+            result.push_str(&format!("Background refresh: {}\n", requests(&background)));
+        }
+
+        let all = || tools.values().chain([&background]);
+        let sent: u64 = all().map(|usage| usage.api_requests).sum();
+        let cached: u64 = all().map(|usage| usage.cache_hits).sum();
+        result.push_str(&format!(
+            "\nTotal: {} Kiwi API requests, and {} answered from the cache instead.\n",
+            sent, cached
+        ));
+
+        if let Some(quota) = self.client.quota() {
+            result.push_str(&format!(
+                "The API reports {}{} requests left in the current quota.\n",
+                quota.remaining,
+                quota
+                    .limit
+                    .map(|limit| format!(" of {}", limit))
+                    .unwrap_or_default()
+            ));
+        }
+
+        for budget in self.client.budget().uses() {
+            result.push_str(&format!(
+                "{} of the {} budget of {} requests used.\n",
+                budget.used, budget.period, budget.limit
+            ));
+        }
+
+        if args.reset {
+            usage.reset();
+            result.push_str("\nCounters reset.\n");
+        }
+
+        Ok(vec![ToolContent::Text { text: result }])
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            name: "usage_stats".into(),
+            description: Some(
+                "Show this server's usage: calls, failures and average time per tool, and the Kiwi API requests each made, to see how much API quota a conversation uses"
+                    .into(),
+            ),
+            input_schema: input::schema::<UsageStatsArgs>(),
+        }
+    }
+}
+
+/// `3 calls (1 failed), 2.4s on average`
+fn calls(usage: &ToolUsage) -> String {
+    let mut text = match usage.calls {
+        1 => "1 call".to_string(),
+        calls => format!("{} calls", calls),
+    };
+    if usage.errors > 0 {
+        text.push_str(&format!(" ({} failed)", usage.errors));
+    }
+    if let Some(average) = usage.average_time() {
+        text.push_str(&format!(", {} on average", format_latency(average)));
+    }
+    text
+}
+
+/// `12 API requests (3 more from the cache)`
+fn requests(usage: &ToolUsage) -> String {
+    let mut text = match usage.api_requests {
+        1 => "1 API request".to_string(),
+        requests => format!("{} API requests", requests),
+    };
+    if usage.cache_hits > 0 {
+        text.push_str(&format!(" ({} more from the cache)", usage.cache_hits));
+    }
+    text
+}
+
+fn format_latency(duration: Duration) -> String {
+    if duration < Duration::from_secs(1) {
+        format!("{}ms", duration.as_millis())
+    } else {
+        format!("{:.1}s", duration.as_secs_f64())
+    }
+}
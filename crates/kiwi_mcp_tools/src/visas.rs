@@ -0,0 +1,123 @@
+//! Bundled rules of thumb for transit and entry documents. They cover the
+//! countries travellers most often get caught out by and are informational
+//! only: requirements change and depend on more than nationality.
+
+use crate::model::Itinerary;
+
+struct Rule {
+    country: &'static str,
+    name: &'static str,
+    /// Airports the rule applies to when connecting there.
+    airports: &'static [&'static str],
+    /// Nationalities the rule applies to; empty means every nationality not
+    /// in `exempt`.
+    applies_to: &'static [&'static str],
+    exempt: &'static [&'static str],
+    transit: &'static str,
+    entry: &'static str,
+}
+
+const SCHENGEN_HUBS: &[&str] = &[
+    "AMS", "ATH", "BCN", "BRU", "CDG", "CPH", "FCO", "FRA", "HEL", "LIS", "MAD", "MUC", "MXP",
+    "ORY", "OSL", "PRG", "VIE", "WAW", "ZRH", "ARN", "DUS", "BER", "HAM", "GVA",
+];
+
+/// Schengen airport transit visa list (Annex IV of the Visa Code).
+const SCHENGEN_ATV: &[&str] = &[
+    "AF", "BD", "CD", "ER", "ET", "GH", "IQ", "IR", "LK", "NG", "PK", "SO",
+];
+
+const RULES: &[Rule] = &[
+    Rule {
+        country: "US",
+        name: "United States",
+        airports: &[
+            "ATL", "BOS", "DFW", "DEN", "EWR", "IAD", "IAH", "JFK", "LAX", "MIA", "ORD", "SEA",
+            "SFO",
+        ],
+        applies_to: &[],
+        exempt: &["US"],
+        transit: "there is no airside transit in the US; you clear immigration and need an ESTA or a visa even to connect",
+        entry: "an ESTA (Visa Waiver Program nationals) or a visa is required",
+    },
+    Rule {
+        country: "CA",
+        name: "Canada",
+        airports: &["YUL", "YVR", "YYC", "YYZ"],
+        applies_to: &[],
+        exempt: &["CA", "US"],
+        transit: "an eTA or a visa is usually required even to connect",
+        entry: "an eTA or a visa is required",
+    },
+    Rule {
+        country: "GB",
+        name: "United Kingdom",
+        airports: &["LGW", "LHR", "MAN"],
+        applies_to: &[],
+        exempt: &["GB", "IE"],
+        transit: "an ETA, or a Direct Airside Transit Visa for some nationalities, may be required to connect",
+        entry: "an ETA or a visa is required",
+    },
+    Rule {
+        country: "AU",
+        name: "Australia",
+        airports: &["BNE", "MEL", "PER", "SYD"],
+        applies_to: &[],
+        exempt: &["AU", "NZ"],
+        transit: "a transit visa is required unless you qualify for transit without visa",
+        entry: "an ETA, eVisitor or visa is required",
+    },
+    Rule {
+        country: "SCHENGEN",
+        name: "the Schengen Area",
+        airports: SCHENGEN_HUBS,
+        applies_to: SCHENGEN_ATV,
+        exempt: &[],
+        transit: "an airport transit visa is required to change planes",
+        entry: "a Schengen visa is required",
+    },
+];
+
+impl Rule {
+    fn applies(&self, nationality: &str) -> bool {
+        if self.applies_to.is_empty() {
+            !self.exempt.contains(&nationality)
+        } else {
+            self.applies_to.contains(&nationality)
+        }
+    }
+}
+
+/// Document hints for a passenger of `nationality` (ISO country code) taking
+/// the itinerary, one per country concerned.
+pub fn hints(nationality: &str, itinerary: &Itinerary) -> Vec<String> {
+    let nationality = nationality.to_uppercase();
+    let mut hints = Vec::new();
+
+    // Connections: every airport where one leg ends and the next begins
+    for layover in itinerary.layovers() {
+        let airport = layover.arriving.fly_to.as_str();
+        if let Some(rule) = RULES
+            .iter()
+            .find(|rule| rule.airports.contains(&airport) && rule.applies(&nationality))
+        {
+            let hint = format!(
+                "Connecting in {} ({}): {}",
+                rule.name, airport, rule.transit
+            );
+            if !hints.contains(&hint) {
+                hints.push(hint);
+            }
+        }
+    }
+
+    if let Some(country) = &itinerary.country_to
+        && let Some(rule) = RULES
+            .iter()
+            .find(|rule| rule.country == country.code && rule.applies(&nationality))
+    {
+        hints.push(format!("Entering {}: {}", rule.name, rule.entry));
+    }
+
+    hints
+}
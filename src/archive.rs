@@ -0,0 +1,58 @@
+use std::{fs, path::Path, sync::Arc};
+
+use anyhow::{Context, Result};
+use http_client::HttpClient;
+use kiwi_mcp_tools::{KiwiClient, StateArchive, config::Config};
+
+use crate::state::ContextServerState;
+
+/// Writes the persisted state to `file`, or to standard output.
+pub fn export(
+    http_client: Arc<dyn HttpClient>,
+    config_path: Option<&Path>,
+    file: Option<&Path>,
+) -> Result<()> {
+    let client = load_client(http_client, config_path)?;
+    let archive = serde_json::to_string_pretty(&StateArchive::export(&client))?;
+
+    match file {
+        Some(file) => {
+            fs::write(file, archive)
+                .with_context(|| format!("Failed to write {}", file.display()))?;
+            eprintln!("Exported to {}", file.display());
+        }
+        None => println!("{}", archive),
+    }
+
+    Ok(())
+}
+
+/// Adds the state in the archive at `file` to this machine's.
+pub fn import(
+    http_client: Arc<dyn HttpClient>,
+    config_path: Option<&Path>,
+    file: &Path,
+) -> Result<()> {
+    let contents =
+        fs::read_to_string(file).with_context(|| format!("Failed to read {}", file.display()))?;
+    let archive: StateArchive = serde_json::from_str(&contents)
+        .with_context(|| format!("{} is not a state archive", file.display()))?;
+
+    let client = load_client(http_client, config_path)?;
+    let summary = archive.import(&client)?;
+    eprintln!(
+        "Imported {} saved searches and {} history entries",
+        summary.bookmarks, summary.history
+    );
+
+    Ok(())
+}
+
+/// A client with the configured stores. No API requests are made, so a
+/// missing API key is fine.
+fn load_client(http_client: Arc<dyn HttpClient>, config_path: Option<&Path>) -> Result<KiwiClient> {
+    let config = Config::load(config_path)?;
+    let client = KiwiClient::new(http_client, config.api_key().unwrap_or_default());
+    ContextServerState::configure_client(&client, &config, false)?;
+    Ok(client)
+}
@@ -0,0 +1,131 @@
+use std::{env, fs, io, path::Path, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use http_client::HttpClient;
+use kiwi_mcp_tools::{KiwiClient, config::Config};
+
+const API_CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Runs a series of self-checks and prints a diagnosis. Exits with a non-zero
+/// status when any check fails.
+pub async fn run(http_client: Arc<dyn HttpClient>, config_path: Option<&Path>) -> Result<()> {
+    let mut report = Report::default();
+
+    let config = match Config::locate(config_path) {
+        Some(path) => match Config::from_file(&path) {
+            Ok(config) => {
+                report.pass("Configuration", format!("loaded {}", path.display()));
+                Some(config)
+            }
+            Err(err) => {
+                report.fail("Configuration", format!("{:#}", err));
+                None
+            }
+        },
+        None => {
+            let expected = Config::default_path()
+                .map(|path| format!(" (looked for {})", path.display()))
+                .unwrap_or_default();
+            match Config::load(None) {
+                Ok(config) => {
+                    report.pass(
+                        "Configuration",
+                        format!(
+                            "no config file, using defaults and KIWI_MCP_* variables{}",
+                            expected
+                        ),
+                    );
+                    Some(config)
+                }
+                Err(err) => {
+                    report.fail("Configuration", format!("{:#}", err));
+                    None
+                }
+            }
+        }
+    };
+
+    if let Some(config) = &config {
+        match config.cache.disk_dir() {
+            Some(dir) => match check_writable(&dir) {
+                Ok(()) => report.pass("Cache directory", format!("{} is writable", dir.display())),
+                Err(err) => report.fail("Cache directory", format!("{}: {}", dir.display(), err)),
+            },
+            None => report.pass("Cache directory", "on-disk cache disabled"),
+        }
+
+        match config.auth_token() {
+            Some(_) => report.pass("HTTP transport", "auth token set; admin tools available"),
+            None => report.pass(
+                "HTTP transport",
+                "no auth token; admin tools are only available over stdio",
+            ),
+        }
+    }
+
+    let api_key = config.as_ref().and_then(|config| config.api_key());
+    match &api_key {
+        Some(_) if env::var("KIWI_API_KEY").is_ok() => {
+            report.pass("API key", "set via KIWI_API_KEY")
+        }
+        Some(_) => report.pass("API key", "set in config file"),
+        None => report.fail(
+            "API key",
+            "not set; export KIWI_API_KEY or add `api_key` to the config file",
+        ),
+    }
+
+    match api_key {
+        Some(api_key) => {
+            let client = KiwiClient::new(http_client, api_key);
+            if let Some(config) = &config {
+                client.set_base_url(config.api.base_url.clone());
+                client.set_request_headers(config.api.request_headers());
+            }
+            match tokio::time::timeout(API_CHECK_TIMEOUT, client.locations_query("PRG", 1)).await {
+                Ok(Ok(_)) => report.pass("Kiwi API", "locations query succeeded"),
+                Ok(Err(err)) => report.fail("Kiwi API", format!("{:#}", err)),
+                Err(_) => report.fail(
+                    "Kiwi API",
+                    format!("no response within {}s", API_CHECK_TIMEOUT.as_secs()),
+                ),
+            }
+        }
+        None => report.skip("Kiwi API", "no API key to test with"),
+    }
+
+    if report.failed {
+        println!("\nSome checks failed.");
+        std::process::exit(1);
+    }
+
+    println!("\nAll checks passed.");
+    Ok(())
+}
+
+fn check_writable(dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let probe = dir.join(".doctor-probe");
+    fs::write(&probe, b"ok")?;
+    fs::remove_file(&probe)
+}
+
+#[derive(Default)]
+struct Report {
+    failed: bool,
+}
+
+impl Report {
+    fn pass(&mut self, check: &str, detail: impl AsRef<str>) {
+        println!("[ok]   {}: {}", check, detail.as_ref());
+    }
+
+    fn fail(&mut self, check: &str, detail: impl AsRef<str>) {
+        self.failed = true;
+        println!("[fail] {}: {}", check, detail.as_ref());
+    }
+
+    fn skip(&mut self, check: &str, detail: impl AsRef<str>) {
+        println!("[skip] {}: {}", check, detail.as_ref());
+    }
+}
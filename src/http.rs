@@ -0,0 +1,157 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use anyhow::Result;
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{HeaderMap, HeaderValue, StatusCode, header::AUTHORIZATION},
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use context_server::ContextServerRpcRequest;
+use kiwi_mcp_tools::{redact::redact, session};
+use tokio::net::TcpListener;
+
+use crate::{
+    ReloadSignal, ShutdownSignal,
+    state::{Access, ContextServerState},
+};
+
+/// Header identifying the MCP session a request belongs to.
+const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Serves JSON-RPC requests POSTed to `/mcp`. Requests carrying the
+/// configured bearer token get the admin tool group; everyone else only sees
+/// the public tools.
+///
+/// `initialize` hands out a session ID in the `Mcp-Session-Id` header, and
+/// every later request must send it back, so each client only sees its own
+/// search results and history. Requests without a session are refused with
+/// 400, and IDs the server never issued, or that ended, with 404. A DELETE
+/// with the header ends the session and drops what was kept for it.
+pub async fn serve(state: ContextServerState, addr: SocketAddr) -> Result<()> {
+    let state = Arc::new(state);
+
+    if state.auth_token().is_none() {
+        eprintln!("No auth token configured; admin tools are disabled over HTTP");
+    }
+
+    tokio::spawn(reload_on_signal(state.clone()));
+
+    let app = Router::new()
+        .route("/mcp", post(handle_request).delete(end_session))
+        .with_state(state.clone());
+
+    let listener = TcpListener::bind(addr).await?;
+    eprintln!("Listening on http://{}/mcp", listener.local_addr()?);
+
+    let idle_state = state.clone();
+    let mut shutdown_signal = ShutdownSignal::new()?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            tokio::select! {
+                _ = idle_state.idle_exit() => {
+                    eprintln!("No client activity within the idle timeout; exiting");
+                }
+                _ = shutdown_signal.recv() => eprintln!("Asked to shut down; exiting"),
+            }
+        })
+        .await?;
+    state.shutdown();
+
+    Ok(())
+}
+
+async fn handle_request(
+    State(state): State<Arc<ContextServerState>>,
+    headers: HeaderMap,
+    Json(request): Json<ContextServerRpcRequest>,
+) -> Response {
+    let access = match authorize(&headers, state.auth_token().as_deref()) {
+        Ok(access) => access,
+        Err(status) => return status.into_response(),
+    };
+
+    let mut new_session = None;
+    let session = match headers.get(SESSION_HEADER) {
+        Some(value) => match value.to_str() {
+            Ok(value) if session::touch(value) => value.to_string(),
+            Ok(_) => return StatusCode::NOT_FOUND.into_response(),
+            Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+        },
+        None if request.method == "initialize" => {
+            let session = session::new_id();
+            new_session = Some(session.clone());
+            session
+        }
+        None => return StatusCode::BAD_REQUEST.into_response(),
+    };
+
+    let response = session::scope(Some(session), state.process_request(request, access)).await;
+    let mut response = match response {
+        Ok(Some(response)) => Json(response).into_response(),
+        Ok(None) => StatusCode::ACCEPTED.into_response(),
+        Err(err) => {
+            eprintln!(
+                "Error processing request: {}",
+                redact(&format!("{:#}", err))
+            );
+            (StatusCode::INTERNAL_SERVER_ERROR, redact(&err.to_string())).into_response()
+        }
+    };
+
+    if let Some(session) = new_session
+        && let Ok(value) = HeaderValue::from_str(&session)
+    {
+        response.headers_mut().insert(SESSION_HEADER, value);
+    }
+    response
+}
+
+async fn end_session(headers: HeaderMap) -> StatusCode {
+    match headers.get(SESSION_HEADER).map(HeaderValue::to_str) {
+        Some(Ok(session)) if session::end(session) => StatusCode::NO_CONTENT,
+        Some(Ok(_)) => StatusCode::NOT_FOUND,
+        _ => StatusCode::BAD_REQUEST,
+    }
+}
+
+/// Requests without credentials are served as public. Presenting a token
+/// that doesn't match is rejected outright rather than silently downgraded.
+fn authorize(headers: &HeaderMap, auth_token: Option<&str>) -> Result<Access, StatusCode> {
+    let Some(header) = headers.get(AUTHORIZATION) else {
+        return Ok(Access::Public);
+    };
+
+    let presented = header
+        .to_str()
+        .ok()
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    match auth_token {
+        Some(expected) if constant_time_eq(presented.as_bytes(), expected.as_bytes()) => {
+            Ok(Access::Admin)
+        }
+        _ => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+async fn reload_on_signal(state: Arc<ContextServerState>) -> Result<()> {
+    let mut reload_signal = ReloadSignal::new()?;
+
+    loop {
+        reload_signal.recv().await;
+        match state.reload() {
+            Ok(()) => eprintln!("Configuration reloaded"),
+            Err(err) => eprintln!(
+                "Failed to reload configuration: {}",
+                redact(&format!("{:#}", err))
+            ),
+        }
+    }
+}
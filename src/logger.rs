@@ -0,0 +1,30 @@
+//! Writes the tools' error log to stderr, which stays clear of the stdio
+//! transport, with secrets redacted. Lower levels are left out: they are
+//! for debugging the library, and the server reports what users need
+//! itself.
+
+use kiwi_mcp_tools::redact::redact;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Error
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("{}", redact(&record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs the logger; later calls keep the first one.
+pub fn init() {
+    if log::set_logger(&StderrLogger).is_ok() {
+        log::set_max_level(LevelFilter::Error);
+    }
+}
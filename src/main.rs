@@ -1,78 +1,395 @@
-use std::{env, sync::Arc};
+mod archive;
+mod doctor;
+#[cfg(feature = "http")]
+mod http;
+mod logger;
+mod negotiation;
+mod repl;
+mod state;
+mod stdio;
+
+use std::{collections::VecDeque, net::SocketAddr, path::PathBuf, sync::Arc};
 
 use anyhow::Result;
-use context_server::{ContextServer, ContextServerRpcRequest, ContextServerRpcResponse};
-use context_server_utils::{
-    prompt_registry::PromptRegistry, resource_registry::ResourceRegistry,
-    tool_registry::ToolRegistry,
-};
-use http_client::HttpClient;
+use clap::{Parser, Subcommand};
+use context_server::ContextServerRpcRequest;
 use http_client_reqwest::HttpClientReqwest;
-use kiwi_mcp_tools::PlanTripTool;
-use tokio::io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader};
+use kiwi_mcp_tools::{config::Config, guard, paths, redact::redact};
+use serde_json::json;
+use tokio::{
+    io::{self, AsyncRead, AsyncWriteExt},
+    sync::mpsc,
+};
 
-struct ContextServerState {
-    rpc: ContextServer,
-}
+use crate::{
+    negotiation::{LogLevel, Negotiation},
+    state::{Access, ContextServerState},
+    stdio::MessageReader,
+};
 
-impl ContextServerState {
-    fn new(http_client: Arc<dyn HttpClient>) -> Result<Self> {
-        let resource_registry = Arc::new(ResourceRegistry::default());
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Path to the TOML configuration file
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
 
-        let tool_registry = Arc::new(ToolRegistry::default());
+    /// Keep the config file, data and cache under this directory instead of
+    /// the platform directories (also `KIWI_MCP_HOME`)
+    #[arg(long, global = true, value_name = "DIR")]
+    home: Option<PathBuf>,
 
-        tool_registry.register(Arc::new(PlanTripTool::new(http_client.clone())));
+    /// Don't persist anything (search history, on-disk cache) to disk
+    #[arg(long, global = true)]
+    ephemeral: bool,
 
-        let prompt_registry = Arc::new(PromptRegistry::default());
+    /// Threads running async tasks (overrides `runtime.worker_threads`)
+    #[arg(long, global = true, value_name = "N")]
+    worker_threads: Option<usize>,
 
-        Ok(Self {
-            rpc: ContextServer::builder()
-                .with_server_info((env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")))
-                .with_resources(resource_registry)
-                .with_tools(tool_registry)
-                .with_prompts(prompt_registry)
-                .build()?,
-        })
-    }
+    /// Most threads kept for blocking work (overrides
+    /// `runtime.max_blocking_threads`)
+    #[arg(long, global = true, value_name = "N")]
+    max_blocking_threads: Option<usize>,
 
-    async fn process_request(
-        &self,
-        request: ContextServerRpcRequest,
-    ) -> Result<Option<ContextServerRpcResponse>> {
-        self.rpc.handle_incoming_message(request).await
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Serve the MCP protocol over stdin/stdout (default) or HTTP
+    Serve {
+        /// Listen for HTTP requests on this address instead of using stdio
+        #[arg(long, value_name = "ADDR")]
+        http: Option<SocketAddr>,
+    },
+    /// Call tools interactively from the terminal
+    Repl,
+    /// Check configuration and connectivity to the Kiwi API
+    Doctor,
+    /// Write saved searches and the search history to a JSON archive
+    Export {
+        /// Archive to write (standard output when omitted)
+        file: Option<PathBuf>,
+    },
+    /// Add the saved searches and history from a JSON archive
+    Import {
+        /// Archive written by `export`
+        file: PathBuf,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    logger::init();
+    guard::install_panic_hook();
+    paths::set_root(
+        cli.home
+            .clone()
+            .or_else(|| std::env::var_os("KIWI_MCP_HOME").map(PathBuf::from)),
+    );
+
+    // A broken config file is reported once the server (or doctor) reads it
+    let config = Config::load(cli.config.as_deref()).ok();
+    let runtime = config
+        .as_ref()
+        .map(|config| config.runtime.clone())
+        .unwrap_or_default();
+    let listen = config.and_then(|config| config.http.listen);
+
+    let mut builder = tokio::runtime::Builder::new_multi_thread();
+    builder.enable_all();
+    if let Some(threads) = cli.worker_threads.or(runtime.worker_threads) {
+        builder.worker_threads(threads.max(1));
+    }
+    if let Some(threads) = cli.max_blocking_threads.or(runtime.max_blocking_threads) {
+        builder.max_blocking_threads(threads.max(1));
     }
+
+    builder.build()?.block_on(run(cli, listen))
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+/// `listen` is the configured HTTP address, used when `serve` is given none.
+async fn run(cli: Cli, listen: Option<SocketAddr>) -> Result<()> {
     let http_client = Arc::new(HttpClientReqwest::default());
 
-    if env::var("KIWI_API_KEY").is_err() {
-        eprintln!("KIWI_API_KEY environment variable is required");
-        std::process::exit(1);
+    let command = cli.command.unwrap_or(Command::Serve { http: None });
+    match command {
+        Command::Doctor => return doctor::run(http_client, cli.config.as_deref()).await,
+        Command::Export { file } => {
+            return archive::export(http_client, cli.config.as_deref(), file.as_deref());
+        }
+        Command::Import { file } => {
+            return archive::import(http_client, cli.config.as_deref(), &file);
+        }
+        _ => {}
+    }
+
+    let state = ContextServerState::new(http_client, cli.config, cli.ephemeral)?;
+
+    match command {
+        Command::Serve { http } => match http.or(listen) {
+            #[cfg(feature = "http")]
+            Some(addr) => http::serve(state, addr).await,
+            #[cfg(not(feature = "http"))]
+            Some(_) => Err(anyhow::anyhow!(
+                "This build has no HTTP transport; rebuild with the `http` feature"
+            )),
+            None => serve(state).await,
+        },
+        Command::Repl => repl::run(state).await,
+        Command::Doctor | Command::Export { .. } | Command::Import { .. } => {
+            unreachable!("handled above")
+        }
     }
+}
 
-    let state = ContextServerState::new(http_client)?;
+/// Serves a single trusted client over stdio, which gets the admin tools too.
+/// The server exits cleanly when the client closes the pipe, even in the
+/// middle of a response, or asks it to stop with a signal or console event,
+/// cancelling the request and background work.
+async fn serve(state: ContextServerState) -> Result<()> {
+    let result = serve_stdio(&state).await;
+    state.shutdown();
+
+    match result {
+        Err(err) if is_disconnect(&err) => {
+            eprintln!("Client closed the connection");
+            Ok(())
+        }
+        result => result,
+    }
+}
 
-    let mut stdin = BufReader::new(io::stdin()).lines();
+async fn serve_stdio(state: &ContextServerState) -> Result<()> {
+    let mut stdin = MessageReader::new(io::stdin());
     let mut stdout = io::stdout();
+    let mut reload_signal = ReloadSignal::new()?;
+    let mut shutdown_signal = ShutdownSignal::new()?;
+
+    let (progress_sender, mut progress) = mpsc::unbounded_channel();
+    state.progress().connect(progress_sender);
+    let mut negotiation = Negotiation::default();
+    let idle_exit = state.idle_exit();
+    tokio::pin!(idle_exit);
+
+    // Messages that arrived while a request was running
+    let mut pending: VecDeque<String> = VecDeque::new();
+
+    loop {
+        tokio::select! {
+            line = next_message(&mut stdin, &mut pending) => {
+                let Some(line) = line? else {
+                    break;
+                };
+
+                let request: ContextServerRpcRequest = match serde_json::from_str(&line) {
+                    Ok(req) => req,
+                    Err(e) => {
+                        eprintln!("Error parsing request: {}", redact(&e.to_string()));
+                        continue;
+                    }
+                };
+
+                negotiation.observe(&request);
+                if let Some(response) = negotiation.handle(&request) {
+                    write_message(&mut stdout, &response.to_string()).await?;
+                    continue;
+                }
+                let is_initialize = request.method == "initialize";
+
+                // Forward progress while the request runs, for clients that
+                // asked for it with a progress token
+                let token = request
+                    .params
+                    .as_ref()
+                    .and_then(|params| params.pointer("/_meta/progressToken"))
+                    .cloned();
+                state.progress().begin(token);
 
-    while let Some(line) = stdin.next_line().await? {
-        let request: ContextServerRpcRequest = match serde_json::from_str(&line) {
-            Ok(req) => req,
-            Err(e) => {
-                eprintln!("Error parsing request: {}", e);
-                continue;
+                let processing = state.process_request(request, Access::Admin);
+                tokio::pin!(processing);
+                let response = loop {
+                    tokio::select! {
+                        response = &mut processing => break response,
+                        Some(notification) = progress.recv() => {
+                            let notification = negotiation.progress(notification);
+                            write_message(&mut stdout, &notification.to_string()).await?;
+                        }
+                        // Dropping `processing` on the way out cancels it
+                        line = stdin.next_line() => match line? {
+                            Some(line) => pending.push_back(line),
+                            None => {
+                                eprintln!("Client closed the connection; cancelling the running request");
+                                return Ok(());
+                            }
+                        },
+                        _ = shutdown_signal.recv() => {
+                            eprintln!("Asked to shut down; cancelling the running request");
+                            return Ok(());
+                        }
+                    }
+                };
+                state.progress().end();
+                while let Ok(notification) = progress.try_recv() {
+                    let notification = negotiation.progress(notification);
+                    write_message(&mut stdout, &notification.to_string()).await?;
+                }
+
+                let mut response = response?;
+                if is_initialize {
+                    response = response
+                        .map(|response| negotiation.extend_initialize(response))
+                        .transpose()?;
+                }
+                if let Some(response) = response {
+                    write_message(&mut stdout, &serde_json::to_string(&response)?).await?;
+                }
+            }
+            // Notifications from the background refresh, between requests
+            Some(notification) = progress.recv() => {
+                if negotiation.is_initialized() {
+                    let notification = negotiation.progress(notification);
+                    write_message(&mut stdout, &notification.to_string()).await?;
+                }
+            }
+            _ = &mut idle_exit => {
+                eprintln!("No client activity within the idle timeout; exiting");
+                break;
             }
-        };
+            _ = shutdown_signal.recv() => {
+                eprintln!("Asked to shut down; exiting");
+                break;
+            }
+            _ = reload_signal.recv() => {
+                let (level, message) = match state.reload() {
+                    Ok(()) => (LogLevel::Info, "Configuration reloaded".to_string()),
+                    Err(err) => (
+                        LogLevel::Error,
+                        format!("Failed to reload configuration: {}", redact(&format!("{:#}", err))),
+                    ),
+                };
+                eprintln!("{}", message);
 
-        if let Some(response) = state.process_request(request).await? {
-            let response_json = serde_json::to_string(&response)?;
-            stdout.write_all(response_json.as_bytes()).await?;
-            stdout.write_all(b"\n").await?;
-            stdout.flush().await?;
+                if let Some(notification) = negotiation.log(level, &message) {
+                    write_message(&mut stdout, &notification.to_string()).await?;
+                }
+                if level == LogLevel::Info && negotiation.is_initialized() {
+                    let notification = json!({
+                        "jsonrpc": "2.0",
+                        "method": "notifications/tools/list_changed"
+                    });
+                    write_message(&mut stdout, &notification.to_string()).await?;
+                }
+            }
         }
     }
 
     Ok(())
 }
+
+/// The next message queued while a request ran, or else the next from stdin.
+async fn next_message<R: AsyncRead + Unpin>(
+    stdin: &mut MessageReader<R>,
+    pending: &mut VecDeque<String>,
+) -> Result<Option<String>> {
+    match pending.pop_front() {
+        Some(line) => Ok(Some(line)),
+        None => stdin.next_line().await,
+    }
+}
+
+/// Whether `err` means the client has stopped reading our output.
+fn is_disconnect(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<std::io::Error>().is_some_and(|err| {
+        matches!(
+            err.kind(),
+            std::io::ErrorKind::BrokenPipe | std::io::ErrorKind::ConnectionReset
+        )
+    })
+}
+
+async fn write_message(stdout: &mut io::Stdout, message: &str) -> Result<()> {
+    stdout.write_all(message.as_bytes()).await?;
+    stdout.write_all(b"\n").await?;
+    stdout.flush().await?;
+    Ok(())
+}
+
+/// Resolves every time the process receives SIGHUP. Never resolves on
+/// platforms without it.
+struct ReloadSignal {
+    #[cfg(unix)]
+    hangup: tokio::signal::unix::Signal,
+}
+
+impl ReloadSignal {
+    fn new() -> Result<Self> {
+        Ok(Self {
+            #[cfg(unix)]
+            hangup: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?,
+        })
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        self.hangup.recv().await;
+        #[cfg(not(unix))]
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Resolves when the process is asked to stop: SIGTERM or SIGINT on Unix, and
+/// Ctrl+C, Ctrl+Break, closing the console window, logoff or system shutdown
+/// on Windows, which has no SIGTERM.
+struct ShutdownSignal {
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    interrupt: tokio::signal::unix::Signal,
+    #[cfg(windows)]
+    ctrl_c: tokio::signal::windows::CtrlC,
+    #[cfg(windows)]
+    ctrl_break: tokio::signal::windows::CtrlBreak,
+    #[cfg(windows)]
+    ctrl_close: tokio::signal::windows::CtrlClose,
+    #[cfg(windows)]
+    ctrl_shutdown: tokio::signal::windows::CtrlShutdown,
+}
+
+impl ShutdownSignal {
+    fn new() -> Result<Self> {
+        Ok(Self {
+            #[cfg(unix)]
+            terminate: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?,
+            #[cfg(unix)]
+            interrupt: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::interrupt())?,
+            #[cfg(windows)]
+            ctrl_c: tokio::signal::windows::ctrl_c()?,
+            #[cfg(windows)]
+            ctrl_break: tokio::signal::windows::ctrl_break()?,
+            #[cfg(windows)]
+            ctrl_close: tokio::signal::windows::ctrl_close()?,
+            #[cfg(windows)]
+            ctrl_shutdown: tokio::signal::windows::ctrl_shutdown()?,
+        })
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        tokio::select! {
+            _ = self.terminate.recv() => {}
+            _ = self.interrupt.recv() => {}
+        }
+        #[cfg(windows)]
+        tokio::select! {
+            _ = self.ctrl_c.recv() => {}
+            _ = self.ctrl_break.recv() => {}
+            _ = self.ctrl_close.recv() => {}
+            _ = self.ctrl_shutdown.recv() => {}
+        }
+        #[cfg(not(any(unix, windows)))]
+        std::future::pending::<()>().await;
+    }
+}
@@ -0,0 +1,161 @@
+use anyhow::{Result, anyhow};
+use context_server::{ContextServerRpcRequest, ContextServerRpcResponse};
+use serde_json::{Value, json};
+
+/// First protocol version whose progress notifications carry a `message`.
+const PROGRESS_MESSAGE_VERSION: &str = "2025-03-26";
+
+/// Severity of a `notifications/message` log entry, as defined by MCP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+}
+
+impl LogLevel {
+    fn parse(level: &str) -> Result<Self> {
+        match level {
+            "debug" => Ok(Self::Debug),
+            "info" => Ok(Self::Info),
+            "notice" => Ok(Self::Notice),
+            "warning" => Ok(Self::Warning),
+            // Anything more severe is reported like an error
+            "error" | "critical" | "alert" | "emergency" => Ok(Self::Error),
+            _ => Err(anyhow!("Unknown log level '{}'", level)),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Notice => "notice",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// Optional protocol features agreed with the stdio client during
+/// `initialize`. Each is used only once the client has shown it can handle
+/// it; otherwise the server falls back to stderr or leaves it out.
+#[derive(Debug, Default)]
+pub struct Negotiation {
+    /// Protocol version the client asked for.
+    protocol_version: Option<String>,
+    /// Set once the client sends `notifications/initialized`.
+    initialized: bool,
+    /// Log messages at or above this level are sent to the client, after it
+    /// asked for them with `logging/setLevel`.
+    log_level: Option<LogLevel>,
+}
+
+impl Negotiation {
+    /// Notes what the client declares in `initialize` and
+    /// `notifications/initialized`.
+    pub fn observe(&mut self, request: &ContextServerRpcRequest) {
+        match request.method.as_str() {
+            "initialize" => {
+                *self = Self {
+                    protocol_version: request
+                        .params
+                        .as_ref()
+                        .and_then(|params| params.get("protocolVersion"))
+                        .and_then(Value::as_str)
+                        .map(String::from),
+                    ..Self::default()
+                };
+            }
+            "notifications/initialized" => self.initialized = true,
+            _ => {}
+        }
+    }
+
+    /// Answers `logging/setLevel`, which the server handles itself. Returns
+    /// `None` for every other request.
+    pub fn handle(&mut self, request: &ContextServerRpcRequest) -> Option<Value> {
+        if request.method != "logging/setLevel" {
+            return None;
+        }
+
+        let level = request
+            .params
+            .as_ref()
+            .and_then(|params| params.get("level"))
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+
+        Some(match LogLevel::parse(level) {
+            Ok(level) => {
+                self.log_level = Some(level);
+                json!({ "jsonrpc": "2.0", "id": request.id, "result": {} })
+            }
+            Err(err) => json!({
+                "jsonrpc": "2.0",
+                "id": request.id,
+                "error": { "code": -32602, "message": err.to_string() }
+            }),
+        })
+    }
+
+    /// Adds the optional capabilities this transport supports to the
+    /// `initialize` response.
+    pub fn extend_initialize(
+        &self,
+        response: ContextServerRpcResponse,
+    ) -> Result<ContextServerRpcResponse> {
+        let mut response = serde_json::to_value(response)?;
+        if let Some(capabilities) = response
+            .pointer_mut("/result/capabilities")
+            .and_then(Value::as_object_mut)
+        {
+            capabilities.insert("logging".into(), json!({}));
+            if let Some(tools) = capabilities.get_mut("tools").and_then(Value::as_object_mut) {
+                tools.insert("listChanged".into(), json!(true));
+            }
+        }
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Whether the client is ready for server-initiated notifications.
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    /// A progress notification in the form the client's protocol version
+    /// understands.
+    pub fn progress(&self, mut notification: Value) -> Value {
+        let supports_message = self
+            .protocol_version
+            .as_deref()
+            .is_some_and(|version| version >= PROGRESS_MESSAGE_VERSION);
+        if !supports_message
+            && let Some(params) = notification
+                .get_mut("params")
+                .and_then(Value::as_object_mut)
+        {
+            params.remove("message");
+        }
+        notification
+    }
+
+    /// A `notifications/message` for `message`, when the client asked for
+    /// log messages at this level.
+    pub fn log(&self, level: LogLevel, message: &str) -> Option<Value> {
+        let wanted = self.initialized && self.log_level.is_some_and(|min| level >= min);
+        wanted.then(|| {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/message",
+                "params": {
+                    "level": level.name(),
+                    "logger": env!("CARGO_PKG_NAME"),
+                    "data": message
+                }
+            })
+        })
+    }
+}
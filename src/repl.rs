@@ -0,0 +1,184 @@
+use anyhow::{Result, anyhow};
+use context_server::ContextServerRpcRequest;
+use kiwi_mcp_tools::redact::redact;
+use serde_json::{Value, json};
+use tokio::io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::state::{Access, ContextServerState};
+
+const HELP: &str = "\
+Commands:
+  tools                      List the registered tools
+  reload                     Re-read the configuration file
+  <tool> [json arguments]    Call a tool, e.g. plan_trip {\"fly_from\": \"LON\", ...}
+  help                       Show this message
+  exit                       Leave the REPL";
+
+/// Reads `<tool> <json>` lines from the terminal, runs them through the same
+/// RPC handler the stdio transport uses and pretty-prints the result.
+pub async fn run(state: ContextServerState) -> Result<()> {
+    let mut session = Session { state, next_id: 0 };
+    session.initialize().await?;
+
+    let mut stdin = BufReader::new(io::stdin()).lines();
+    let mut stdout = io::stdout();
+
+    println!(
+        "{} {} REPL. Type `help` for commands.",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    );
+
+    loop {
+        stdout.write_all(b"kiwi> ").await?;
+        stdout.flush().await?;
+
+        let Some(line) = stdin.next_line().await? else {
+            break;
+        };
+        let line = line.trim();
+
+        let (command, rest) = line
+            .split_once(char::is_whitespace)
+            .map(|(command, rest)| (command, rest.trim()))
+            .unwrap_or((line, ""));
+
+        let outcome = match command {
+            "" => continue,
+            "exit" | "quit" => break,
+            "help" => {
+                println!("{}", HELP);
+                continue;
+            }
+            "tools" => session.list_tools().await,
+            "reload" => session.state.reload(),
+            tool => session.call_tool(tool, rest).await,
+        };
+
+        if let Err(err) = outcome {
+            eprintln!("error: {}", redact(&err.to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+struct Session {
+    state: ContextServerState,
+    next_id: u64,
+}
+
+impl Session {
+    async fn initialize(&mut self) -> Result<()> {
+        self.send(
+            "initialize",
+            json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": {},
+                "clientInfo": {
+                    "name": concat!(env!("CARGO_PKG_NAME"), "-repl"),
+                    "version": env!("CARGO_PKG_VERSION")
+                }
+            }),
+        )
+        .await?;
+
+        let notification = serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/initialized"
+        }))?;
+        self.state
+            .process_request(notification, Access::Admin)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn list_tools(&mut self) -> Result<()> {
+        let result = self.send("tools/list", json!({})).await?;
+
+        let tools = result
+            .get("tools")
+            .and_then(|t| t.as_array())
+            .ok_or_else(|| anyhow!("Unexpected tools/list response: {}", result))?;
+
+        for tool in tools {
+            let name = tool.get("name").and_then(|n| n.as_str()).unwrap_or("?");
+            let description = tool
+                .get("description")
+                .and_then(|d| d.as_str())
+                .unwrap_or("");
+            println!("{:<24} {}", name, description);
+        }
+
+        Ok(())
+    }
+
+    async fn call_tool(&mut self, name: &str, arguments: &str) -> Result<()> {
+        let arguments: Value = if arguments.is_empty() {
+            json!({})
+        } else {
+            serde_json::from_str(arguments)
+                .map_err(|err| anyhow!("Arguments must be a JSON object: {}", err))?
+        };
+
+        let result = self
+            .send(
+                "tools/call",
+                json!({
+                    "name": name,
+                    "arguments": arguments
+                }),
+            )
+            .await?;
+
+        if result.get("isError").and_then(|e| e.as_bool()) == Some(true) {
+            eprintln!("Tool reported an error:");
+        }
+
+        match result.get("content").and_then(|c| c.as_array()) {
+            Some(content) => {
+                for item in content {
+                    match item.get("text").and_then(|t| t.as_str()) {
+                        Some(text) => println!("{}", text),
+                        None => println!("{}", serde_json::to_string_pretty(item)?),
+                    }
+                }
+            }
+            None => println!("{}", serde_json::to_string_pretty(&result)?),
+        }
+
+        Ok(())
+    }
+
+    async fn send(&mut self, method: &str, params: Value) -> Result<Value> {
+        self.next_id += 1;
+
+        let request: ContextServerRpcRequest = serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id,
+            "method": method,
+            "params": params
+        }))?;
+
+        let response = self
+            .state
+            .process_request(request, Access::Admin)
+            .await?
+            .ok_or_else(|| anyhow!("No response to {}", method))?;
+        let response = serde_json::to_value(response)?;
+
+        if let Some(error) = response.get("error").filter(|e| !e.is_null()) {
+            return Err(anyhow!(
+                "{}",
+                error
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .map(String::from)
+                    .unwrap_or_else(|| error.to_string())
+            ));
+        }
+
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+}
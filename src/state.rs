@@ -0,0 +1,513 @@
+use std::{
+    panic::AssertUnwindSafe,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result, anyhow};
+use context_server::{
+    ContextServer, ContextServerRpcRequest, ContextServerRpcResponse, PromptExecutor,
+    ResourceExecutor, ToolExecutor,
+};
+use context_server_utils::{
+    prompt_registry::PromptRegistry, resource_registry::ResourceRegistry,
+    tool_registry::ToolRegistry,
+};
+use futures::FutureExt;
+use http_client::HttpClient;
+use kiwi_mcp_tools::{
+    AirportDetailsTool, BestDepartureTimesTool, BookingAdviceTool, CacheClearTool, CacheStatsTool,
+    CheapestMonthTool, CityAirportsTool, CompareBaggageFaresTool, CompareCabinsTool,
+    CompareSearchesTool, DayOfWeekPricesTool, ExportTripSummaryTool, FileAirlineQuality,
+    HasDirectFlightsTool, HistoryResource, HolidaySurchargesTool, KiwiClient,
+    ListSavedSearchesTool, PlanOpenJawTripTool, PlanTripBudgetTool, PlanTripTool,
+    PlanTripWithStopoverTool, PositioningFlightsTool, Prefetcher, ProgressHub, PurgeUserDataTool,
+    RecheckSavedSearchTool, RotateApiKeyTool, RouteGeoJsonResource, RouteStatsTool, SaveSearchTool,
+    SavedSearchDigestResource, SavingsFinderTool, SearchAirlinesTool, SearchHistoryTool,
+    SearchStore, SplitLongHaulTool, StopoverGuidePrompt, UsageStatsTool,
+    config::{Config, IdleAction, IdleConfig},
+    diagnostics,
+    dry_run::DryRunnable,
+    encryption::{self, Cipher},
+    guard::Guarded,
+    redact::{self, RedactErrors},
+    storage,
+    usage::Metered,
+};
+use parking_lot::{Mutex, RwLock};
+use serde_json::json;
+
+const MISSING_API_KEY: &str = "KIWI_API_KEY environment variable is required";
+
+/// JSON-RPC error code for a failure inside the server.
+const INTERNAL_ERROR: i64 = -32603;
+
+/// Public tools that change saved state without calling the API, which a
+/// dry run couldn't hold back. Admin tools don't take `dry_run` either.
+const SAVES_STATE: &[&str] = &["save_search", "usage_stats"];
+
+/// How often the idle timeout is checked.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// What a caller is allowed to see. Admin sessions additionally get the
+/// admin tool group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// Only HTTP clients without the auth token get public access.
+    #[cfg_attr(not(feature = "http"), allow(dead_code))]
+    Public,
+    Admin,
+}
+
+pub struct ContextServerState {
+    client: Arc<KiwiClient>,
+    store: Arc<SearchStore>,
+    config_path: Option<PathBuf>,
+    ephemeral: bool,
+    auth_token: RwLock<Option<String>>,
+    servers: RwLock<Servers>,
+    prefetcher: Arc<Prefetcher>,
+    idle: RwLock<IdleConfig>,
+    last_activity: Mutex<Instant>,
+}
+
+#[derive(Clone)]
+struct Servers {
+    public: Arc<ContextServer>,
+    admin: Arc<ContextServer>,
+}
+
+impl ContextServerState {
+    /// With `ephemeral` set nothing is written to disk, whatever the
+    /// configuration says.
+    pub fn new(
+        http_client: Arc<dyn HttpClient>,
+        config_path: Option<PathBuf>,
+        ephemeral: bool,
+    ) -> Result<Self> {
+        let config = Config::load(config_path.as_deref())?;
+        let api_key = config.api_key().ok_or_else(|| anyhow!(MISSING_API_KEY))?;
+
+        let client = Arc::new(KiwiClient::new(http_client, api_key));
+        Self::configure_client(&client, &config, ephemeral)?;
+
+        let store = Arc::new(SearchStore::default());
+
+        let servers = Self::build_servers(&client, &store, &config)?;
+
+        let prefetcher = Prefetcher::new(client.clone(), config.prefetch.clone());
+        prefetcher.set_notifications(
+            config.notifications.clone(),
+            config.defaults.price_format()?,
+        );
+        prefetcher.spawn();
+
+        Ok(Self {
+            client,
+            store,
+            config_path,
+            ephemeral,
+            auth_token: RwLock::new(Self::registered_auth_token(&config)),
+            servers: RwLock::new(servers),
+            prefetcher,
+            idle: RwLock::new(config.idle.clone()),
+            last_activity: Mutex::new(Instant::now()),
+        })
+    }
+
+    /// Applies `config` to `client`. With `ephemeral` set nothing is read
+    /// from or written to disk. The settings that can be wrong are checked
+    /// first, so a bad one is refused before anything changes.
+    pub fn configure_client(client: &KiwiClient, config: &Config, ephemeral: bool) -> Result<()> {
+        let cipher = Self::check(config, ephemeral)?;
+
+        client.set_rate_limit(config.rate_limit.requests_per_minute);
+        client.set_cache_ttl(Duration::from_secs(config.cache.ttl_secs));
+        client.set_cache_max_ttl(Duration::from_secs(config.cache.max_ttl_secs));
+        client.set_cache_max_disk_size(config.cache.max_disk_mb * 1024 * 1024);
+        client.set_base_url(config.api.base_url.clone());
+        client.set_budget(config.budget.clone());
+        client
+            .bookmarks()
+            .set_expire_after(config.saved_searches.expire_after_checks);
+        client.set_request_headers(config.api.request_headers());
+
+        // Before the stores load, so they can read encrypted files
+        encryption::set_cipher(cipher);
+
+        diagnostics::set_config(config);
+        if ephemeral {
+            client.set_cache_dir(None);
+            client.set_history_path(None)?;
+            client.set_bookmarks_path(None)?;
+            client.set_request_counts_path(None)?;
+            diagnostics::set_data_dir(None);
+        } else {
+            client.set_cache_dir(config.cache.disk_dir());
+            client.set_history_path(config.history_path())?;
+            client.set_bookmarks_path(config.bookmarks_path())?;
+            client.set_request_counts_path(config.request_counts_path())?;
+            diagnostics::set_data_dir(config.data_dir());
+        }
+
+        Ok(())
+    }
+
+    fn build_servers(
+        client: &Arc<KiwiClient>,
+        store: &Arc<SearchStore>,
+        config: &Config,
+    ) -> Result<Servers> {
+        let price_format = config.defaults.price_format()?;
+        let mut plan_trip =
+            PlanTripTool::new(client.clone(), store.clone(), config.defaults.clone());
+        if let Some(path) = &config.airline_quality.file {
+            plan_trip = plan_trip.with_airline_quality(Arc::new(FileAirlineQuality::load(path)?));
+        }
+
+        let tools: Vec<Arc<dyn ToolExecutor>> = vec![
+            Arc::new(plan_trip),
+            Arc::new(PlanTripWithStopoverTool::new(
+                client.clone(),
+                config.defaults.clone(),
+            )),
+            Arc::new(PlanOpenJawTripTool::new(
+                client.clone(),
+                config.defaults.clone(),
+            )),
+            Arc::new(PlanTripBudgetTool::new(
+                client.clone(),
+                config.defaults.clone(),
+            )),
+            Arc::new(CompareCabinsTool::new(
+                client.clone(),
+                config.defaults.clone(),
+            )),
+            Arc::new(CompareBaggageFaresTool::new(
+                client.clone(),
+                config.defaults.clone(),
+            )),
+            Arc::new(BestDepartureTimesTool::new(
+                client.clone(),
+                config.defaults.clone(),
+            )),
+            Arc::new(CheapestMonthTool::new(
+                client.clone(),
+                config.defaults.clone(),
+            )),
+            Arc::new(PositioningFlightsTool::new(
+                client.clone(),
+                config.defaults.clone(),
+            )),
+            Arc::new(SplitLongHaulTool::new(
+                client.clone(),
+                config.defaults.clone(),
+            )),
+            Arc::new(RouteStatsTool::new(client.clone(), config.defaults.clone())),
+            Arc::new(DayOfWeekPricesTool::new(
+                client.clone(),
+                config.defaults.clone(),
+            )),
+            Arc::new(HolidaySurchargesTool::new(
+                client.clone(),
+                config.defaults.clone(),
+            )),
+            Arc::new(BookingAdviceTool::new(
+                client.clone(),
+                config.defaults.clone(),
+            )),
+            Arc::new(SavingsFinderTool::new(
+                client.clone(),
+                config.defaults.clone(),
+            )),
+            Arc::new(SearchHistoryTool::new(client.clone(), price_format)),
+            Arc::new(SaveSearchTool::new(
+                client.clone(),
+                store.clone(),
+                price_format,
+            )),
+            Arc::new(RecheckSavedSearchTool::new(client.clone(), price_format)),
+            Arc::new(ListSavedSearchesTool::new(client.clone(), price_format)),
+            Arc::new(CompareSearchesTool::new(store.clone(), price_format)),
+            Arc::new(ExportTripSummaryTool::new(store.clone(), price_format)),
+            Arc::new(SearchAirlinesTool),
+            Arc::new(AirportDetailsTool::new(client.clone())),
+            Arc::new(CityAirportsTool::new(client.clone())),
+            Arc::new(HasDirectFlightsTool::new(client.clone())),
+            Arc::new(UsageStatsTool::new(client.clone())),
+        ];
+        let tools: Vec<Arc<dyn ToolExecutor>> = tools
+            .into_iter()
+            .map(|tool| -> Arc<dyn ToolExecutor> {
+                if SAVES_STATE.contains(&tool.to_tool().name.as_str()) {
+                    tool
+                } else {
+                    Arc::new(DryRunnable::new(tool))
+                }
+            })
+            .collect();
+
+        let admin_tools: Vec<Arc<dyn ToolExecutor>> = vec![
+            Arc::new(RotateApiKeyTool::new(client.clone())),
+            Arc::new(CacheStatsTool::new(client.clone())),
+            Arc::new(CacheClearTool::new(client.clone())),
+            Arc::new(PurgeUserDataTool::new(client.clone(), store.clone())),
+        ];
+
+        // The tool names are only known here, so check them after the rest
+        // of the configuration
+        let names: Vec<String> = tools
+            .iter()
+            .chain(&admin_tools)
+            .map(|tool| tool.to_tool().name)
+            .collect();
+        let configured: [(&str, Vec<&String>); 2] = [
+            (
+                "enabled_tools",
+                config.enabled_tools.iter().flatten().collect(),
+            ),
+            ("tools.timeouts", config.tools.timeouts.keys().collect()),
+        ];
+        for (setting, given) in configured {
+            let unknown: Vec<&str> = given
+                .into_iter()
+                .filter(|name| !names.contains(name))
+                .map(String::as_str)
+                .collect();
+            if !unknown.is_empty() {
+                return Err(anyhow!(
+                    "{}: unknown tools {} (available: {})",
+                    setting,
+                    unknown.join(", "),
+                    names.join(", ")
+                ));
+            }
+        }
+
+        let resources: Vec<Arc<dyn ResourceExecutor>> = vec![
+            Arc::new(HistoryResource::new(client.clone())),
+            Arc::new(RouteGeoJsonResource::new(client.clone(), store.clone())),
+            Arc::new(SavedSearchDigestResource::new(client.clone(), price_format)),
+        ];
+
+        let prompts: Vec<Arc<dyn PromptExecutor>> =
+            vec![Arc::new(StopoverGuidePrompt::new(store.clone()))];
+
+        Ok(Servers {
+            public: Arc::new(Self::build_rpc(
+                client, config, &tools, &resources, &prompts,
+            )?),
+            admin: Arc::new(Self::build_rpc(
+                client,
+                config,
+                tools.iter().chain(&admin_tools),
+                &resources,
+                &prompts,
+            )?),
+        })
+    }
+
+    fn build_rpc<'a>(
+        client: &Arc<KiwiClient>,
+        config: &Config,
+        tools: impl IntoIterator<Item = &'a Arc<dyn ToolExecutor>>,
+        resources: &[Arc<dyn ResourceExecutor>],
+        prompts: &[Arc<dyn PromptExecutor>],
+    ) -> Result<ContextServer> {
+        let resource_registry = Arc::new(ResourceRegistry::default());
+
+        for resource in resources {
+            resource_registry.register(resource.clone());
+        }
+
+        let tool_registry = Arc::new(ToolRegistry::default());
+
+        for tool in tools {
+            if config.is_tool_enabled(&tool.to_tool().name) {
+                let timeout = config.tools.timeout(&tool.to_tool().name);
+                let guarded = Arc::new(Guarded::new(tool.clone(), timeout));
+                let metered = Arc::new(Metered::new(guarded, client.clone()));
+                tool_registry.register(Arc::new(RedactErrors::new(metered)));
+            }
+        }
+
+        let prompt_registry = Arc::new(PromptRegistry::default());
+
+        for prompt in prompts {
+            prompt_registry.register(prompt.clone());
+        }
+
+        ContextServer::builder()
+            .with_server_info((env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")))
+            .with_resources(resource_registry)
+            .with_tools(tool_registry)
+            .with_prompts(prompt_registry)
+            .build()
+    }
+
+    /// Checks the parts of `config` that applying it could fail on: the
+    /// price format, the encryption key and the data files, which must
+    /// open with that key. Returns the cipher to install.
+    fn check(config: &Config, ephemeral: bool) -> Result<Option<Cipher>> {
+        config.defaults.price_format()?;
+        if ephemeral {
+            return config.encryption.cipher(None);
+        }
+
+        let cipher = config.encryption.cipher(config.salt_path().as_deref())?;
+        KiwiClient::check_data_files(
+            config.history_path().as_deref(),
+            config.bookmarks_path().as_deref(),
+            cipher.as_ref(),
+        )?;
+        if let Some(path) = config.request_counts_path() {
+            storage::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+        }
+
+        Ok(cipher)
+    }
+
+    /// Re-reads the configuration file and applies it in place, keeping the
+    /// client connection and the shared API client (and its rate limiter and
+    /// cache).
+    pub fn reload(&self) -> Result<()> {
+        let config = Config::load(self.config_path.as_deref())?;
+        let api_key = config.api_key().ok_or_else(|| anyhow!(MISSING_API_KEY))?;
+
+        // Everything that can fail runs before anything is applied, so a
+        // bad configuration leaves the running one intact
+        let servers = Self::build_servers(&self.client, &self.store, &config)?;
+        let price_format = config.defaults.price_format()?;
+        Self::configure_client(&self.client, &config, self.ephemeral)?;
+
+        self.client.set_api_key(api_key);
+        *self.auth_token.write() = Self::registered_auth_token(&config);
+        *self.servers.write() = servers;
+        self.prefetcher.set_config(config.prefetch.clone());
+        self.prefetcher
+            .set_notifications(config.notifications.clone(), price_format);
+        *self.idle.write() = config.idle.clone();
+
+        Ok(())
+    }
+
+    /// The configured auth token, registered so it is redacted from output.
+    fn registered_auth_token(config: &Config) -> Option<String> {
+        let auth_token = config.auth_token();
+        if let Some(auth_token) = &auth_token {
+            redact::register_secret(auth_token);
+        }
+        auth_token
+    }
+
+    /// Resolves once no request has arrived for the configured idle
+    /// timeout and the server should exit. With the `pause` action it
+    /// stops background work instead and never resolves.
+    pub async fn idle_exit(&self) {
+        loop {
+            tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+
+            let idle = self.idle.read().clone();
+            let Some(timeout) = idle.timeout() else {
+                continue;
+            };
+            if self.last_activity.lock().elapsed() < timeout {
+                continue;
+            }
+
+            match idle.action {
+                IdleAction::Exit => return,
+                IdleAction::Pause if self.prefetcher.is_running() => {
+                    eprintln!("No client activity; pausing background work");
+                    self.prefetcher.stop();
+                }
+                IdleAction::Pause => {}
+            }
+        }
+    }
+
+    /// Stops background work once the client has gone.
+    pub fn shutdown(&self) {
+        self.prefetcher.stop();
+    }
+
+    /// Interim findings of the tool call in flight.
+    pub fn progress(&self) -> &ProgressHub {
+        self.client.progress()
+    }
+
+    /// The bearer token HTTP clients must present for admin access.
+    #[cfg_attr(not(feature = "http"), allow(dead_code))]
+    pub fn auth_token(&self) -> Option<String> {
+        self.auth_token.read().clone()
+    }
+
+    /// Handles a request. A panic while handling it fails only this
+    /// request, with a JSON-RPC internal error, and the server carries on.
+    pub async fn process_request(
+        &self,
+        request: ContextServerRpcRequest,
+        access: Access,
+    ) -> Result<Option<ContextServerRpcResponse>> {
+        let id = request.id.clone();
+        let method = request.method.clone();
+        match AssertUnwindSafe(self.dispatch(request, access))
+            .catch_unwind()
+            .await
+        {
+            Ok(response) => response,
+            // Notifications get no response, even an error
+            Err(_) => {
+                let report = diagnostics::write_report(&format!("panic handling {}", method))
+                    .map(|path| format!(" {}", diagnostics::mention(&path)))
+                    .unwrap_or_default();
+                match id {
+                    Some(id) => Ok(Some(serde_json::from_value(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {
+                            "code": INTERNAL_ERROR,
+                            "message": format!(
+                                "Internal error handling {}; the server is still running.{}",
+                                method, report
+                            )
+                        }
+                    }))?)),
+                    None => Ok(None),
+                }
+            }
+        }
+    }
+
+    async fn dispatch(
+        &self,
+        request: ContextServerRpcRequest,
+        access: Access,
+    ) -> Result<Option<ContextServerRpcResponse>> {
+        *self.last_activity.lock() = Instant::now();
+        if !self.prefetcher.is_running() {
+            self.prefetcher.spawn();
+        }
+
+        // Keep-alive pings need no tools, so answer them here
+        if request.method == "ping" {
+            return Ok(Some(serde_json::from_value(json!({
+                "jsonrpc": "2.0",
+                "id": request.id,
+                "result": {}
+            }))?));
+        }
+
+        let rpc = {
+            let servers = self.servers.read();
+            match access {
+                Access::Public => servers.public.clone(),
+                Access::Admin => servers.admin.clone(),
+            }
+        };
+
+        rpc.handle_incoming_message(request).await
+    }
+}
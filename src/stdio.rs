@@ -0,0 +1,174 @@
+//! Reads newline-delimited JSON-RPC messages from stdin, tolerating what
+//! Windows clients and consoles send: CRLF line endings, byte order marks
+//! and UTF-16 text. Other bytes that aren't UTF-8, such as text in a legacy
+//! console code page, are replaced with U+FFFD rather than decoded.
+
+use std::io::ErrorKind;
+
+use anyhow::Result;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Encoding {
+    /// Guesses the encoding from the first bytes of the stream: a byte order
+    /// mark, or the zero byte ASCII characters have in UTF-16.
+    fn detect(start: &[u8]) -> Self {
+        match start {
+            [0xFF, 0xFE, ..] | [_, 0, ..] => Self::Utf16Le,
+            [0xFE, 0xFF, ..] | [0, _, ..] => Self::Utf16Be,
+            _ => Self::Utf8,
+        }
+    }
+
+    /// Whether `bytes` end with a complete line feed.
+    fn ends_line(self, bytes: &[u8]) -> bool {
+        match self {
+            Self::Utf8 => bytes.ends_with(b"\n"),
+            Self::Utf16Le => bytes.len().is_multiple_of(2) && bytes.ends_with(&[b'\n', 0]),
+            Self::Utf16Be => bytes.len().is_multiple_of(2) && bytes.ends_with(&[0, b'\n']),
+        }
+    }
+
+    /// Text that isn't valid in the encoding is replaced with U+FFFD, so a
+    /// garbled message is reported as unparseable instead of ending the
+    /// session.
+    fn decode(self, bytes: &[u8]) -> String {
+        let units = |to_u16: fn([u8; 2]) -> u16| {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|pair| to_u16([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        };
+
+        match self {
+            Self::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            Self::Utf16Le => units(u16::from_le_bytes),
+            Self::Utf16Be => units(u16::from_be_bytes),
+        }
+    }
+}
+
+pub struct MessageReader<R> {
+    reader: BufReader<R>,
+    /// Detected from the first line.
+    encoding: Option<Encoding>,
+    /// The line being read, kept here so a cancelled read loses nothing.
+    partial: Vec<u8>,
+}
+
+impl<R: AsyncRead + Unpin> MessageReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            encoding: None,
+            partial: Vec::new(),
+        }
+    }
+
+    /// The next non-blank line, without its line ending or a byte order
+    /// mark, or `None` once the client closes the stream. Cancel safe: a
+    /// line cut off by another `select!` branch is finished by the next
+    /// call.
+    pub async fn next_line(&mut self) -> Result<Option<String>> {
+        loop {
+            let Some((bytes, encoding)) = self.read_line().await? else {
+                return Ok(None);
+            };
+
+            let line = encoding.decode(&bytes);
+            let line = line
+                .trim_start_matches('\u{FEFF}')
+                .trim_end_matches(['\r', '\n']);
+            if !line.trim().is_empty() {
+                return Ok(Some(line.to_string()));
+            }
+        }
+    }
+
+    async fn read_line(&mut self) -> Result<Option<(Vec<u8>, Encoding)>> {
+        // In UTF-16 a 0x0A byte may be half of another character, and in
+        // little-endian the line feed's second byte is still unread
+        let encoding = loop {
+            let read = if self.encoding == Some(Encoding::Utf16Le)
+                && !self.partial.len().is_multiple_of(2)
+            {
+                match self.reader.read_u8().await {
+                    Ok(byte) => {
+                        self.partial.push(byte);
+                        1
+                    }
+                    Err(err) if err.kind() == ErrorKind::UnexpectedEof => 0,
+                    Err(err) => return Err(err.into()),
+                }
+            } else {
+                self.reader.read_until(b'\n', &mut self.partial).await?
+            };
+
+            if read == 0 && self.partial.is_empty() {
+                return Ok(None);
+            }
+            let encoding = *self
+                .encoding
+                .get_or_insert_with(|| Encoding::detect(&self.partial));
+            if read == 0 || encoding.ends_line(&self.partial) {
+                break encoding;
+            }
+        };
+
+        Ok(Some((std::mem::take(&mut self.partial), encoding)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn lines(input: &[u8]) -> Vec<String> {
+        let mut reader = MessageReader::new(input);
+        let mut lines = Vec::new();
+        while let Some(line) = reader.next_line().await.unwrap() {
+            lines.push(line);
+        }
+        lines
+    }
+
+    fn utf16(text: &str, to_bytes: fn(u16) -> [u8; 2]) -> Vec<u8> {
+        text.encode_utf16().flat_map(to_bytes).collect()
+    }
+
+    #[tokio::test]
+    async fn reads_lf_and_crlf_lines() {
+        assert_eq!(
+            lines(b"{\"id\":1}\r\n\r\n{\"id\":2}\n{\"id\":3}").await,
+            ["{\"id\":1}", "{\"id\":2}", "{\"id\":3}"]
+        );
+    }
+
+    #[tokio::test]
+    async fn strips_a_byte_order_mark() {
+        assert_eq!(lines("\u{FEFF}{}\n".as_bytes()).await, ["{}"]);
+    }
+
+    #[tokio::test]
+    async fn decodes_utf16() {
+        // U+010A has a 0x0A byte that isn't a line feed
+        let text = "\u{FEFF}{\"city\":\"Ċ→Zürich\"}\r\n{}\r\n";
+        let expected = ["{\"city\":\"Ċ→Zürich\"}", "{}"];
+
+        assert_eq!(lines(&utf16(text, u16::to_le_bytes)).await, expected);
+        assert_eq!(lines(&utf16(text, u16::to_be_bytes)).await, expected);
+        assert_eq!(lines(&utf16(&text[3..], u16::to_le_bytes)).await, expected);
+    }
+
+    #[tokio::test]
+    async fn replaces_invalid_utf8() {
+        assert_eq!(lines(b"caf\xe9\n").await, ["caf\u{FFFD}"]);
+    }
+}
@@ -0,0 +1,224 @@
+//! Development tasks, run with `cargo xtask <task>`.
+//!
+//! - `codegen`: regenerates the Tequila API models from `openapi/tequila.json`.
+//! - `codegen --check`: fails if the checked-in models are out of date.
+
+use std::{
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use anyhow::{Context, Result, anyhow};
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+const SPEC: &str = "openapi/tequila.json";
+const OUTPUT: &str = "crates/kiwi_mcp_tools/src/model/generated.rs";
+
+fn main() -> Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["codegen"] => codegen(false),
+        ["codegen", "--check"] => codegen(true),
+        _ => Err(anyhow!("Usage: cargo xtask codegen [--check]")),
+    }
+}
+
+fn codegen(check: bool) -> Result<()> {
+    let root = workspace_root();
+    let spec: Spec = serde_json::from_str(
+        &fs::read_to_string(root.join(SPEC)).with_context(|| format!("Failed to read {}", SPEC))?,
+    )
+    .with_context(|| format!("Failed to parse {}", SPEC))?;
+
+    let generated = rustfmt(&generate(&spec)?)?;
+    let output = root.join(OUTPUT);
+
+    if check {
+        let current = fs::read_to_string(&output).unwrap_or_default();
+        if current != generated {
+            return Err(anyhow!(
+                "{} is out of date; run `cargo xtask codegen`",
+                OUTPUT
+            ));
+        }
+        println!("{} is up to date", OUTPUT);
+        return Ok(());
+    }
+
+    fs::write(&output, generated).with_context(|| format!("Failed to write {}", OUTPUT))?;
+    println!("Wrote {}", OUTPUT);
+    Ok(())
+}
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask lives in the workspace root")
+        .to_path_buf()
+}
+
+/// The parts of an OpenAPI 3.0 document the generator reads.
+#[derive(Deserialize)]
+struct Spec {
+    components: Components,
+}
+
+#[derive(Deserialize)]
+struct Components {
+    schemas: IndexMap<String, Schema>,
+}
+
+#[derive(Deserialize)]
+struct Schema {
+    #[serde(rename = "type")]
+    type_: Option<String>,
+    format: Option<String>,
+    #[serde(rename = "$ref")]
+    reference: Option<String>,
+    #[serde(default)]
+    nullable: bool,
+    description: Option<String>,
+    #[serde(default)]
+    properties: IndexMap<String, Schema>,
+    items: Option<Box<Schema>>,
+    #[serde(rename = "additionalProperties")]
+    additional_properties: Option<Box<Schema>>,
+    /// Field name to use when the property's own isn't valid Rust.
+    #[serde(rename = "x-rust-name")]
+    rust_name: Option<String>,
+}
+
+fn generate(spec: &Spec) -> Result<String> {
+    let mut out = String::from(
+        "// @generated by `cargo xtask codegen` from openapi/tequila.json; edit the spec, not this file.\n\n\
+         use std::collections::HashMap;\n\n\
+         use chrono::{DateTime, Utc};\n\
+         use serde::{Deserialize, Serialize};\n",
+    );
+
+    for (name, schema) in &spec.components.schemas {
+        out.push('\n');
+        push_doc(&mut out, schema.description.as_deref(), "");
+        out.push_str("#[derive(Debug, Clone, Default, Serialize, Deserialize)]\n");
+        out.push_str("#[serde(default)]\n");
+        out.push_str(&format!("pub struct {} {{\n", name));
+
+        for (property, field) in &schema.properties {
+            let rust_name = field
+                .rust_name
+                .clone()
+                .unwrap_or_else(|| snake_case(property));
+            push_doc(&mut out, field.description.as_deref(), "    ");
+            if rust_name != *property {
+                out.push_str(&format!("    #[serde(rename = \"{}\")]\n", property));
+            }
+            let ty = rust_type(field).with_context(|| format!("{}.{}", name, property))?;
+            let ty = if field.nullable {
+                format!("Option<{}>", ty)
+            } else {
+                ty
+            };
+            out.push_str(&format!("    pub {}: {},\n", rust_name, ty));
+        }
+
+        out.push_str("}\n");
+    }
+
+    Ok(out)
+}
+
+fn rust_type(schema: &Schema) -> Result<String> {
+    if let Some(reference) = &schema.reference {
+        return reference
+            .strip_prefix("#/components/schemas/")
+            .map(String::from)
+            .ok_or_else(|| anyhow!("Unsupported $ref {}", reference));
+    }
+
+    let ty = match (schema.type_.as_deref(), schema.format.as_deref()) {
+        (Some("string"), Some("date-time")) => "DateTime<Utc>".to_string(),
+        (Some("string"), _) => "String".to_string(),
+        (Some("number"), _) => "f64".to_string(),
+        (Some("integer"), Some("uint64")) => "u64".to_string(),
+        (Some("integer"), Some("uint8")) => "u8".to_string(),
+        (Some("integer"), _) => "i64".to_string(),
+        (Some("boolean"), _) => "bool".to_string(),
+        (Some("array"), _) => {
+            let items = schema
+                .items
+                .as_ref()
+                .ok_or_else(|| anyhow!("Array without items"))?;
+            format!("Vec<{}>", rust_type(items)?)
+        }
+        (Some("object"), _) => {
+            let values = schema
+                .additional_properties
+                .as_ref()
+                .ok_or_else(|| anyhow!("Inline objects need additionalProperties"))?;
+            format!("HashMap<String, {}>", rust_type(values)?)
+        }
+        (ty, _) => return Err(anyhow!("Unsupported type {:?}", ty)),
+    };
+    Ok(ty)
+}
+
+/// `flyFrom` becomes `fly_from`.
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            out.push('_');
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn push_doc(out: &mut String, description: Option<&str>, indent: &str) {
+    let Some(description) = description else {
+        return;
+    };
+
+    let mut line = String::new();
+    for word in description.split_whitespace() {
+        if !line.is_empty() && indent.len() + 4 + line.len() + 1 + word.len() > 80 {
+            out.push_str(&format!("{}/// {}\n", indent, line));
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    out.push_str(&format!("{}/// {}\n", indent, line));
+}
+
+fn rustfmt(source: &str) -> Result<String> {
+    let mut child = Command::new("rustfmt")
+        .args(["--edition", "2024", "--emit", "stdout"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("Failed to run rustfmt")?;
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(source.as_bytes())?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow!("rustfmt failed on the generated code"));
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}